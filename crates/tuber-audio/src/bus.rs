@@ -0,0 +1,357 @@
+//! Mixing buses grouping the sounds whose volume and effects are controlled
+//! together, and snapshots transitioning the settings of several buses.
+
+use std::collections::HashMap;
+
+use crate::effect::Effect;
+
+pub const MASTER_BUS: &str = "master";
+pub const MUSIC_BUS: &str = "music";
+pub const SFX_BUS: &str = "sfx";
+pub const UI_BUS: &str = "ui";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BusSettings {
+    pub volume: f32,
+    pub muted: bool,
+    /// The effects applied to the sounds of the bus, in order
+    pub effects: Vec<Box<dyn Effect>>,
+}
+
+impl Default for BusSettings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            effects: vec![],
+        }
+    }
+}
+
+impl BusSettings {
+    #[must_use]
+    pub fn with_effect(mut self, effect: impl Effect + 'static) -> Self {
+        self.effects.push(Box::new(effect));
+        self
+    }
+
+    fn gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+/// The settings of several buses, applied at once, e.g. to muffle the game
+/// sounds while the pause menu is opened. Buses absent from the snapshot keep
+/// their settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MixerSnapshot {
+    pub buses: HashMap<String, BusSettings>,
+}
+
+impl MixerSnapshot {
+    #[must_use]
+    pub fn with_bus(mut self, bus: &str, settings: BusSettings) -> Self {
+        self.buses.insert(bus.into(), settings);
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Transition {
+    from_gain: f32,
+    /// The parameters of the effects of the chain when the transition started
+    from_parameters: Vec<Vec<f32>>,
+    /// The effects absent from the new settings, faded to their neutral
+    /// parameters before being dropped
+    removed_effects: Vec<(Box<dyn Effect>, Vec<f32>)>,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Transition {
+    fn progress(&self) -> f32 {
+        self.elapsed / self.duration
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Bus {
+    pub(crate) name: String,
+    sample_rate: u32,
+    settings: BusSettings,
+    transition: Option<Transition>,
+    /// The effects processing the buffer, kept across the settings changes so
+    /// their state carries over
+    effects: Vec<Box<dyn Effect>>,
+    pub(crate) buffer: Vec<f32>,
+}
+
+impl Bus {
+    pub(crate) fn new(name: &str, settings: BusSettings, sample_rate: u32) -> Self {
+        Self {
+            name: name.into(),
+            sample_rate,
+            effects: settings.effects.clone(),
+            settings,
+            transition: None,
+            buffer: vec![],
+        }
+    }
+
+    pub(crate) fn settings(&self) -> BusSettings {
+        self.settings.clone()
+    }
+
+    /// Changes the settings of the bus, progressively if a duration in
+    /// seconds is given. The effects of the new settings replace the ones at
+    /// the same position in the chain if they have the same name, the others
+    /// are faded in or out.
+    pub(crate) fn set_settings(&mut self, settings: BusSettings, duration: f32) {
+        let from_gain = self.current_gain();
+        let mut previous_effects: Vec<Option<Box<dyn Effect>>> =
+            self.effects.drain(..).map(Some).collect();
+        let mut from_parameters = Vec::with_capacity(settings.effects.len());
+        for (index, effect) in settings.effects.iter().enumerate() {
+            let previous_effect = previous_effects
+                .get_mut(index)
+                .filter(|previous_effect| {
+                    previous_effect
+                        .as_ref()
+                        .is_some_and(|previous_effect| previous_effect.name() == effect.name())
+                })
+                .and_then(Option::take);
+            if let Some(mut previous_effect) = previous_effect {
+                from_parameters.push(previous_effect.parameters());
+                previous_effect.set_parameters(&effect.parameters());
+                self.effects.push(previous_effect);
+            } else {
+                from_parameters.push(effect.neutral_parameters(self.sample_rate));
+                self.effects.push(effect.clone());
+            }
+        }
+
+        let previous_transition = self.transition.take();
+        self.settings = settings;
+        if duration <= 0.0 {
+            return;
+        }
+
+        let mut removed_effects: Vec<(Box<dyn Effect>, Vec<f32>)> = previous_effects
+            .into_iter()
+            .flatten()
+            .map(|effect| {
+                let parameters = effect.parameters();
+                (effect, parameters)
+            })
+            .collect();
+        if let Some(previous_transition) = previous_transition {
+            removed_effects.extend(previous_transition.removed_effects.into_iter().map(
+                |(effect, _)| {
+                    let parameters = effect.parameters();
+                    (effect, parameters)
+                },
+            ));
+        }
+
+        self.transition = Some(Transition {
+            from_gain,
+            from_parameters,
+            removed_effects,
+            duration,
+            elapsed: 0.0,
+        });
+    }
+
+    pub(crate) fn clear_buffer(&mut self, length: usize) {
+        self.buffer.clear();
+        self.buffer.resize(length, 0.0);
+    }
+
+    /// Applies the effects and the volume of the bus to its buffer
+    pub(crate) fn process(&mut self) {
+        self.interpolate_effect_parameters();
+        for effect in &mut self.effects {
+            effect.process(&mut self.buffer, self.sample_rate);
+        }
+        if let Some(transition) = &mut self.transition {
+            for (effect, _) in &mut transition.removed_effects {
+                effect.process(&mut self.buffer, self.sample_rate);
+            }
+        }
+
+        let gain = self.current_gain();
+        for sample in &mut self.buffer {
+            *sample *= gain;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let processed_duration = (self.buffer.len() / 2) as f32 / self.sample_rate as f32;
+        if let Some(transition) = &mut self.transition {
+            transition.elapsed += processed_duration;
+            if transition.elapsed >= transition.duration {
+                self.transition = None;
+                for (effect, settings_effect) in self.effects.iter_mut().zip(&self.settings.effects)
+                {
+                    effect.set_parameters(&settings_effect.parameters());
+                }
+            }
+        }
+    }
+
+    /// Sets the parameters of the effects to the current point of the
+    /// transition
+    fn interpolate_effect_parameters(&mut self) {
+        let Some(transition) = &mut self.transition else {
+            return;
+        };
+
+        let progress = transition.progress();
+        for ((effect, from), settings_effect) in self
+            .effects
+            .iter_mut()
+            .zip(&transition.from_parameters)
+            .zip(&self.settings.effects)
+        {
+            effect.set_parameters(&lerp_parameters(
+                from,
+                &settings_effect.parameters(),
+                progress,
+            ));
+        }
+        for (effect, from) in &mut transition.removed_effects {
+            let to = effect.neutral_parameters(self.sample_rate);
+            effect.set_parameters(&lerp_parameters(from, &to, progress));
+        }
+    }
+
+    /// Returns the gain of the bus at the current point of its transition
+    fn current_gain(&self) -> f32 {
+        match &self.transition {
+            Some(transition) => lerp(
+                transition.from_gain,
+                self.settings.gain(),
+                transition.progress(),
+            ),
+            None => self.settings.gain(),
+        }
+    }
+}
+
+fn lerp(from: f32, to: f32, progress: f32) -> f32 {
+    from + (to - from) * progress.clamp(0.0, 1.0)
+}
+
+/// Interpolates the parameters of an effect, parameters absent from the
+/// starting ones are set directly
+fn lerp_parameters(from: &[f32], to: &[f32], progress: f32) -> Vec<f32> {
+    to.iter()
+        .enumerate()
+        .map(|(index, &to)| from.get(index).map_or(to, |&from| lerp(from, to, progress)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effect::LowPass;
+
+    #[test]
+    fn process_volume_and_mute() {
+        let mut bus = Bus::new(SFX_BUS, BusSettings::default(), 1);
+        bus.set_settings(
+            BusSettings {
+                volume: 0.5,
+                ..Default::default()
+            },
+            0.0,
+        );
+        bus.buffer = vec![1.0, 1.0];
+
+        bus.process();
+        assert!((bus.buffer[0] - 0.5).abs() < 0.001);
+
+        bus.set_settings(
+            BusSettings {
+                muted: true,
+                ..Default::default()
+            },
+            0.0,
+        );
+        bus.buffer = vec![1.0, 1.0];
+        bus.process();
+        assert!(bus.buffer[0].abs() < 0.001);
+    }
+
+    #[test]
+    fn transition() {
+        let mut bus = Bus::new(MUSIC_BUS, BusSettings::default(), 1);
+        bus.set_settings(
+            BusSettings {
+                volume: 0.0,
+                ..Default::default()
+            },
+            2.0,
+        );
+
+        bus.buffer = vec![1.0; 2];
+        bus.process();
+        assert!((bus.buffer[0] - 1.0).abs() < 0.001);
+        bus.buffer = vec![1.0; 2];
+        bus.process();
+        assert!((bus.buffer[0] - 0.5).abs() < 0.001);
+        bus.buffer = vec![1.0; 2];
+        bus.process();
+        assert!(bus.buffer[0].abs() < 0.001);
+    }
+
+    #[test]
+    fn transition_effect_parameters() {
+        let mut bus = Bus::new(MUSIC_BUS, BusSettings::default(), 4);
+        bus.set_settings(BusSettings::default().with_effect(LowPass::new(1.0)), 2.0);
+
+        bus.buffer = vec![0.0; 4];
+        bus.process();
+        assert_eq!(bus.effects[0].parameters(), vec![2.0]);
+        bus.buffer = vec![0.0; 4];
+        bus.process();
+        assert_eq!(bus.effects[0].parameters(), vec![1.75]);
+
+        bus.set_settings(BusSettings::default().with_effect(LowPass::new(0.5)), 2.0);
+        bus.buffer = vec![0.0; 4];
+        bus.process();
+        assert_eq!(bus.effects[0].parameters(), vec![1.75]);
+        bus.buffer = vec![0.0; 4];
+        bus.process();
+        assert_eq!(bus.effects[0].parameters(), vec![1.4375]);
+        bus.buffer = vec![0.0; 8];
+        bus.process();
+        assert!(bus.transition.is_none());
+        assert_eq!(bus.effects[0].parameters(), vec![0.5]);
+    }
+
+    #[test]
+    fn transition_removed_effect() {
+        let mut bus = Bus::new(
+            MUSIC_BUS,
+            BusSettings::default().with_effect(LowPass::new(1.0)),
+            4,
+        );
+        bus.set_settings(BusSettings::default(), 2.0);
+        assert!(bus.effects.is_empty());
+
+        bus.buffer = vec![0.0; 4];
+        bus.process();
+        bus.buffer = vec![0.0; 4];
+        bus.process();
+        let removed_effect = &bus.transition.as_ref().unwrap().removed_effects[0].0;
+        assert_eq!(removed_effect.parameters(), vec![1.25]);
+
+        bus.buffer = vec![0.0; 8];
+        bus.process();
+        assert!(bus.transition.is_none());
+    }
+}
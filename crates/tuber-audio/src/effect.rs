@@ -0,0 +1,133 @@
+//! Effects applied in chain to the sounds of a bus.
+
+use std::f32::consts::PI;
+use std::fmt::Debug;
+
+/// An effect processing the samples of a bus. Its parameters are interpolated
+/// when a snapshot transitions the bus, between the effects of a same name at
+/// the same position of both chains.
+pub trait Effect: Debug + Send {
+    fn name(&self) -> &'static str;
+
+    /// Processes interleaved stereo samples in place
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32);
+
+    fn parameters(&self) -> Vec<f32>;
+
+    fn set_parameters(&mut self, parameters: &[f32]);
+
+    /// Returns the parameters leaving the samples unchanged, an effect added or
+    /// removed by a transition is faded from or to them
+    fn neutral_parameters(&self, sample_rate: u32) -> Vec<f32>;
+
+    fn clone_effect(&self) -> Box<dyn Effect>;
+}
+
+impl Clone for Box<dyn Effect> {
+    fn clone(&self) -> Self {
+        self.clone_effect()
+    }
+}
+
+impl PartialEq for dyn Effect {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name() && self.parameters() == other.parameters()
+    }
+}
+
+/// A one-pole low-pass filter, letting the samples through unchanged when its
+/// cutoff reaches the Nyquist frequency
+#[derive(Debug, Clone, PartialEq)]
+pub struct LowPass {
+    /// The cutoff frequency, in Hz
+    pub cutoff: f32,
+    /// The last output of the filter for both speakers
+    state: [f32; 2],
+}
+
+impl LowPass {
+    #[must_use]
+    pub fn new(cutoff: f32) -> Self {
+        Self {
+            cutoff,
+            state: [0.0; 2],
+        }
+    }
+}
+
+impl Effect for LowPass {
+    fn name(&self) -> &'static str {
+        "low_pass"
+    }
+
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32) {
+        #[allow(clippy::cast_precision_loss)]
+        let sample_rate = sample_rate as f32;
+        if self.cutoff >= sample_rate / 2.0 {
+            if let Some(frame) = samples.rchunks_exact(2).next() {
+                self.state.copy_from_slice(frame);
+            }
+            return;
+        }
+
+        let rc = 1.0 / (2.0 * PI * self.cutoff.max(0.0));
+        let dt = 1.0 / sample_rate;
+        let alpha = dt / (rc + dt);
+        for frame in samples.chunks_exact_mut(2) {
+            for (sample, state) in frame.iter_mut().zip(&mut self.state) {
+                *state += alpha * (*sample - *state);
+                *sample = *state;
+            }
+        }
+    }
+
+    fn parameters(&self) -> Vec<f32> {
+        vec![self.cutoff]
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+        if let Some(&cutoff) = parameters.first() {
+            self.cutoff = cutoff;
+        }
+    }
+
+    fn neutral_parameters(&self, sample_rate: u32) -> Vec<f32> {
+        #[allow(clippy::cast_precision_loss)]
+        let nyquist_frequency = sample_rate as f32 / 2.0;
+        vec![nyquist_frequency]
+    }
+
+    fn clone_effect(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn low_pass() {
+        let mut low_pass = LowPass::new(100.0);
+        let mut samples = vec![1.0; 8];
+
+        low_pass.process(&mut samples, 44100);
+
+        assert!(samples[0] < 0.1);
+        assert!(samples[6] > samples[0]);
+        assert!(samples[6] < 1.0);
+    }
+
+    #[test]
+    fn low_pass_at_nyquist_frequency() {
+        let mut low_pass = LowPass::new(100.0);
+        low_pass.set_parameters(&low_pass.neutral_parameters(44100));
+        let mut samples = vec![1.0, 0.5, 0.25, 0.0];
+
+        low_pass.process(&mut samples, 44100);
+
+        assert_eq!(samples, vec![1.0, 0.5, 0.25, 0.0]);
+        assert!((low_pass.state[0] - 0.25).abs() < f32::EPSILON);
+        assert!(low_pass.state[1].abs() < f32::EPSILON);
+    }
+}
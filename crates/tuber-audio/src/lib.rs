@@ -0,0 +1,357 @@
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemBundle;
+
+use bus::{BusSettings, MixerSnapshot, MASTER_BUS, MUSIC_BUS, SFX_BUS, UI_BUS};
+use effect::Effect;
+use mixer::{Mixer, Playback, PlaybackId};
+use sink::{AudioSink, NullSink};
+use sound::Sound;
+
+pub mod bus;
+pub mod effect;
+pub mod mixer;
+pub mod ogg;
+pub mod sink;
+pub mod sound;
+pub mod spatial;
+pub mod wav;
+
+const DEFAULT_SAMPLE_RATE: u32 = 44100;
+
+pub type AudioResult<T> = Result<T, AudioError>;
+
+#[derive(Debug, Clone)]
+pub enum AudioError {
+    WavParseError(String),
+    OggParseError(String),
+    UnsupportedWavFormat {
+        audio_format: u16,
+        bits_per_sample: u16,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioSettings {
+    pub sample_rate: u32,
+    /// The buses sounds can be played on, the master bus is always created
+    pub buses: HashMap<String, BusSettings>,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            sample_rate: DEFAULT_SAMPLE_RATE,
+            buses: [MASTER_BUS, MUSIC_BUS, SFX_BUS, UI_BUS]
+                .into_iter()
+                .map(|bus| (bus.to_string(), BusSettings::default()))
+                .collect(),
+        }
+    }
+}
+
+/// Returns the systems positioning the sounds played by the given mixer
+#[must_use]
+pub fn default_system_bundle<AD: 'static>(mixer: Arc<Mutex<Mixer>>) -> SystemBundle<AD> {
+    let mut system_bundle = SystemBundle::default();
+    system_bundle.add_system(move |ecs: &mut Ecs, _: &mut AD| {
+        spatial::update_audio_sources(ecs, &mut mixer.lock().unwrap())
+    });
+    system_bundle
+}
+
+/// The playback API. The samples of the playing sounds are produced by the
+/// mixer and sent to the audio sink every frame by [`Audio::update`].
+pub struct Audio {
+    mixer: Arc<Mutex<Mixer>>,
+    sink: Box<dyn AudioSink>,
+    /// The fraction of a frame elapsed but not mixed yet
+    pending_frames: f64,
+    buffer: Vec<f32>,
+    next_playback_id: u64,
+    music: Option<PlaybackId>,
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new(&AudioSettings::default())
+    }
+}
+
+impl Audio {
+    #[must_use]
+    pub fn new(settings: &AudioSettings) -> Self {
+        Self {
+            mixer: Arc::new(Mutex::new(Mixer::new(settings))),
+            sink: Box::new(NullSink),
+            pending_frames: 0.0,
+            buffer: vec![],
+            next_playback_id: 0,
+            music: None,
+        }
+    }
+
+    /// Sets the sink the mixed samples are sent to, the samples are discarded
+    /// by default
+    pub fn set_sink(&mut self, sink: Box<dyn AudioSink>) {
+        self.sink = sink;
+    }
+
+    /// Mixes the samples played during the elapsed time in seconds and sends
+    /// them to the sink
+    pub fn update(&mut self, delta_time: f64) {
+        let mut mixer = self.mixer.lock().unwrap();
+        self.pending_frames += delta_time.max(0.0) * f64::from(mixer.sample_rate());
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let elapsed_frames = self.pending_frames as usize;
+        #[allow(clippy::cast_precision_loss)]
+        {
+            self.pending_frames -= elapsed_frames as f64;
+        }
+
+        let frames = self.sink.requested_frames(elapsed_frames);
+        if frames == 0 {
+            return;
+        }
+
+        self.buffer.clear();
+        self.buffer.resize(frames * 2, 0.0);
+        mixer.mix(&mut self.buffer);
+        self.sink.write(&self.buffer);
+    }
+
+    /// Returns the mixer the audio output pulls samples from
+    #[must_use]
+    pub fn mixer(&self) -> Arc<Mutex<Mixer>> {
+        self.mixer.clone()
+    }
+
+    /// Plays a sound on the sound effects bus
+    pub fn play_sound(&mut self, sound: &Sound) -> PlaybackId {
+        self.play(sound, SFX_BUS, false)
+    }
+
+    pub fn play_sound_on_bus(&mut self, sound: &Sound, bus: &str) -> PlaybackId {
+        self.play(sound, bus, false)
+    }
+
+    /// Plays a music on the music bus, replacing the one currently playing
+    pub fn play_music(&mut self, sound: &Sound, looped: bool) -> PlaybackId {
+        self.stop_music();
+        let id = self.play(sound, MUSIC_BUS, looped);
+        self.music = Some(id);
+        id
+    }
+
+    pub fn stop_music(&mut self) {
+        if let Some(music) = self.music.take() {
+            self.stop(music);
+        }
+    }
+
+    pub fn pause(&self, id: PlaybackId) {
+        if let Some(playback) = self.mixer.lock().unwrap().playback_mut(id) {
+            playback.paused = true;
+        }
+    }
+
+    pub fn resume(&self, id: PlaybackId) {
+        if let Some(playback) = self.mixer.lock().unwrap().playback_mut(id) {
+            playback.paused = false;
+        }
+    }
+
+    pub fn stop(&self, id: PlaybackId) {
+        self.mixer
+            .lock()
+            .unwrap()
+            .playbacks
+            .retain(|playback| playback.id != id);
+    }
+
+    /// Returns true if the sound hasn't ended or been stopped, even if it is
+    /// paused
+    #[must_use]
+    pub fn is_playing(&self, id: PlaybackId) -> bool {
+        self.mixer.lock().unwrap().is_playing(id)
+    }
+
+    pub fn add_bus(&self, bus: &str, settings: BusSettings) {
+        self.mixer.lock().unwrap().add_bus(bus, settings);
+    }
+
+    #[must_use]
+    pub fn bus_settings(&self, bus: &str) -> Option<BusSettings> {
+        self.mixer.lock().unwrap().bus_settings(bus)
+    }
+
+    pub fn set_bus_volume(&self, bus: &str, volume: f32) {
+        self.update_bus_settings(bus, |settings| settings.volume = volume.max(0.0));
+    }
+
+    pub fn set_bus_muted(&self, bus: &str, muted: bool) {
+        self.update_bus_settings(bus, |settings| settings.muted = muted);
+    }
+
+    /// Replaces the chain of effects applied to the sounds of the bus
+    pub fn set_bus_effects(&self, bus: &str, effects: Vec<Box<dyn Effect>>) {
+        self.update_bus_settings(bus, |settings| settings.effects = effects);
+    }
+
+    /// Transitions the buses to the settings of the snapshot over the given
+    /// duration in seconds
+    pub fn apply_snapshot(&self, snapshot: &MixerSnapshot, duration: f32) {
+        self.mixer
+            .lock()
+            .unwrap()
+            .apply_snapshot(snapshot, duration);
+    }
+
+    fn update_bus_settings(&self, bus: &str, update: impl FnOnce(&mut BusSettings)) {
+        let mut mixer = self.mixer.lock().unwrap();
+        let mut settings = mixer.bus_settings(bus).unwrap_or_default();
+        update(&mut settings);
+        mixer.set_bus_settings(bus, settings, 0.0);
+    }
+
+    fn play(&mut self, sound: &Sound, bus: &str, looped: bool) -> PlaybackId {
+        let id = PlaybackId(self.next_playback_id);
+        self.next_playback_id += 1;
+        self.mixer
+            .lock()
+            .unwrap()
+            .playbacks
+            .push(Playback::new(id, sound.clone(), bus, looped));
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use effect::LowPass;
+
+    fn sound() -> Sound {
+        Sound::new(4, 1, vec![0.5; 4])
+    }
+
+    fn audio(sample_rate: u32) -> Audio {
+        Audio::new(&AudioSettings {
+            sample_rate,
+            ..Default::default()
+        })
+    }
+
+    fn mix(audio: &Audio, frames: usize) -> Vec<f32> {
+        let mut output = vec![0.0; frames * 2];
+        audio.mixer().lock().unwrap().mix(&mut output);
+        output
+    }
+
+    #[derive(Clone, Default)]
+    struct BufferSink(Arc<Mutex<Vec<f32>>>);
+
+    impl AudioSink for BufferSink {
+        fn write(&mut self, samples: &[f32]) {
+            self.0.lock().unwrap().extend_from_slice(samples);
+        }
+    }
+
+    #[test]
+    fn update_sends_samples_to_sink() {
+        let mut audio = audio(4);
+        let sink = BufferSink::default();
+        audio.set_sink(Box::new(sink.clone()));
+        let id = audio.play_sound(&sound());
+
+        audio.update(0.375);
+        assert_eq!(*sink.0.lock().unwrap(), vec![0.5; 2]);
+        audio.update(0.375);
+        assert_eq!(sink.0.lock().unwrap().len(), 6);
+        assert!(audio.is_playing(id));
+
+        audio.update(0.5);
+        assert_eq!(sink.0.lock().unwrap().len(), 10);
+        assert!(!audio.is_playing(id));
+    }
+
+    #[test]
+    fn play_sound() {
+        let mut audio = audio(4);
+
+        let id = audio.play_sound(&sound());
+
+        assert!(audio.is_playing(id));
+        assert_eq!(mix(&audio, 1), vec![0.5, 0.5]);
+        mix(&audio, 4);
+        assert!(!audio.is_playing(id));
+    }
+
+    #[test]
+    fn play_music_replaces_music() {
+        let mut audio = audio(4);
+        let first_music = audio.play_music(&sound(), true);
+        let sound_effect = audio.play_sound(&sound());
+
+        let second_music = audio.play_music(&sound(), true);
+
+        assert!(!audio.is_playing(first_music));
+        assert!(audio.is_playing(second_music));
+        assert!(audio.is_playing(sound_effect));
+    }
+
+    #[test]
+    fn pause_resume() {
+        let mut audio = audio(4);
+        let id = audio.play_sound(&sound());
+
+        audio.pause(id);
+        assert_eq!(mix(&audio, 8), vec![0.0; 16]);
+        assert!(audio.is_playing(id));
+        audio.resume(id);
+
+        assert_eq!(mix(&audio, 1), vec![0.5, 0.5]);
+    }
+
+    #[test]
+    fn bus_settings() {
+        let mut audio = audio(4);
+        audio.play_music(&sound(), false);
+        audio.play_sound_on_bus(&sound(), UI_BUS);
+
+        audio.set_bus_volume(MUSIC_BUS, 0.5);
+        audio.set_bus_muted(UI_BUS, true);
+        audio.set_bus_effects(SFX_BUS, vec![Box::new(LowPass::new(500.0))]);
+
+        assert!((audio.bus_settings(MUSIC_BUS).unwrap().volume - 0.5).abs() < f32::EPSILON);
+        assert_eq!(
+            audio.bus_settings(SFX_BUS).unwrap().effects[0].parameters(),
+            vec![500.0]
+        );
+        assert_eq!(mix(&audio, 1), vec![0.25, 0.25]);
+    }
+
+    #[test]
+    fn add_bus() {
+        let mut audio = audio(4);
+        audio.add_bus(
+            "voices",
+            BusSettings {
+                volume: 0.5,
+                ..Default::default()
+            },
+        );
+
+        audio.play_sound_on_bus(&sound(), "voices");
+
+        assert_eq!(mix(&audio, 1), vec![0.25, 0.25]);
+    }
+}
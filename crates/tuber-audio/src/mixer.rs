@@ -0,0 +1,373 @@
+//! Mixing of the playing sounds into the stereo samples sent to the audio
+//! device.
+
+use log::warn;
+
+use crate::bus::{Bus, BusSettings, MixerSnapshot, MASTER_BUS};
+use crate::sound::Sound;
+use crate::AudioSettings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlaybackId(pub(crate) u64);
+
+#[derive(Debug, Clone)]
+pub(crate) struct Playback {
+    pub(crate) id: PlaybackId,
+    pub(crate) sound: Sound,
+    pub(crate) bus: String,
+    /// The position in the sound, in frames
+    pub(crate) position: f64,
+    pub(crate) looped: bool,
+    pub(crate) paused: bool,
+    pub(crate) volume: f32,
+    /// The attenuation applied to a sound positioned in the world
+    pub(crate) spatial_volume: f32,
+    /// The balance between the left (-1) and right (1) speakers
+    pub(crate) pan: f32,
+}
+
+impl Playback {
+    pub(crate) fn new(id: PlaybackId, sound: Sound, bus: &str, looped: bool) -> Self {
+        Self {
+            id,
+            sound,
+            bus: bus.into(),
+            position: 0.0,
+            looped,
+            paused: false,
+            volume: 1.0,
+            spatial_volume: 1.0,
+            pan: 0.0,
+        }
+    }
+
+    /// Adds the next samples of the sound to the buffer, returns false once
+    /// the sound has ended
+    fn mix_into(&mut self, buffer: &mut [f32], sample_rate: u32) -> bool {
+        if self.paused {
+            return true;
+        }
+
+        let frame_count = self.sound.frame_count();
+        if frame_count == 0 || sample_rate == 0 {
+            return false;
+        }
+
+        let volume = self.volume * self.spatial_volume;
+        let (left_gain, right_gain) = pan_gains(self.pan);
+        let step = f64::from(self.sound.sample_rate()) / f64::from(sample_rate);
+
+        for buffer_frame in buffer.chunks_exact_mut(2) {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let mut frame = self.position as usize;
+            if frame >= frame_count {
+                if !self.looped {
+                    return false;
+                }
+
+                #[allow(clippy::cast_precision_loss)]
+                {
+                    self.position %= frame_count as f64;
+                }
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                {
+                    frame = self.position as usize;
+                }
+            }
+
+            let (left, right) = self.sound.stereo_frame(frame);
+            buffer_frame[0] += left * volume * left_gain;
+            buffer_frame[1] += right * volume * right_gain;
+            self.position += step;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let ended = self.position >= frame_count as f64;
+        self.looped || !ended
+    }
+}
+
+pub struct Mixer {
+    sample_rate: u32,
+    /// The buses, starting with the master bus the other ones are mixed into
+    buses: Vec<Bus>,
+    pub(crate) playbacks: Vec<Playback>,
+}
+
+impl Mixer {
+    #[must_use]
+    pub fn new(settings: &AudioSettings) -> Self {
+        let master_settings = settings.buses.get(MASTER_BUS).cloned().unwrap_or_default();
+        let mut buses = vec![Bus::new(MASTER_BUS, master_settings, settings.sample_rate)];
+
+        let mut bus_names: Vec<&String> = settings
+            .buses
+            .keys()
+            .filter(|name| name.as_str() != MASTER_BUS)
+            .collect();
+        bus_names.sort();
+        for name in bus_names {
+            buses.push(Bus::new(
+                name,
+                settings.buses[name].clone(),
+                settings.sample_rate,
+            ));
+        }
+
+        Self {
+            sample_rate: settings.sample_rate,
+            buses,
+            playbacks: vec![],
+        }
+    }
+
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Adds a bus mixed into the master bus, or changes the settings of an
+    /// existing one
+    pub fn add_bus(&mut self, name: &str, settings: BusSettings) {
+        match self.bus_mut(name) {
+            Some(bus) => bus.set_settings(settings, 0.0),
+            None => self.buses.push(Bus::new(name, settings, self.sample_rate)),
+        }
+    }
+
+    #[must_use]
+    pub fn bus_settings(&self, name: &str) -> Option<BusSettings> {
+        self.buses
+            .iter()
+            .find(|bus| bus.name == name)
+            .map(Bus::settings)
+    }
+
+    /// Changes the settings of a bus, progressively over the given duration in
+    /// seconds
+    pub fn set_bus_settings(&mut self, name: &str, settings: BusSettings, duration: f32) {
+        match self.bus_mut(name) {
+            Some(bus) => bus.set_settings(settings, duration),
+            None => warn!("Audio bus {name} not found"),
+        }
+    }
+
+    /// Transitions the buses of the snapshot to their settings over the given
+    /// duration in seconds
+    pub fn apply_snapshot(&mut self, snapshot: &MixerSnapshot, duration: f32) {
+        for (name, settings) in &snapshot.buses {
+            self.set_bus_settings(name, settings.clone(), duration);
+        }
+    }
+
+    #[must_use]
+    pub fn is_playing(&self, id: PlaybackId) -> bool {
+        self.playbacks.iter().any(|playback| playback.id == id)
+    }
+
+    /// Attenuates and pans a playing sound according to its position relative
+    /// to the listener
+    pub fn spatialize(&mut self, id: PlaybackId, volume: f32, pan: f32) {
+        if let Some(playback) = self.playback_mut(id) {
+            playback.spatial_volume = volume.clamp(0.0, 1.0);
+            playback.pan = pan.clamp(-1.0, 1.0);
+        }
+    }
+
+    pub(crate) fn playback_mut(&mut self, id: PlaybackId) -> Option<&mut Playback> {
+        self.playbacks.iter_mut().find(|playback| playback.id == id)
+    }
+
+    fn bus_mut(&mut self, name: &str) -> Option<&mut Bus> {
+        self.buses.iter_mut().find(|bus| bus.name == name)
+    }
+
+    /// Fills the output with interleaved stereo samples of the playing sounds,
+    /// resampled to the mixer's sample rate. Each bus applies its effects and
+    /// volume to its sounds before being mixed into the master bus, sounds
+    /// played on an unknown bus go directly to the master bus. Sounds that end
+    /// are removed.
+    pub fn mix(&mut self, output: &mut [f32]) {
+        for bus in &mut self.buses {
+            bus.clear_buffer(output.len());
+        }
+
+        let sample_rate = self.sample_rate;
+        let buses = &mut self.buses;
+        self.playbacks.retain_mut(|playback| {
+            let bus_index = buses
+                .iter()
+                .position(|bus| bus.name == playback.bus)
+                .unwrap_or(0);
+            playback.mix_into(&mut buses[bus_index].buffer, sample_rate)
+        });
+
+        let (master_bus, buses) = self.buses.split_first_mut().expect("The master bus exists");
+        for bus in buses {
+            bus.process();
+            for (master_sample, sample) in master_bus.buffer.iter_mut().zip(&bus.buffer) {
+                *master_sample += sample;
+            }
+        }
+
+        master_bus.process();
+        output.copy_from_slice(&master_bus.buffer);
+    }
+}
+
+/// Returns the gains of the left and right speakers, a centered sound being
+/// played at full volume on both
+fn pan_gains(pan: f32) -> (f32, f32) {
+    let pan = pan.clamp(-1.0, 1.0);
+    ((1.0 - pan).min(1.0), (1.0 + pan).min(1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{MUSIC_BUS, SFX_BUS};
+    use crate::effect::LowPass;
+
+    fn assert_samples_eq(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, b) in actual.iter().zip(expected) {
+            assert!((a - b).abs() < 0.0001, "{actual:?} != {expected:?}");
+        }
+    }
+
+    fn mixer(sample_rate: u32) -> Mixer {
+        Mixer::new(&AudioSettings {
+            sample_rate,
+            ..Default::default()
+        })
+    }
+
+    fn playback(id: u64, sound: Sound, bus: &str) -> Playback {
+        Playback::new(PlaybackId(id), sound, bus, false)
+    }
+
+    #[test]
+    fn mix() {
+        let mut mixer = mixer(4);
+        mixer
+            .playbacks
+            .push(playback(0, Sound::new(4, 1, vec![0.25, 0.5]), SFX_BUS));
+        mixer.playbacks.push(playback(
+            1,
+            Sound::new(4, 2, vec![0.25, 0.0, 0.25, 0.0, 0.25, 0.0]),
+            MUSIC_BUS,
+        ));
+        let mut output = [1.0; 8];
+
+        mixer.mix(&mut output);
+
+        assert_samples_eq(&output, &[0.5, 0.25, 0.75, 0.5, 0.25, 0.0, 0.0, 0.0]);
+        assert!(mixer.playbacks.is_empty());
+    }
+
+    #[test]
+    fn mix_bus_volumes_and_pan() {
+        let mut mixer = mixer(1);
+        let mut sound_playback = playback(0, Sound::new(1, 1, vec![1.0]), SFX_BUS);
+        sound_playback.volume = 0.5;
+        sound_playback.pan = -1.0;
+        mixer.playbacks.push(sound_playback);
+        mixer.set_bus_settings(
+            SFX_BUS,
+            BusSettings {
+                volume: 0.5,
+                ..Default::default()
+            },
+            0.0,
+        );
+        mixer.set_bus_settings(
+            MASTER_BUS,
+            BusSettings {
+                volume: 0.5,
+                ..Default::default()
+            },
+            0.0,
+        );
+        let mut output = [0.0; 2];
+
+        mixer.mix(&mut output);
+
+        assert_samples_eq(&output, &[0.125, 0.0]);
+    }
+
+    #[test]
+    fn mix_unknown_bus() {
+        let mut mixer = mixer(1);
+        mixer
+            .playbacks
+            .push(playback(0, Sound::new(1, 1, vec![1.0]), "voices"));
+        mixer.set_bus_settings(
+            MASTER_BUS,
+            BusSettings {
+                volume: 0.5,
+                ..Default::default()
+            },
+            0.0,
+        );
+        let mut output = [0.0; 2];
+
+        mixer.mix(&mut output);
+
+        assert_samples_eq(&output, &[0.5, 0.5]);
+    }
+
+    #[test]
+    fn mix_looped_and_paused() {
+        let mut mixer = mixer(2);
+        let mut looped_playback = playback(0, Sound::new(2, 1, vec![0.5, 0.25]), MUSIC_BUS);
+        looped_playback.looped = true;
+        let mut paused_playback = playback(1, Sound::new(2, 1, vec![1.0]), SFX_BUS);
+        paused_playback.paused = true;
+        mixer.playbacks.push(looped_playback);
+        mixer.playbacks.push(paused_playback);
+        let mut output = [0.0; 6];
+
+        mixer.mix(&mut output);
+
+        assert_samples_eq(&output, &[0.5, 0.5, 0.25, 0.25, 0.5, 0.5]);
+        assert_eq!(mixer.playbacks.len(), 2);
+    }
+
+    #[test]
+    fn mix_resampled() {
+        let mut mixer = mixer(4);
+        mixer
+            .playbacks
+            .push(playback(0, Sound::new(2, 1, vec![0.5, 0.25]), SFX_BUS));
+        let mut output = [0.0; 8];
+
+        mixer.mix(&mut output);
+
+        assert_samples_eq(&output, &[0.5, 0.5, 0.5, 0.5, 0.25, 0.25, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn apply_snapshot() {
+        let mut mixer = mixer(1);
+        let music_settings = BusSettings {
+            volume: 0.5,
+            ..Default::default()
+        }
+        .with_effect(LowPass::new(1000.0));
+        let snapshot = MixerSnapshot::default()
+            .with_bus(MUSIC_BUS, music_settings.clone())
+            .with_bus(
+                SFX_BUS,
+                BusSettings {
+                    muted: true,
+                    ..Default::default()
+                },
+            );
+
+        mixer.apply_snapshot(&snapshot, 0.0);
+
+        assert_eq!(mixer.bus_settings(MUSIC_BUS), Some(music_settings));
+        assert!(mixer.bus_settings(SFX_BUS).unwrap().muted);
+        assert_eq!(mixer.bus_settings(MASTER_BUS), Some(BusSettings::default()));
+    }
+}
@@ -0,0 +1,54 @@
+//! Decoding of Ogg Vorbis files.
+
+use std::io::Cursor;
+
+use lewton::inside_ogg::OggStreamReader;
+use lewton::samples::InterleavedSamples;
+
+use crate::sound::Sound;
+use crate::{AudioError, AudioResult};
+
+pub fn parse_ogg(bytes: &[u8]) -> AudioResult<Sound> {
+    let mut reader = OggStreamReader::new(Cursor::new(bytes))
+        .map_err(|e| AudioError::OggParseError(e.to_string()))?;
+
+    let mut samples = vec![];
+    while let Some(packet) = reader
+        .read_dec_packet_generic::<InterleavedSamples<f32>>()
+        .map_err(|e| AudioError::OggParseError(e.to_string()))?
+    {
+        samples.extend(packet.samples);
+    }
+
+    Ok(Sound::new(
+        reader.ident_hdr.audio_sample_rate,
+        u16::from(reader.ident_hdr.audio_channels),
+        samples,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A stereo Vorbis stream of 64 silent frames at 8000 Hz
+    const SILENCE: &[u8] = include_bytes!("../test_data/silence.ogg");
+
+    #[test]
+    fn parse_vorbis() {
+        let sound = parse_ogg(SILENCE).unwrap();
+
+        assert_eq!(sound.channels(), 2);
+        assert_eq!(sound.sample_rate(), 8000);
+        assert_eq!(sound.frame_count(), 64);
+        assert!(sound.samples().iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn parse_invalid_file() {
+        assert!(matches!(
+            parse_ogg(b"RIFF"),
+            Err(AudioError::OggParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,24 @@
+//! The outputs the mixed samples are sent to.
+
+/// The destination of the samples produced by the mixer, such as the stream
+/// of an audio device
+pub trait AudioSink {
+    /// Returns the number of stereo frames to mix, given the number of frames
+    /// played at the mixer's sample rate since the last call. A sink feeding a
+    /// device queue can ask for more frames to keep the queue filled.
+    fn requested_frames(&mut self, elapsed_frames: usize) -> usize {
+        elapsed_frames
+    }
+
+    /// Receives the mixed interleaved stereo samples
+    fn write(&mut self, samples: &[f32]);
+}
+
+/// A sink discarding the samples, so the sounds keep playing and ending when
+/// there is no audio device
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullSink;
+
+impl AudioSink for NullSink {
+    fn write(&mut self, _samples: &[f32]) {}
+}
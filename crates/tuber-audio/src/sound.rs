@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use tuber_core::asset::{read_asset_file, Metadata};
+use tuber_core::vfs::Vfs;
+use tuber_core::{CoreError, CoreResult};
+
+use crate::ogg::parse_ogg;
+use crate::wav::parse_wav;
+use crate::AudioResult;
+
+/// Decoded audio samples, interleaved when there are several channels
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sound {
+    sample_rate: u32,
+    channels: u16,
+    samples: Arc<[f32]>,
+}
+
+impl Sound {
+    #[must_use]
+    pub fn new(sample_rate: u32, channels: u16, samples: Vec<f32>) -> Self {
+        Self {
+            sample_rate,
+            channels: channels.max(1),
+            samples: samples.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    #[must_use]
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    #[must_use]
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Returns the number of samples per channel
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / usize::from(self.channels)
+    }
+
+    /// Returns the duration of the sound in seconds
+    #[must_use]
+    pub fn duration(&self) -> f64 {
+        if self.sample_rate == 0 {
+            return 0.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let frame_count = self.frame_count() as f64;
+        frame_count / f64::from(self.sample_rate)
+    }
+
+    /// Returns the left and right samples of a frame, mono sounds being
+    /// played on both sides
+    #[must_use]
+    pub fn stereo_frame(&self, frame: usize) -> (f32, f32) {
+        let offset = frame * usize::from(self.channels);
+        match self.channels {
+            1 => (self.samples[offset], self.samples[offset]),
+            _ => (self.samples[offset], self.samples[offset + 1]),
+        }
+    }
+}
+
+/// Decodes a WAV or an Ogg Vorbis file, the format being recognized from the
+/// first bytes of the file
+pub fn parse_sound(bytes: &[u8]) -> AudioResult<Sound> {
+    if bytes.starts_with(b"OggS") {
+        parse_ogg(bytes)
+    } else {
+        parse_wav(bytes)
+    }
+}
+
+/// Loads the WAV or Ogg Vorbis file named by the "file" metadata of the
+/// asset
+pub fn sound_loader(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Box<Sound>> {
+    let sound = parse_sound(&read_asset_file(metadata, vfs)?)
+        .map_err(|e| CoreError::AssetFileParseError(format!("{e:?}")))?;
+    Ok(Box::new(sound))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use tuber_core::vfs::InMemoryVfs;
+
+    use super::*;
+    use crate::wav::tests::wav_bytes;
+
+    fn metadata(file: &str) -> Metadata {
+        let mut metadata = HashMap::new();
+        metadata.insert("file".to_string(), file.to_string());
+        Metadata {
+            identifier: "jump".into(),
+            kind: "sound".into(),
+            metadata,
+            dependencies: vec![],
+            asset_path: PathBuf::from("assets/jump"),
+        }
+    }
+
+    #[test]
+    fn duration() {
+        let sound = Sound::new(4, 2, vec![0.0; 16]);
+
+        assert_eq!(sound.frame_count(), 8);
+        assert!((sound.duration() - 2.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn stereo_frame() {
+        let mono = Sound::new(8000, 1, vec![0.5, -0.5]);
+        let stereo = Sound::new(8000, 2, vec![0.5, -0.5]);
+
+        assert_eq!(mono.stereo_frame(1), (-0.5, -0.5));
+        assert_eq!(stereo.stereo_frame(0), (0.5, -0.5));
+    }
+
+    #[test]
+    fn load_sound() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("assets/jump/jump.wav", wav_bytes(1, 8000, &[0, 16384]));
+
+        let sound = sound_loader(&metadata("jump.wav"), &vfs).unwrap();
+
+        assert_eq!(sound.sample_rate(), 8000);
+        assert_eq!(sound.samples(), &[0.0, 0.5]);
+        assert!(matches!(
+            sound_loader(&metadata("missing.wav"), &vfs),
+            Err(CoreError::AssetFileOpenError(_))
+        ));
+    }
+
+    #[test]
+    fn load_ogg_sound() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "assets/jump/jump.ogg",
+            include_bytes!("../test_data/silence.ogg").as_slice(),
+        );
+
+        let sound = sound_loader(&metadata("jump.ogg"), &vfs).unwrap();
+
+        assert_eq!(sound.sample_rate(), 8000);
+        assert_eq!(sound.frame_count(), 64);
+    }
+}
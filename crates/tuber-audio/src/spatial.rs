@@ -0,0 +1,123 @@
+//! Positioning of the playing sounds in the world, relatively to the entity
+//! listening to them.
+
+use tuber_core::transform::Transform;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemResult;
+
+use crate::mixer::{Mixer, PlaybackId};
+
+/// The sounds emitted from the position of its entity
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSource {
+    /// The playing sounds, removed once they have ended
+    pub playbacks: Vec<PlaybackId>,
+    /// The distance from the listener at which the sound can't be heard anymore
+    pub range: f32,
+}
+
+impl AudioSource {
+    #[must_use]
+    pub fn new(playback: PlaybackId, range: f32) -> Self {
+        Self {
+            playbacks: vec![playback],
+            range,
+        }
+    }
+
+    /// Returns the volume and pan of the sound heard from the given position
+    #[must_use]
+    pub fn spatialization(&self, source: &Transform, listener: &Transform) -> (f32, f32) {
+        if self.range <= 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let dx = source.translation.x - listener.translation.x;
+        let dy = source.translation.y - listener.translation.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        let volume = (1.0 - distance / self.range).clamp(0.0, 1.0);
+        let pan = (dx / self.range).clamp(-1.0, 1.0);
+        (volume, pan)
+    }
+}
+
+/// The entity hearing the audio sources, usually the camera or the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AudioListener;
+
+/// Pans and attenuates the sounds of the audio sources. Sounds aren't
+/// positioned if there is no listener.
+pub fn update_audio_sources(ecs: &mut Ecs, mixer: &mut Mixer) -> SystemResult {
+    let listener_transform = match ecs.query_one::<(&AudioListener, &Transform)>() {
+        Some((_, (_, transform))) => *transform,
+        None => return Ok(()),
+    };
+
+    for (_, (mut source, transform)) in ecs.query::<(&mut AudioSource, &Transform)>() {
+        source
+            .playbacks
+            .retain(|&playback| mixer.is_playing(playback));
+
+        let (volume, pan) = source.spatialization(&transform, &listener_transform);
+        for &playback in &source.playbacks {
+            mixer.spatialize(playback, volume, pan);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sound::Sound;
+    use crate::{Audio, AudioSettings};
+    use tuber_math::vector::Vector3;
+
+    fn transform(x: f32, y: f32) -> Transform {
+        Transform {
+            translation: Vector3::new(x, y, 0.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn spatialization() {
+        let source = AudioSource {
+            playbacks: vec![],
+            range: 100.0,
+        };
+
+        let (volume, pan) = source.spatialization(&transform(30.0, 40.0), &transform(0.0, 0.0));
+        assert!((volume - 0.5).abs() < 0.001);
+        assert!((pan - 0.3).abs() < 0.001);
+
+        let (volume, pan) = source.spatialization(&transform(-300.0, 0.0), &transform(0.0, 0.0));
+        assert!(volume.abs() < 0.001);
+        assert!((pan + 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn update_audio_sources_system() {
+        let mut audio = Audio::new(&AudioSettings {
+            sample_rate: 1,
+            ..Default::default()
+        });
+        let playback = audio.play_sound(&Sound::new(1, 1, vec![1.0, 1.0]));
+        let mut ecs = Ecs::default();
+        ecs.insert((AudioListener, transform(0.0, 0.0)));
+        ecs.insert((AudioSource::new(playback, 10.0), transform(5.0, 0.0)));
+        let mixer = audio.mixer();
+
+        update_audio_sources(&mut ecs, &mut mixer.lock().unwrap()).unwrap();
+        let mut output = [0.0; 2];
+        mixer.lock().unwrap().mix(&mut output);
+        assert!((output[0] - 0.25).abs() < 0.001);
+        assert!((output[1] - 0.5).abs() < 0.001);
+
+        mixer.lock().unwrap().mix(&mut output);
+        update_audio_sources(&mut ecs, &mut mixer.lock().unwrap()).unwrap();
+        let (_, (source,)) = ecs.query_one::<(&AudioSource,)>().unwrap();
+        assert!(source.playbacks.is_empty());
+    }
+}
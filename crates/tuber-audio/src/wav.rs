@@ -0,0 +1,179 @@
+//! Decoding of WAV files holding PCM integer or float samples.
+
+use crate::sound::Sound;
+use crate::{AudioError, AudioResult};
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_IEEE_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+struct Format {
+    encoding: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits_per_sample: u16,
+}
+
+pub fn parse_wav(bytes: &[u8]) -> AudioResult<Sound> {
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(AudioError::WavParseError("not a RIFF WAVE file".into()));
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let chunk_id = &bytes[offset..offset + 4];
+        let chunk_size = read_u32(bytes, offset + 4) as usize;
+        let chunk_start = offset + 8;
+        let chunk = bytes
+            .get(chunk_start..chunk_start + chunk_size)
+            .ok_or_else(|| AudioError::WavParseError("truncated chunk".into()))?;
+
+        match chunk_id {
+            b"fmt " => format = Some(parse_format(chunk)?),
+            b"data" => data = Some(chunk),
+            _ => {}
+        }
+
+        // Chunks are padded to an even size
+        offset = chunk_start + chunk_size + chunk_size % 2;
+    }
+
+    let format = format.ok_or_else(|| AudioError::WavParseError("no fmt chunk".into()))?;
+    let data = data.ok_or_else(|| AudioError::WavParseError("no data chunk".into()))?;
+    let samples = decode_samples(&format, data)?;
+    Ok(Sound::new(format.sample_rate, format.channels, samples))
+}
+
+fn parse_format(chunk: &[u8]) -> AudioResult<Format> {
+    if chunk.len() < 16 {
+        return Err(AudioError::WavParseError("fmt chunk too short".into()));
+    }
+
+    let mut audio_format = read_u16(chunk, 0);
+    if audio_format == FORMAT_EXTENSIBLE && chunk.len() >= 26 {
+        // The format is the start of the sub-format GUID
+        audio_format = read_u16(chunk, 24);
+    }
+
+    Ok(Format {
+        encoding: audio_format,
+        channels: read_u16(chunk, 2),
+        sample_rate: read_u32(chunk, 4),
+        bits_per_sample: read_u16(chunk, 14),
+    })
+}
+
+fn decode_samples(format: &Format, data: &[u8]) -> AudioResult<Vec<f32>> {
+    let decode: fn(&[u8]) -> f32 = match (format.encoding, format.bits_per_sample) {
+        (FORMAT_PCM, 8) => |bytes| (f32::from(bytes[0]) - 128.0) / 128.0,
+        (FORMAT_PCM, 16) => |bytes| f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0,
+        (FORMAT_PCM, 24) => |bytes| {
+            #[allow(clippy::cast_precision_loss)]
+            let sample = (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32;
+            sample / 8_388_608.0
+        },
+        (FORMAT_PCM, 32) => |bytes| {
+            #[allow(clippy::cast_precision_loss)]
+            let sample = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32;
+            sample / 2_147_483_648.0
+        },
+        (FORMAT_IEEE_FLOAT, 32) => {
+            |bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        }
+        (audio_format, bits_per_sample) => {
+            return Err(AudioError::UnsupportedWavFormat {
+                audio_format,
+                bits_per_sample,
+            })
+        }
+    };
+
+    let sample_size = usize::from(format.bits_per_sample / 8);
+    Ok(data.chunks_exact(sample_size).map(decode).collect())
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds a 16 bits PCM WAV file
+    pub(crate) fn wav_bytes(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let data_size = u32::try_from(data.len()).unwrap();
+        let mut bytes = vec![];
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * u32::from(channels) * 2).to_le_bytes());
+        bytes.extend_from_slice(&(channels * 2).to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    #[test]
+    fn parse_pcm_16() {
+        let sound = parse_wav(&wav_bytes(2, 22050, &[0, 16384, -32768, 32767])).unwrap();
+
+        assert_eq!(sound.channels(), 2);
+        assert_eq!(sound.sample_rate(), 22050);
+        assert_eq!(sound.frame_count(), 2);
+        assert_eq!(&sound.samples()[..3], &[0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn parse_skips_unknown_chunks() {
+        let mut bytes = wav_bytes(1, 8000, &[16384]);
+        let list_chunk = [b"LIST".as_slice(), &3u32.to_le_bytes(), &[1, 2, 3, 0]].concat();
+        let fmt_offset = 12;
+        bytes.splice(fmt_offset..fmt_offset, list_chunk);
+
+        let sound = parse_wav(&bytes).unwrap();
+
+        assert_eq!(sound.samples(), &[0.5]);
+    }
+
+    #[test]
+    fn parse_unsupported_format() {
+        let mut bytes = wav_bytes(1, 8000, &[0]);
+        bytes[20] = 2;
+
+        assert!(matches!(
+            parse_wav(&bytes),
+            Err(AudioError::UnsupportedWavFormat {
+                audio_format: 2,
+                bits_per_sample: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_file() {
+        assert!(matches!(
+            parse_wav(b"fLaC"),
+            Err(AudioError::WavParseError(_))
+        ));
+    }
+}
@@ -1,51 +1,128 @@
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
-use std::io::BufReader;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::path::{Path, PathBuf};
 
-use log::info;
+use log::{info, warn};
 use serde_derive::Deserialize;
 
+use crate::vfs::{OsVfs, Vfs};
 use crate::{CoreError, CoreResult};
 
 const ASSETS_DIRECTORY: &str = "assets";
 const ASSET_DESCRIPTION_FILE: &str = "asset.json";
 
-pub type GenericLoader = Box<dyn Fn(&Metadata) -> Box<dyn Any>>;
+pub type GenericLoader = Box<dyn Fn(&Metadata, &dyn Vfs) -> CoreResult<Box<dyn Any>>>;
 
-#[derive(Default)]
 pub struct Store {
+    vfs: Box<dyn Vfs>,
     assets: HashMap<TypeId, HashMap<String, Box<dyn Any>>>,
     asset_loaders: HashMap<TypeId, GenericLoader>,
+    asset_kinds: HashMap<String, TypeId>,
     assets_metadata: HashMap<String, Metadata>,
+    /// The queued assets along with the size of their files, if known
+    loading_queue: VecDeque<(TypeId, String, Option<u64>)>,
+    loading_progress: LoadingProgress,
+    events: Vec<AssetEvent>,
+}
+
+/// The progress of the loading of the queued assets
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoadingProgress {
+    pub items_queued: usize,
+    pub items_loaded: usize,
+    pub items_failed: usize,
+    /// The size in bytes of the files of the queued items whose size is
+    /// known. The size of an item is removed once it fails to load.
+    pub bytes_total: u64,
+    /// The size in bytes of the files of the loaded items
+    pub bytes_loaded: u64,
+}
+
+impl LoadingProgress {
+    /// Returns the fraction of the queued bytes that have been loaded, or of
+    /// the queued items that have been processed if no size is known,
+    /// between 0 and 1
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fraction(&self) -> f32 {
+        if self.is_done() {
+            return 1.0;
+        }
+
+        if self.bytes_total > 0 {
+            return self.bytes_loaded as f32 / self.bytes_total as f32;
+        }
+
+        (self.items_loaded + self.items_failed) as f32 / self.items_queued as f32
+    }
+
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.items_loaded + self.items_failed == self.items_queued
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetEvent {
+    Loaded(String),
+    Failed(String),
+    Reloaded(String),
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self::new(Box::new(OsVfs::default()))
+    }
 }
 
 impl Store {
+    /// Creates a store reading the assets from the given file system
+    #[must_use]
+    pub fn new(vfs: Box<dyn Vfs>) -> Self {
+        Self {
+            vfs,
+            assets: HashMap::new(),
+            asset_loaders: HashMap::new(),
+            asset_kinds: HashMap::new(),
+            assets_metadata: HashMap::new(),
+            loading_queue: VecDeque::new(),
+            loading_progress: LoadingProgress::default(),
+            events: vec![],
+        }
+    }
+
+    /// Returns the file system the assets are read from
+    #[must_use]
+    pub fn vfs(&self) -> &dyn Vfs {
+        self.vfs.as_ref()
+    }
+
     pub fn load_assets_metadata(&mut self) -> CoreResult<()> {
         info!("Loading assets metadata");
-        let paths = match std::fs::read_dir(Store::asset_directory()?) {
-            Ok(paths) => paths,
-            Err(_) => return Ok(()),
+        let Ok(paths) = self.vfs.read_dir(Path::new(ASSETS_DIRECTORY)) else {
+            return Ok(());
         };
 
         let asset_directory_paths: Vec<_> = paths
-            .filter_map(Result::ok)
-            .filter(|p| p.path().is_dir())
+            .into_iter()
+            .filter(|path| self.vfs.is_dir(path))
             .collect();
 
         for asset_directory_path in asset_directory_paths {
-            let mut path = asset_directory_path.path();
-            path.push(ASSET_DESCRIPTION_FILE);
+            let path = asset_directory_path.join(ASSET_DESCRIPTION_FILE);
 
-            if !path.is_file() {
+            if !self.vfs.is_file(&path) {
                 return Err(CoreError::AssetDescriptionFileNotFound);
             }
 
-            let f = std::fs::File::open(path).map_err(CoreError::AssetDescriptionFileOpenError)?;
-            let reader = BufReader::new(f);
-            let mut asset_metadata: Metadata = serde_json::from_reader(reader)
+            let asset_description = self
+                .vfs
+                .read(&path)
+                .map_err(CoreError::AssetDescriptionFileOpenError)?;
+            let mut asset_metadata: Metadata = serde_json::from_slice(&asset_description)
                 .map_err(CoreError::AssetDescriptionFileParseError)?;
-            asset_metadata.asset_path = asset_directory_path.path();
+            asset_metadata.asset_path = asset_directory_path;
             info!(
                 "Loaded resource metadata identifier={} kind={}",
                 &asset_metadata.identifier, &asset_metadata.kind
@@ -60,12 +137,14 @@ impl Store {
 
     pub fn register_loaders<Loader>(&mut self, loaders: Vec<(TypeId, Loader)>)
     where
-        Loader: 'static + Fn(&Metadata) -> Box<dyn Any>,
+        Loader: 'static + Fn(&Metadata, &dyn Vfs) -> CoreResult<Box<dyn Any>>,
     {
         for (type_id, loader) in loaders {
             self.asset_loaders.insert(
                 type_id,
-                Box::new(move |asset_metadata: &Metadata| ((loader)(asset_metadata))),
+                Box::new(move |asset_metadata: &Metadata, vfs: &dyn Vfs| {
+                    (loader)(asset_metadata, vfs)
+                }),
             );
         }
     }
@@ -73,49 +152,297 @@ impl Store {
     pub fn register_loader<AssetType, Loader>(&mut self, loader: Loader)
     where
         AssetType: 'static + Any,
-        Loader: 'static + Fn(&Metadata) -> Box<AssetType>,
+        Loader: 'static + Fn(&Metadata, &dyn Vfs) -> CoreResult<Box<AssetType>>,
     {
         self.asset_loaders.insert(
             TypeId::of::<AssetType>(),
-            Box::new(move |asset_metadata: &Metadata| ((loader)(asset_metadata))),
+            Box::new(move |asset_metadata: &Metadata, vfs: &dyn Vfs| {
+                Ok((loader)(asset_metadata, vfs)? as Box<dyn Any>)
+            }),
         );
     }
 
+    /// Associates an asset kind, as written in asset description files, with
+    /// the type its loader produces.
+    ///
+    /// Dependencies are resolved through their kind, so every kind that can
+    /// appear as a dependency must be registered.
+    pub fn register_asset_kind<AssetType>(&mut self, kind: &str)
+    where
+        AssetType: 'static + Any,
+    {
+        self.asset_kinds
+            .insert(kind.to_owned(), TypeId::of::<AssetType>());
+    }
+
     #[must_use]
     pub fn has_asset<AssetType>(&self, identifier: &str) -> bool
     where
         AssetType: 'static + Any,
     {
-        let type_id = TypeId::of::<AssetType>();
+        self.has_asset_of_type(TypeId::of::<AssetType>(), identifier)
+    }
+
+    fn has_asset_of_type(&self, type_id: TypeId, identifier: &str) -> bool {
         self.assets.get(&type_id).is_some() && self.assets[&type_id].contains_key(identifier)
     }
 
+    /// Returns the description of an asset
+    pub fn metadata(&self, identifier: &str) -> CoreResult<&Metadata> {
+        self.assets_metadata
+            .get(identifier)
+            .ok_or(CoreError::AssetMetadataNotFound)
+    }
+
+    /// Returns the identifiers of the assets the given asset directly depends on
+    pub fn dependencies(&self, identifier: &str) -> CoreResult<&[String]> {
+        Ok(&self.metadata(identifier)?.dependencies)
+    }
+
+    /// Returns the identifiers of all the assets required to load the given
+    /// asset, in loading order. The asset itself comes last.
+    pub fn load_order(&self, identifier: &str) -> CoreResult<Vec<String>> {
+        let mut load_order = vec![];
+        self.collect_load_order(identifier, &mut vec![], &mut load_order)?;
+        Ok(load_order)
+    }
+
+    fn collect_load_order(
+        &self,
+        identifier: &str,
+        dependency_chain: &mut Vec<String>,
+        load_order: &mut Vec<String>,
+    ) -> CoreResult<()> {
+        if load_order.iter().any(|loaded| loaded == identifier) {
+            return Ok(());
+        }
+
+        if dependency_chain.iter().any(|visited| visited == identifier) {
+            return Err(CoreError::AssetDependencyCycle(identifier.into()));
+        }
+
+        dependency_chain.push(identifier.into());
+        for dependency in self.dependencies(identifier)? {
+            self.collect_load_order(dependency, dependency_chain, load_order)?;
+        }
+        dependency_chain.pop();
+
+        load_order.push(identifier.into());
+        Ok(())
+    }
+
+    /// Loads an asset, loading the assets it depends on beforehand
     pub fn load<AssetType>(&mut self, identifier: &str) -> CoreResult<()>
     where
         AssetType: 'static + Any,
     {
-        if self.has_asset::<AssetType>(identifier) {
+        self.load_asset_and_dependencies(TypeId::of::<AssetType>(), identifier)
+    }
+
+    fn load_asset_and_dependencies(&mut self, type_id: TypeId, identifier: &str) -> CoreResult<()> {
+        if self.has_asset_of_type(type_id, identifier) {
             return Ok(());
         }
 
-        let type_id = TypeId::of::<AssetType>();
+        let load_order = self.load_order(identifier)?;
+        let (identifier, dependencies) = load_order
+            .split_last()
+            .expect("The load order contains at least the asset itself");
+
+        for dependency in dependencies {
+            let dependency_type_id = self.asset_kind_type_id(dependency)?;
+            self.load_asset_of_type(dependency_type_id, dependency)?;
+        }
+
+        self.load_asset_of_type(type_id, identifier)
+    }
+
+    fn asset_kind_type_id(&self, identifier: &str) -> CoreResult<TypeId> {
+        let kind = &self
+            .assets_metadata
+            .get(identifier)
+            .ok_or(CoreError::AssetMetadataNotFound)?
+            .kind;
 
+        self.asset_kinds
+            .get(kind)
+            .copied()
+            .ok_or_else(|| CoreError::AssetKindNotRegistered(kind.clone()))
+    }
+
+    fn load_asset_of_type(&mut self, type_id: TypeId, identifier: &str) -> CoreResult<()> {
+        if self.has_asset_of_type(type_id, identifier) {
+            return Ok(());
+        }
+
+        match self.run_loader(type_id, identifier) {
+            Ok(asset) => {
+                self.assets
+                    .entry(type_id)
+                    .or_insert_with(HashMap::new)
+                    .insert(identifier.into(), asset);
+                self.events.push(AssetEvent::Loaded(identifier.into()));
+                Ok(())
+            }
+            Err(error) => {
+                self.events.push(AssetEvent::Failed(identifier.into()));
+                Err(error)
+            }
+        }
+    }
+
+    fn run_loader(&self, type_id: TypeId, identifier: &str) -> CoreResult<Box<dyn Any>> {
         let asset_metadata = self
             .assets_metadata
             .get(identifier)
             .ok_or(CoreError::AssetMetadataNotFound)?;
+        let loader = self
+            .asset_loaders
+            .get(&type_id)
+            .ok_or(CoreError::AssetLoaderNotFound)?;
+
+        (loader)(asset_metadata, self.vfs.as_ref())
+    }
+
+    /// Loads an asset again, replacing the stored one
+    pub fn reload<AssetType>(&mut self, identifier: &str) -> CoreResult<()>
+    where
+        AssetType: 'static + Any,
+    {
+        self.reload_asset_of_type(TypeId::of::<AssetType>(), identifier)
+    }
+
+    /// Loads an asset again with the loader of its kind, replacing the stored
+    /// one
+    pub fn reload_asset(&mut self, identifier: &str) -> CoreResult<()> {
+        let type_id = self.asset_kind_type_id(identifier)?;
+        self.reload_asset_of_type(type_id, identifier)
+    }
+
+    fn reload_asset_of_type(&mut self, type_id: TypeId, identifier: &str) -> CoreResult<()> {
+        if !self.has_asset_of_type(type_id, identifier) {
+            return self.load_asset_and_dependencies(type_id, identifier);
+        }
+
+        match self.run_loader(type_id, identifier) {
+            Ok(asset) => {
+                self.assets
+                    .entry(type_id)
+                    .or_default()
+                    .insert(identifier.into(), asset);
+                self.events.push(AssetEvent::Reloaded(identifier.into()));
+                Ok(())
+            }
+            Err(error) => {
+                self.events.push(AssetEvent::Failed(identifier.into()));
+                Err(error)
+            }
+        }
+    }
+
+    /// Queues an asset and its dependencies for loading.
+    ///
+    /// The queued assets are loaded by calling [`Store::load_next_queued`],
+    /// typically once per frame during a loading screen. Queuing assets while
+    /// the queue is empty starts a new batch and resets the loading progress.
+    pub fn queue<AssetType>(&mut self, identifier: &str) -> CoreResult<()>
+    where
+        AssetType: 'static + Any,
+    {
+        let load_order = self.load_order(identifier)?;
+        let (identifier, dependencies) = load_order
+            .split_last()
+            .expect("The load order contains at least the asset itself");
+
+        let mut to_queue = vec![];
+        for dependency in dependencies {
+            to_queue.push((self.asset_kind_type_id(dependency)?, dependency.clone()));
+        }
+        to_queue.push((TypeId::of::<AssetType>(), identifier.clone()));
+
+        if self.loading_queue.is_empty() {
+            self.loading_progress = LoadingProgress::default();
+        }
+
+        for (type_id, identifier) in to_queue {
+            if self
+                .loading_queue
+                .iter()
+                .any(|(queued_type_id, queued_identifier, _)| {
+                    *queued_type_id == type_id && *queued_identifier == identifier
+                })
+            {
+                continue;
+            }
+
+            let size = self.asset_size(&identifier);
+            self.loading_progress.items_queued += 1;
+            self.loading_progress.bytes_total += size.unwrap_or(0);
+            self.loading_queue.push_back((type_id, identifier, size));
+        }
 
-        let asset_storage = self.assets.entry(type_id).or_insert_with(HashMap::new);
-        asset_storage.insert(
-            identifier.into(),
-            (self
-                .asset_loaders
-                .get(&type_id)
-                .ok_or(CoreError::AssetLoaderNotFound)?)(asset_metadata),
-        );
         Ok(())
     }
 
+    /// Loads the next queued asset, returns false if the queue was empty
+    pub fn load_next_queued(&mut self) -> bool {
+        let Some((type_id, identifier, size)) = self.loading_queue.pop_front() else {
+            return false;
+        };
+
+        let size = size.unwrap_or(0);
+        if let Err(error) = self.load_asset_of_type(type_id, &identifier) {
+            warn!("Failed to load asset {identifier}: {error:?}");
+            self.loading_progress.items_failed += 1;
+            self.loading_progress.bytes_total -= size;
+        } else {
+            self.loading_progress.items_loaded += 1;
+            self.loading_progress.bytes_loaded += size;
+        }
+
+        true
+    }
+
+    /// Returns the size in bytes of the files of an asset, or None if the
+    /// asset has no directory in the file system
+    fn asset_size(&self, identifier: &str) -> Option<u64> {
+        let asset_path = &self.assets_metadata.get(identifier)?.asset_path;
+        if asset_path.as_os_str().is_empty() {
+            return None;
+        }
+
+        self.directory_size(asset_path).ok()
+    }
+
+    fn directory_size(&self, path: &Path) -> io::Result<u64> {
+        let mut size = 0;
+        for entry in self.vfs.read_dir(path)? {
+            size += if self.vfs.is_dir(&entry) {
+                self.directory_size(&entry)?
+            } else {
+                self.vfs.file_size(&entry)?
+            };
+        }
+
+        Ok(size)
+    }
+
+    #[must_use]
+    pub fn loading_progress(&self) -> LoadingProgress {
+        self.loading_progress
+    }
+
+    /// Returns the events that occurred since the last call
+    pub fn drain_events(&mut self) -> impl Iterator<Item = AssetEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Drops the events that haven't been drained, the engine calls it at the
+    /// end of every step
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
     pub fn insert_asset<AssetType>(
         &mut self,
         asset_metadata: Metadata,
@@ -159,12 +486,6 @@ impl Store {
 
         self.stored_asset::<AssetType>(identifier)
     }
-
-    fn asset_directory() -> CoreResult<PathBuf> {
-        let mut path = crate::application_directory()?;
-        path.push(ASSETS_DIRECTORY);
-        Ok(path)
-    }
 }
 
 pub trait IntoLoader<F> {
@@ -173,18 +494,326 @@ pub trait IntoLoader<F> {
 
 impl<F> IntoLoader<F> for F
 where
-    F: 'static + Fn(&Metadata) -> Box<dyn Any>,
+    F: 'static + Fn(&Metadata, &dyn Vfs) -> CoreResult<Box<dyn Any>>,
 {
     fn into_loader(self) -> GenericLoader {
         Box::new(self)
     }
 }
 
+/// Returns an entry of the metadata of an asset
+pub fn metadata_entry<'a>(metadata: &'a Metadata, key: &str) -> CoreResult<&'a str> {
+    metadata
+        .metadata
+        .get(key)
+        .map(String::as_str)
+        .ok_or_else(|| CoreError::AssetMetadataEntryNotFound(key.into()))
+}
+
+/// Reads the file named by the "file" metadata of an asset
+pub fn read_asset_file(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Vec<u8>> {
+    let file = metadata_entry(metadata, "file")?;
+    vfs.read(&metadata.asset_path.join(file))
+        .map_err(CoreError::AssetFileOpenError)
+}
+
 #[derive(Deserialize)]
 pub struct Metadata {
     pub identifier: String,
     pub kind: String,
     pub metadata: HashMap<String, String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// The path of the asset directory in the store's file system
     #[serde(skip)]
     pub asset_path: PathBuf,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::vfs::InMemoryVfs;
+
+    use super::*;
+
+    struct Texture(String);
+    struct Font {
+        atlas: String,
+    }
+
+    fn metadata(identifier: &str, kind: &str, dependencies: &[&str]) -> Metadata {
+        Metadata {
+            identifier: identifier.into(),
+            kind: kind.into(),
+            metadata: HashMap::new(),
+            dependencies: dependencies.iter().map(|&d| d.into()).collect(),
+            asset_path: PathBuf::new(),
+        }
+    }
+
+    fn store_with_metadata(assets_metadata: Vec<Metadata>) -> Store {
+        let mut store = Store::default();
+        for asset_metadata in assets_metadata {
+            store
+                .assets_metadata
+                .insert(asset_metadata.identifier.clone(), asset_metadata);
+        }
+
+        store.register_asset_kind::<Texture>("texture");
+        store.register_asset_kind::<Font>("font");
+        store.register_loader(|metadata: &Metadata, _: &dyn Vfs| {
+            Ok(Box::new(Texture(metadata.identifier.clone())))
+        });
+        store.register_loader(|metadata: &Metadata, _: &dyn Vfs| {
+            Ok(Box::new(Font {
+                atlas: metadata.dependencies[0].clone(),
+            }))
+        });
+        store
+    }
+
+    #[test]
+    fn load_loads_dependencies() {
+        let mut store = store_with_metadata(vec![
+            metadata("font_atlas", "texture", &[]),
+            metadata("font", "font", &["font_atlas"]),
+        ]);
+
+        store.load::<Font>("font").unwrap();
+
+        assert!(store.has_asset::<Font>("font"));
+        assert!(store.has_asset::<Texture>("font_atlas"));
+        let font = store.stored_asset::<Font>("font").unwrap();
+        let atlas = store.stored_asset::<Texture>(&font.atlas).unwrap();
+        assert_eq!(atlas.0, "font_atlas");
+    }
+
+    #[test]
+    fn load_order() {
+        let store = store_with_metadata(vec![
+            metadata("albedo", "texture", &[]),
+            metadata("normal", "texture", &[]),
+            metadata("material", "texture", &["albedo", "normal"]),
+            metadata("model", "texture", &["material", "albedo"]),
+        ]);
+
+        let load_order = store.load_order("model").unwrap();
+
+        assert_eq!(load_order, vec!["albedo", "normal", "material", "model"]);
+    }
+
+    #[test]
+    fn load_order_dependency_cycle() {
+        let store = store_with_metadata(vec![
+            metadata("a", "texture", &["b"]),
+            metadata("b", "texture", &["c"]),
+            metadata("c", "texture", &["a"]),
+        ]);
+
+        assert!(matches!(
+            store.load_order("a"),
+            Err(CoreError::AssetDependencyCycle(identifier)) if identifier == "a"
+        ));
+    }
+
+    #[test]
+    fn load_unregistered_dependency_kind() {
+        let mut store = store_with_metadata(vec![
+            metadata("sound", "sound", &[]),
+            metadata("font", "font", &["sound"]),
+        ]);
+
+        assert!(matches!(
+            store.load::<Font>("font"),
+            Err(CoreError::AssetKindNotRegistered(kind)) if kind == "sound"
+        ));
+        assert!(!store.has_asset::<Font>("font"));
+    }
+
+    #[test]
+    fn load_from_vfs() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "assets/greeting/asset.json",
+            r#"{"identifier": "greeting", "kind": "text", "metadata": {"file": "greeting.txt"}}"#,
+        );
+        vfs.insert_file("assets/greeting/greeting.txt", "Hello");
+        let mut store = Store::new(Box::new(vfs));
+        store.register_loader(|metadata: &Metadata, vfs: &dyn Vfs| {
+            Ok(Box::new(
+                String::from_utf8(read_asset_file(metadata, vfs)?).unwrap(),
+            ))
+        });
+
+        store.load_assets_metadata().unwrap();
+
+        assert_eq!(store.asset::<String>("greeting").unwrap(), "Hello");
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "assets/greeting/asset.json",
+            r#"{"identifier": "greeting", "kind": "text", "metadata": {"file": "greeting.txt"}}"#,
+        );
+        let mut store = Store::new(Box::new(vfs));
+        store.register_loader(|metadata: &Metadata, vfs: &dyn Vfs| {
+            Ok(Box::new(read_asset_file(metadata, vfs)?))
+        });
+        store.load_assets_metadata().unwrap();
+
+        store.queue::<Vec<u8>>("greeting").unwrap();
+        store.load_next_queued();
+
+        assert!(!store.has_asset::<Vec<u8>>("greeting"));
+        assert_eq!(store.loading_progress().items_failed, 1);
+        assert_eq!(
+            store.drain_events().collect::<Vec<_>>(),
+            vec![AssetEvent::Failed("greeting".into())]
+        );
+        assert!(matches!(
+            store.load::<Vec<u8>>("greeting"),
+            Err(CoreError::AssetFileOpenError(_))
+        ));
+    }
+
+    #[test]
+    fn loading_progress() {
+        let mut store = store_with_metadata(vec![
+            metadata("font_atlas", "texture", &[]),
+            metadata("font", "font", &["font_atlas"]),
+            metadata("sprite", "texture", &[]),
+            metadata("broken", "sound", &[]),
+        ]);
+
+        store.queue::<Font>("font").unwrap();
+        store.queue::<Texture>("sprite").unwrap();
+        store.queue::<Texture>("font_atlas").unwrap();
+        store.queue::<u32>("broken").unwrap();
+        assert_eq!(
+            store.loading_progress(),
+            LoadingProgress {
+                items_queued: 4,
+                items_loaded: 0,
+                items_failed: 0,
+                bytes_total: 0,
+                bytes_loaded: 0,
+            }
+        );
+
+        assert!(store.load_next_queued());
+        assert!(store.has_asset::<Texture>("font_atlas"));
+        assert!((store.loading_progress().fraction() - 0.25).abs() < 0.01);
+
+        while store.load_next_queued() {}
+        let progress = store.loading_progress();
+        assert!(progress.is_done());
+        assert_eq!(progress.items_loaded, 3);
+        assert_eq!(progress.items_failed, 1);
+        assert!(store.has_asset::<Font>("font"));
+        assert!(store.has_asset::<Texture>("sprite"));
+        assert_eq!(
+            store.drain_events().collect::<Vec<_>>(),
+            vec![
+                AssetEvent::Loaded("font_atlas".into()),
+                AssetEvent::Loaded("font".into()),
+                AssetEvent::Loaded("sprite".into()),
+                AssetEvent::Failed("broken".into()),
+            ]
+        );
+        assert_eq!(store.drain_events().count(), 0);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn loading_progress_in_bytes() {
+        let mut vfs = InMemoryVfs::default();
+        let mut sizes = vec![];
+        for (identifier, content_size) in [("small", 10), ("large", 30)] {
+            let description =
+                format!(r#"{{"identifier": "{identifier}", "kind": "text", "metadata": {{}}}}"#);
+            sizes.push((description.len() + content_size) as u64);
+            vfs.insert_file(format!("assets/{identifier}/asset.json"), description);
+            vfs.insert_file(
+                format!("assets/{identifier}/data/content.bin"),
+                vec![0; content_size],
+            );
+        }
+        let mut store = Store::new(Box::new(vfs));
+        store.register_loader(|metadata: &Metadata, _: &dyn Vfs| {
+            Ok(Box::new(metadata.identifier.clone()))
+        });
+        store.load_assets_metadata().unwrap();
+
+        store.queue::<String>("small").unwrap();
+        store.queue::<String>("large").unwrap();
+        assert_eq!(store.loading_progress().bytes_total, sizes[0] + sizes[1]);
+        assert_eq!(store.loading_progress().bytes_loaded, 0);
+
+        store.load_next_queued();
+        let progress = store.loading_progress();
+        assert_eq!(progress.bytes_loaded, sizes[0]);
+        let expected_fraction = sizes[0] as f32 / (sizes[0] + sizes[1]) as f32;
+        assert!((progress.fraction() - expected_fraction).abs() < 0.001);
+
+        store.load_next_queued();
+        assert_eq!(store.loading_progress().bytes_loaded, sizes[0] + sizes[1]);
+        assert!((store.loading_progress().fraction() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn queue_new_batch_resets_progress() {
+        let mut store = store_with_metadata(vec![
+            metadata("a", "texture", &[]),
+            metadata("b", "texture", &[]),
+        ]);
+
+        store.queue::<Texture>("a").unwrap();
+        while store.load_next_queued() {}
+        store.queue::<Texture>("b").unwrap();
+
+        assert_eq!(store.loading_progress().items_queued, 1);
+        assert_eq!(store.loading_progress().items_loaded, 0);
+    }
+
+    #[test]
+    fn reload() {
+        let mut store = store_with_metadata(vec![metadata("a", "texture", &[])]);
+
+        store.load::<Texture>("a").unwrap();
+        store.reload::<Texture>("a").unwrap();
+
+        assert_eq!(
+            store.drain_events().collect::<Vec<_>>(),
+            vec![
+                AssetEvent::Loaded("a".into()),
+                AssetEvent::Reloaded("a".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn reload_asset_by_kind() {
+        let mut store = store_with_metadata(vec![
+            metadata("font_atlas", "texture", &[]),
+            metadata("font", "font", &["font_atlas"]),
+        ]);
+
+        store.reload_asset("font").unwrap();
+        store.reload_asset("font").unwrap();
+
+        assert!(store.has_asset::<Texture>("font_atlas"));
+        assert_eq!(
+            store.drain_events().collect::<Vec<_>>(),
+            vec![
+                AssetEvent::Loaded("font_atlas".into()),
+                AssetEvent::Loaded("font".into()),
+                AssetEvent::Reloaded("font".into())
+            ]
+        );
+        assert!(matches!(
+            store.reload_asset("missing"),
+            Err(CoreError::AssetMetadataNotFound)
+        ));
+    }
+}
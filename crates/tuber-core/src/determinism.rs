@@ -0,0 +1,85 @@
+//! Helpers for deterministic simulations, where the same inputs produce the
+//! same world on every run, as needed by replays, lockstep networking and
+//! regression tests.
+
+use tuber_ecs::ecs::Ecs;
+
+use crate::registry::ComponentRegistry;
+use crate::scene::Scene;
+use crate::{CoreError, CoreResult};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeterminismSettings {
+    /// The seed of the `Random` shared resource
+    pub seed: u64,
+    /// The duration of every step in seconds, whatever the time that actually
+    /// elapsed
+    pub timestep: f64,
+}
+
+impl Default for DeterminismSettings {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            timestep: 1.0 / 100.0,
+        }
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+/// Hashes the registered components of every entity and the registered
+/// shared resources. Two worlds in the same state have the same hash, so
+/// comparing the hashes of two runs after each step finds where they diverge.
+pub fn world_hash(ecs: &Ecs, registry: &ComponentRegistry) -> CoreResult<u64> {
+    let world = serde_json::to_vec(&Scene::from_ecs(ecs, registry)?)
+        .map_err(CoreError::SceneSerializationError)?;
+    Ok(fnv1a(&world))
+}
+
+/// The FNV-1a hash, whose value is stable across platforms and Rust versions
+/// unlike the hasher of the standard library
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::Transform;
+    use tuber_math::vector::Vector3;
+
+    #[test]
+    fn fnv1a_reference_values() {
+        assert_eq!(fnv1a(b""), 0xCBF2_9CE4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xAF63_DC4C_8601_EC8C);
+    }
+
+    #[test]
+    fn world_hash_changes_with_the_world() {
+        let registry = ComponentRegistry::new();
+        let mut ecs = Ecs::default();
+        let mut other_ecs = Ecs::default();
+        let entity = ecs.insert((Transform::default(),));
+        other_ecs.insert((Transform::default(),));
+
+        assert_eq!(
+            world_hash(&ecs, &registry).unwrap(),
+            world_hash(&other_ecs, &registry).unwrap()
+        );
+
+        ecs.query_one_by_id::<(&mut Transform,)>(entity)
+            .unwrap()
+            .1
+             .0
+            .translation = Vector3::new(1.0, 0.0, 0.0);
+
+        assert_ne!(
+            world_hash(&ecs, &registry).unwrap(),
+            world_hash(&other_ecs, &registry).unwrap()
+        );
+    }
+}
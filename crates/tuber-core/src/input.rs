@@ -1,12 +1,11 @@
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
 
 use log::{info, trace};
 use serde_derive::Deserialize;
 
 use crate::input::keyboard::Key;
+use crate::vfs::Vfs;
 use crate::{CoreError, CoreResult};
 
 pub mod keyboard {
@@ -194,15 +193,16 @@ pub struct Keymap {
 }
 
 impl Keymap {
-    pub fn from_file(file_path: &Path) -> CoreResult<Self> {
+    pub fn from_file(vfs: &dyn Vfs, file_path: &Path) -> CoreResult<Self> {
         info!(
             "Loading keymap from file \"{}\"",
             file_path.to_str().unwrap()
         );
-        let file = File::open(file_path).map_err(CoreError::KeymapFileOpenError)?;
-        let reader = BufReader::new(file);
+        let file = vfs
+            .read(file_path)
+            .map_err(CoreError::KeymapFileOpenError)?;
         let keymap: HashMap<Key, Action> =
-            serde_json::from_reader(reader).map_err(CoreError::KeymapParseError)?;
+            serde_json::from_slice(&file).map_err(CoreError::KeymapParseError)?;
         let reversed_keymap: HashMap<Action, Key> = keymap
             .iter()
             .map(|(key, value)| (value.clone(), *key))
@@ -0,0 +1,105 @@
+//! Metadata describing how the fields of a component are edited in the entity
+//! inspector, generated with `#[derive(Inspect)]`.
+//!
+//! The values themselves are read and written through the component registry
+//! as JSON, the metadata only tells the inspector which editor to show.
+
+pub use tuber_derive::Inspect;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldEditor {
+    Checkbox,
+    /// A number field, clamped to the range if one is given
+    Number {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Text,
+    /// A field per component of a vector
+    Vector {
+        components: usize,
+    },
+    /// An RGBA color picker for `[f32; 4]` fields
+    Color,
+    /// The field is displayed but can't be edited
+    ReadOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InspectField {
+    /// The name of the field, or its index for tuple structs
+    pub name: &'static str,
+    pub editor: FieldEditor,
+}
+
+/// A type whose fields can be edited in the entity inspector
+pub trait Inspect {
+    fn inspect_fields() -> &'static [InspectField];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuber_math::vector::Vector2;
+
+    #[allow(dead_code)]
+    #[derive(Inspect)]
+    #[inspect(crate = "crate")]
+    struct Sprite {
+        visible: bool,
+        #[inspect(range(min = 0.0, max = 1.0))]
+        opacity: f32,
+        layer: i32,
+        texture: String,
+        offset: Vector2<f32>,
+        #[inspect(color)]
+        tint: [f32; 4],
+        #[inspect(read_only)]
+        frame: usize,
+        #[inspect(skip)]
+        cache: Vec<u8>,
+    }
+
+    #[test]
+    fn derive_inspect() {
+        assert_eq!(
+            Sprite::inspect_fields(),
+            &[
+                InspectField {
+                    name: "visible",
+                    editor: FieldEditor::Checkbox
+                },
+                InspectField {
+                    name: "opacity",
+                    editor: FieldEditor::Number {
+                        min: Some(0.0),
+                        max: Some(1.0)
+                    }
+                },
+                InspectField {
+                    name: "layer",
+                    editor: FieldEditor::Number {
+                        min: None,
+                        max: None
+                    }
+                },
+                InspectField {
+                    name: "texture",
+                    editor: FieldEditor::Text
+                },
+                InspectField {
+                    name: "offset",
+                    editor: FieldEditor::Vector { components: 2 }
+                },
+                InspectField {
+                    name: "tint",
+                    editor: FieldEditor::Color
+                },
+                InspectField {
+                    name: "frame",
+                    editor: FieldEditor::ReadOnly
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! The entity inspector browses the entities and the shared resources of the
+//! ECS and edits their components, to diagnose the state of a running game.
+//!
+//! Components are read and written as JSON through the [`ComponentRegistry`],
+//! only the components registered with
+//! [`ComponentRegistry::register_inspectable`] can be edited.
+
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::EntityIndex;
+
+use crate::inspect::{FieldEditor, InspectField};
+use crate::registry::ComponentRegistry;
+use crate::{CoreError, CoreResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedComponent {
+    pub name: String,
+    pub value: Value,
+    /// The editors of the fields, empty if the component can't be edited
+    pub fields: &'static [InspectField],
+}
+
+#[derive(Debug, Default)]
+pub struct Inspector {
+    enabled: bool,
+    selected_entity: Option<EntityIndex>,
+}
+
+impl Inspector {
+    #[must_use]
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            selected_entity: None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    #[must_use]
+    pub fn selected_entity(&self) -> Option<EntityIndex> {
+        self.selected_entity
+    }
+
+    pub fn select_entity(&mut self, entity: Option<EntityIndex>) {
+        self.selected_entity = entity;
+    }
+
+    /// Returns the entities having registered components, along with the
+    /// names of these components
+    pub fn entities(
+        ecs: &Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<Vec<(EntityIndex, Vec<String>)>> {
+        let mut entities = vec![];
+        for entity in 0..ecs.entity_count() {
+            let components = registry.serialize_components(ecs, entity)?;
+            if !components.is_empty() {
+                entities.push((entity, components.into_keys().collect()));
+            }
+        }
+
+        Ok(entities)
+    }
+
+    pub fn components(
+        ecs: &Ecs,
+        registry: &ComponentRegistry,
+        entity: EntityIndex,
+    ) -> CoreResult<Vec<InspectedComponent>> {
+        Ok(registry
+            .serialize_components(ecs, entity)?
+            .into_iter()
+            .map(|(name, value)| InspectedComponent {
+                fields: registry.inspect_fields(&name),
+                name,
+                value,
+            })
+            .collect())
+    }
+
+    pub fn shared_resources(
+        ecs: &Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<BTreeMap<String, Value>> {
+        registry.serialize_shared_resources(ecs)
+    }
+
+    /// Changes a field of a component of an entity. Numbers are clamped to the
+    /// range of their editor.
+    pub fn edit_field(
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+        entity: EntityIndex,
+        component: &str,
+        field: &str,
+        value: Value,
+    ) -> CoreResult<()> {
+        let field_path = format!("{component}.{field}");
+        let editor = registry
+            .inspect_fields(component)
+            .iter()
+            .find(|inspect_field| inspect_field.name == field)
+            .ok_or_else(|| CoreError::InspectedFieldNotFound(field_path.clone()))?
+            .editor;
+        let value = match editor {
+            FieldEditor::ReadOnly => return Err(CoreError::InspectedFieldReadOnly(field_path)),
+            FieldEditor::Number { min, max } => clamp_number(value, min, max),
+            _ => value,
+        };
+
+        let mut component_value = registry
+            .serialize_component(ecs, entity, component)?
+            .ok_or_else(|| CoreError::InspectedComponentNotFound(component.into()))?;
+        match &mut component_value {
+            Value::Object(fields) => {
+                fields.insert(field.into(), value);
+            }
+            Value::Array(fields) => {
+                let field = field
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| fields.get_mut(index))
+                    .ok_or(CoreError::InspectedFieldNotFound(field_path))?;
+                *field = value;
+            }
+            // Newtype components are serialized as their only field
+            component_value => *component_value = value,
+        }
+
+        registry.insert_component(ecs, entity, component, component_value)
+    }
+
+    pub fn edit_shared_resource(
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+        name: &str,
+        value: Value,
+    ) -> CoreResult<()> {
+        registry.insert_shared_resource(ecs, name, value)
+    }
+}
+
+fn clamp_number(value: Value, min: Option<f64>, max: Option<f64>) -> Value {
+    let Some(number) = value.as_f64() else {
+        return value;
+    };
+
+    let clamped_number = max.map_or(number, |max| number.min(max));
+    let clamped_number = min.map_or(clamped_number, |min| clamped_number.max(min));
+    if (clamped_number - number).abs() > f64::EPSILON {
+        return Value::from(clamped_number);
+    }
+
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inspect::Inspect;
+    use crate::registry::Component;
+    use serde_derive::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Component, Inspect)]
+    #[component(crate = "crate")]
+    #[inspect(crate = "crate")]
+    struct Light {
+        #[inspect(range(min = 0.0, max = 1.0))]
+        intensity: f32,
+        #[inspect(read_only)]
+        id: u32,
+    }
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::new();
+        registry.register_inspectable::<Light>();
+        registry
+    }
+
+    #[test]
+    fn browse_entities() {
+        let registry = registry();
+        let mut ecs = Ecs::default();
+        ecs.insert(());
+        let entity = ecs.insert((Light {
+            intensity: 0.5,
+            id: 1,
+        },));
+
+        let entities = Inspector::entities(&ecs, &registry).unwrap();
+        let components = Inspector::components(&ecs, &registry, entity).unwrap();
+
+        assert_eq!(entities, vec![(entity, vec!["Light".to_string()])]);
+        assert_eq!(components[0].value, json!({ "intensity": 0.5, "id": 1 }));
+        assert_eq!(components[0].fields, Light::inspect_fields());
+    }
+
+    #[test]
+    fn edit_field() {
+        let registry = registry();
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert((Light {
+            intensity: 0.5,
+            id: 1,
+        },));
+
+        Inspector::edit_field(
+            &mut ecs,
+            &registry,
+            entity,
+            "Light",
+            "intensity",
+            json!(4.0),
+        )
+        .unwrap();
+
+        let (_, (light,)) = ecs.query_one_by_id::<(&Light,)>(entity).unwrap();
+        assert_eq!(
+            *light,
+            Light {
+                intensity: 1.0,
+                id: 1
+            }
+        );
+        drop(light);
+        assert!(matches!(
+            Inspector::edit_field(&mut ecs, &registry, entity, "Light", "id", json!(2)),
+            Err(CoreError::InspectedFieldReadOnly(field)) if field == "Light.id"
+        ));
+    }
+}
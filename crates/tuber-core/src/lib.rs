@@ -5,9 +5,28 @@
 
 use std::path::PathBuf;
 
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemBundle;
+use tuber_math::vector::Vector3;
+
+use transform::Transform;
+
 pub mod asset;
+pub mod determinism;
 pub mod input;
+pub mod inspect;
+pub mod inspector;
+pub mod logging;
+pub mod prefab;
+pub mod profiler;
+pub mod random;
+pub mod registry;
+pub mod save_game;
+pub mod scene;
+pub mod scene_watcher;
 pub mod transform;
+pub mod tween;
+pub mod vfs;
 
 pub type CoreResult<T> = Result<T, CoreError>;
 pub struct DeltaTime(pub f64);
@@ -25,7 +44,45 @@ pub enum CoreError {
     AssetDescriptionFileOpenError(std::io::Error),
     AssetDescriptionFileParseError(serde_json::Error),
     AssetMetadataNotFound,
+    AssetKindNotRegistered(String),
+    AssetDependencyCycle(String),
+    AssetFileOpenError(std::io::Error),
+    AssetMetadataEntryNotFound(String),
+    AssetFileParseError(String),
     CurrentDirInaccessible,
+    ComponentNotRegistered(String),
+    ComponentParseError(String, serde_json::Error),
+    ComponentSerializationError(String, serde_json::Error),
+    PrefabFileOpenError(std::io::Error),
+    PrefabParseError(serde_json::Error),
+    SceneFileOpenError(std::io::Error),
+    SceneFileWriteError(std::io::Error),
+    SceneParseError(serde_json::Error),
+    SceneSerializationError(serde_json::Error),
+    SaveDirectoryNotFound,
+    SaveFileOpenError(std::io::Error),
+    SaveFileWriteError(std::io::Error),
+    SaveFileParseError(serde_json::Error),
+    SaveFileSerializationError(serde_json::Error),
+    SaveFormatVersionUnsupported(u32),
+    InvalidSaveSlot(String),
+    InspectedComponentNotFound(String),
+    InspectedFieldNotFound(String),
+    InspectedFieldReadOnly(String),
+    LogFileOpenError(std::io::Error),
+    LoggerAlreadyInstalled,
+    ProfileFileWriteError(std::io::Error),
+    ProfileSerializationError(serde_json::Error),
+}
+
+/// Returns the systems updating the core components, such as transform tweens
+#[must_use]
+pub fn default_system_bundle<AD: 'static>() -> SystemBundle<AD> {
+    let mut system_bundle = SystemBundle::default();
+    system_bundle.add_system(|ecs: &mut Ecs, _: &mut AD| {
+        tween::update_tweens::<Transform, Vector3<f32>>(ecs)
+    });
+    system_bundle
 }
 
 pub fn application_directory() -> CoreResult<PathBuf> {
@@ -0,0 +1,227 @@
+//! The engine logger writes the messages of the `log` macros to the standard
+//! error and optionally to a file, and keeps the most recent ones in memory so
+//! they can be displayed in game.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{CoreError, CoreResult};
+
+#[derive(Debug, Clone)]
+pub struct LogSettings {
+    pub level: LevelFilter,
+    /// The levels of specific modules, such as `tuber_graphics` or
+    /// `my_game::ai`, overriding the default level for these modules and their
+    /// submodules
+    pub module_levels: HashMap<String, LevelFilter>,
+    /// The file the messages are appended to
+    pub file: Option<PathBuf>,
+    /// The number of recent messages kept in memory
+    pub capacity: usize,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: LevelFilter::Info,
+            module_levels: HashMap::new(),
+            file: None,
+            capacity: 256,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogMessage {
+    pub level: Level,
+    /// The module the message was logged from
+    pub target: String,
+    pub message: String,
+}
+
+/// The recent messages captured by the engine logger, shared between the
+/// logger and the engine
+#[derive(Debug, Clone, Default)]
+pub struct LogCapture {
+    messages: Arc<Mutex<VecDeque<LogMessage>>>,
+}
+
+impl LogCapture {
+    /// Returns the captured messages, oldest first
+    #[must_use]
+    pub fn messages(&self) -> Vec<LogMessage> {
+        self.messages.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.messages.lock().unwrap().clear();
+    }
+
+    fn push(&self, message: LogMessage, capacity: usize) {
+        let mut messages = self.messages.lock().unwrap();
+        while messages.len() >= capacity.max(1) {
+            messages.pop_front();
+        }
+        messages.push_back(message);
+    }
+}
+
+pub struct EngineLogger {
+    settings: LogSettings,
+    capture: LogCapture,
+    file: Option<Mutex<File>>,
+}
+
+impl EngineLogger {
+    pub fn new(settings: LogSettings, capture: LogCapture) -> CoreResult<Self> {
+        let file = settings
+            .file
+            .as_ref()
+            .map(|path| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map(Mutex::new)
+                    .map_err(CoreError::LogFileOpenError)
+            })
+            .transpose()?;
+
+        Ok(Self {
+            settings,
+            capture,
+            file,
+        })
+    }
+
+    /// Installs the logger as the logger of the `log` macros, this fails if a
+    /// logger is already installed
+    pub fn install(self) -> CoreResult<()> {
+        log::set_max_level(self.max_level());
+        log::set_logger(Box::leak(Box::new(self))).map_err(|_| CoreError::LoggerAlreadyInstalled)
+    }
+
+    /// Returns the level of the most specific module setting matching the
+    /// target
+    fn level(&self, target: &str) -> LevelFilter {
+        self.settings
+            .module_levels
+            .iter()
+            .filter(|(module, _)| {
+                target == module.as_str()
+                    || target
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|submodule| submodule.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.settings.level, |(_, level)| *level)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        self.settings
+            .module_levels
+            .values()
+            .copied()
+            .fold(self.settings.level, Ord::max)
+    }
+}
+
+impl Log for EngineLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let message = LogMessage {
+            level: record.level(),
+            target: record.target().into(),
+            message: record.args().to_string(),
+        };
+        let line = format!("[{} {}] {}", message.level, message.target, message.message);
+        eprintln!("{line}");
+        if let Some(file) = &self.file {
+            let _ = writeln!(file.lock().unwrap(), "{line}");
+        }
+        self.capture.push(message, self.settings.capacity);
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(logger: &EngineLogger, level: Level, target: &str, message: &str) {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    #[test]
+    fn module_levels() {
+        let mut settings = LogSettings {
+            level: LevelFilter::Warn,
+            ..Default::default()
+        };
+        settings
+            .module_levels
+            .insert("game".into(), LevelFilter::Debug);
+        settings
+            .module_levels
+            .insert("game::ai".into(), LevelFilter::Error);
+        let capture = LogCapture::default();
+        let logger = EngineLogger::new(settings, capture.clone()).unwrap();
+
+        log(&logger, Level::Info, "tuber_core::asset", "skipped");
+        log(&logger, Level::Debug, "game::physics", "kept");
+        log(&logger, Level::Warn, "game::ai", "skipped");
+        log(&logger, Level::Debug, "gameplay", "skipped");
+
+        let messages = capture.messages();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].target, "game::physics");
+        assert_eq!(logger.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn capture_capacity() {
+        let capture = LogCapture::default();
+        let logger = EngineLogger::new(
+            LogSettings {
+                capacity: 2,
+                ..Default::default()
+            },
+            capture.clone(),
+        )
+        .unwrap();
+
+        for message in ["first", "second", "third"] {
+            log(&logger, Level::Info, "game", message);
+        }
+
+        let messages: Vec<String> = capture
+            .messages()
+            .into_iter()
+            .map(|message| message.message)
+            .collect();
+        assert_eq!(messages, vec!["second", "third"]);
+    }
+}
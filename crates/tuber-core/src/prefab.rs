@@ -0,0 +1,221 @@
+//! Prefabs are entity templates, with their children, defined once and
+//! instantiated many times with per-instance overrides.
+//!
+//! Overrides are merged into the prefab's components: objects are merged
+//! field by field while other values are replaced, so
+//! `{ "Transform": { "translation": { "x": 5.0 } } }` only moves an instance
+//! along the x axis.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::{EntityIndex, Parent};
+
+use crate::asset::{read_asset_file, Metadata};
+use crate::registry::ComponentRegistry;
+use crate::vfs::Vfs;
+use crate::{CoreError, CoreResult};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prefab {
+    #[serde(default)]
+    pub components: BTreeMap<String, Value>,
+    /// The entities created along with the prefab's entity, with a
+    /// [`Parent`] component pointing to it
+    #[serde(default)]
+    pub children: Vec<Prefab>,
+}
+
+impl Prefab {
+    pub fn from_json(json: &[u8]) -> CoreResult<Self> {
+        serde_json::from_slice(json).map_err(CoreError::PrefabParseError)
+    }
+
+    pub fn load(vfs: &dyn Vfs, file_path: &Path) -> CoreResult<Self> {
+        let file = vfs
+            .read(file_path)
+            .map_err(CoreError::PrefabFileOpenError)?;
+        Self::from_json(&file)
+    }
+
+    /// Inserts the prefab and its children into the ECS and returns the index
+    /// of the prefab's entity
+    pub fn instantiate(
+        &self,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<EntityIndex> {
+        self.instantiate_with_overrides(ecs, registry, &BTreeMap::new())
+    }
+
+    /// Inserts the prefab with the overrides merged into the components of its
+    /// entity, the children are instantiated unchanged
+    pub fn instantiate_with_overrides(
+        &self,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+        overrides: &BTreeMap<String, Value>,
+    ) -> CoreResult<EntityIndex> {
+        let flattened_entities = self.flatten(overrides);
+        registry.ensure_registered(
+            flattened_entities
+                .iter()
+                .flat_map(|flattened_entity| flattened_entity.components.keys()),
+        )?;
+
+        let mut entities: Vec<EntityIndex> = Vec::with_capacity(flattened_entities.len());
+        for flattened_entity in flattened_entities {
+            let entity = ecs.insert(());
+            for (name, value) in flattened_entity.components {
+                registry.insert_component(ecs, entity, &name, value)?;
+            }
+
+            if let Some(parent) = flattened_entity.parent {
+                ecs.add_component(Parent(entities[parent]), entity);
+            }
+
+            entities.push(entity);
+        }
+
+        Ok(entities[0])
+    }
+
+    /// Lists the prefab's entity, with the overrides merged, followed by its
+    /// descendants in depth-first order
+    pub(crate) fn flatten(&self, overrides: &BTreeMap<String, Value>) -> Vec<FlattenedEntity> {
+        let mut components = self.components.clone();
+        for (name, patch) in overrides {
+            merge(components.entry(name.clone()).or_insert(Value::Null), patch);
+        }
+
+        let mut flattened_entities = vec![FlattenedEntity {
+            components,
+            parent: None,
+        }];
+        self.flatten_children(0, &mut flattened_entities);
+        flattened_entities
+    }
+
+    fn flatten_children(&self, index: usize, flattened_entities: &mut Vec<FlattenedEntity>) {
+        for child in &self.children {
+            let child_index = flattened_entities.len();
+            flattened_entities.push(FlattenedEntity {
+                components: child.components.clone(),
+                parent: Some(index),
+            });
+            child.flatten_children(child_index, flattened_entities);
+        }
+    }
+}
+
+/// An entity of a prefab, with the index of its parent in the flattened
+/// prefab
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FlattenedEntity {
+    pub(crate) components: BTreeMap<String, Value>,
+    pub(crate) parent: Option<usize>,
+}
+
+fn merge(target: &mut Value, patch: &Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch.clone(),
+    }
+}
+
+/// Loads the prefab file named by the "file" metadata of the asset
+pub fn prefab_loader(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Box<Prefab>> {
+    Ok(Box::new(Prefab::from_json(&read_asset_file(
+        metadata, vfs,
+    )?)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use serde_json::json;
+    use tuber_math::vector::Vector3;
+
+    use super::*;
+    use crate::transform::Transform;
+    use crate::vfs::InMemoryVfs;
+
+    const ORC: &str = r#"{
+        "components": {
+            "Transform": {
+                "translation": { "x": 0.0, "y": 0.0, "z": 0.0 },
+                "angle": { "x": 0.0, "y": 0.0, "z": 0.0 },
+                "rotation_center": { "x": 0.0, "y": 0.0, "z": 0.0 },
+                "scale": { "x": 1.0, "y": 1.0, "z": 1.0 }
+            }
+        },
+        "children": [{ "components": {} }]
+    }"#;
+
+    #[test]
+    fn instantiate_children() {
+        let prefab = Prefab::from_json(ORC.as_bytes()).unwrap();
+        let mut ecs = Ecs::default();
+
+        let orc = prefab
+            .instantiate(&mut ecs, &ComponentRegistry::new())
+            .unwrap();
+
+        let (child, (parent,)) = ecs.query_one::<(&Parent,)>().unwrap();
+        assert_ne!(child, orc);
+        assert_eq!(*parent, Parent(orc));
+    }
+
+    #[test]
+    fn instantiate_with_overrides() {
+        let prefab = Prefab::from_json(ORC.as_bytes()).unwrap();
+        let mut ecs = Ecs::default();
+        let mut overrides = BTreeMap::new();
+        overrides.insert(
+            "Transform".to_string(),
+            json!({ "translation": { "x": 5.0 } }),
+        );
+
+        let orc = prefab
+            .instantiate_with_overrides(&mut ecs, &ComponentRegistry::new(), &overrides)
+            .unwrap();
+        let other_orc = prefab
+            .instantiate(&mut ecs, &ComponentRegistry::new())
+            .unwrap();
+
+        let (_, (transform,)) = ecs.query_one_by_id::<(&Transform,)>(orc).unwrap();
+        assert_eq!(transform.translation, Vector3::new(5.0, 0.0, 0.0));
+        assert_eq!(transform.scale, Vector3::new(1.0, 1.0, 1.0));
+        let (_, (transform,)) = ecs.query_one_by_id::<(&Transform,)>(other_orc).unwrap();
+        assert_eq!(transform.translation, Vector3::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn load_prefab() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("assets/orc/orc.json", ORC);
+        let mut metadata = HashMap::new();
+        metadata.insert("file".to_string(), "orc.json".to_string());
+        let metadata = Metadata {
+            identifier: "orc".into(),
+            kind: "prefab".into(),
+            metadata,
+            dependencies: vec![],
+            asset_path: PathBuf::from("assets/orc"),
+        };
+
+        let prefab = prefab_loader(&metadata, &vfs).unwrap();
+
+        assert_eq!(prefab.children.len(), 1);
+        assert!(prefab.components.contains_key("Transform"));
+    }
+}
@@ -0,0 +1,239 @@
+//! A lightweight CPU profiler recording the duration of scopes, which can be
+//! exported in the Chrome trace format and opened in `chrome://tracing` or
+//! Perfetto.
+//!
+//! Scopes are recorded with the [`profile_scope!`](crate::profile_scope)
+//! macro while profiling is enabled:
+//!
+//! ```
+//! fn update_physics() {
+//!     tuber_core::profile_scope!("update_physics");
+//!     // ...
+//! }
+//! ```
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use serde_json::json;
+
+use crate::{CoreError, CoreResult};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SPANS: Mutex<SpanBuffer> = Mutex::new(SpanBuffer::new(MAX_SPAN_COUNT));
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static THREAD_ID: u64 = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// The number of spans kept until they are taken, the oldest ones being
+/// dropped past it so that profiling without taking the spans doesn't grow
+/// the memory used indefinitely
+pub const MAX_SPAN_COUNT: usize = 100_000;
+
+/// Records the duration of the enclosing scope under the given name
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::profiler::ProfileScope::new($name);
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileSpan {
+    pub name: &'static str,
+    pub thread_id: u64,
+    /// The start of the span, in microseconds since profiling was first
+    /// enabled
+    pub start: u64,
+    /// The duration of the span, in microseconds
+    pub duration: u64,
+    /// The number of spans enclosing this one
+    pub depth: u32,
+}
+
+pub fn set_enabled(enabled: bool) {
+    if enabled {
+        profiler_epoch();
+    }
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+#[must_use]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns the spans recorded since the last call, in the order they ended,
+/// keeping at most the last [`MAX_SPAN_COUNT`] ones
+#[must_use]
+pub fn take_spans() -> Vec<ProfileSpan> {
+    SPANS.lock().unwrap().take()
+}
+
+struct SpanBuffer {
+    spans: VecDeque<ProfileSpan>,
+    capacity: usize,
+}
+
+impl SpanBuffer {
+    const fn new(capacity: usize) -> Self {
+        Self {
+            spans: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, span: ProfileSpan) {
+        if self.spans.len() == self.capacity {
+            self.spans.pop_front();
+        }
+        self.spans.push_back(span);
+    }
+
+    fn take(&mut self) -> Vec<ProfileSpan> {
+        std::mem::take(&mut self.spans).into()
+    }
+}
+
+/// Records a span when dropped, created with `profile_scope!`
+pub struct ProfileScope {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl ProfileScope {
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        let start = is_enabled().then(|| {
+            DEPTH.with(|depth| depth.set(depth.get() + 1));
+            Instant::now()
+        });
+
+        Self { name, start }
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+
+        let depth = DEPTH.with(|depth| {
+            depth.set(depth.get() - 1);
+            depth.get()
+        });
+        let epoch = *profiler_epoch();
+        #[allow(clippy::cast_possible_truncation)]
+        let span = ProfileSpan {
+            name: self.name,
+            thread_id: THREAD_ID.with(|thread_id| *thread_id),
+            start: start.saturating_duration_since(epoch).as_micros() as u64,
+            duration: start.elapsed().as_micros() as u64,
+            depth,
+        };
+        SPANS.lock().unwrap().push(span);
+    }
+}
+
+fn profiler_epoch() -> &'static Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now)
+}
+
+/// Formats spans as a Chrome trace
+pub fn chrome_trace(spans: &[ProfileSpan]) -> CoreResult<Vec<u8>> {
+    let events: Vec<_> = spans
+        .iter()
+        .map(|span| {
+            json!({
+                "name": span.name,
+                "ph": "X",
+                "ts": span.start,
+                "dur": span.duration,
+                "pid": 0,
+                "tid": span.thread_id,
+            })
+        })
+        .collect();
+
+    serde_json::to_vec(&json!({ "traceEvents": events }))
+        .map_err(CoreError::ProfileSerializationError)
+}
+
+pub fn write_chrome_trace(spans: &[ProfileSpan], file_path: &Path) -> CoreResult<()> {
+    std::fs::write(file_path, chrome_trace(spans)?).map_err(CoreError::ProfileFileWriteError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_nested_scopes() {
+        set_enabled(true);
+        {
+            profile_scope!("outer");
+            profile_scope!("inner");
+        }
+        set_enabled(false);
+        {
+            profile_scope!("disabled");
+        }
+
+        let spans = take_spans();
+
+        let names: Vec<_> = spans.iter().map(|span| (span.name, span.depth)).collect();
+        assert_eq!(names, vec![("inner", 1), ("outer", 0)]);
+        assert!(spans[1].duration >= spans[0].duration);
+    }
+
+    #[test]
+    fn drop_oldest_spans_past_capacity() {
+        let span = |start| ProfileSpan {
+            name: "step",
+            thread_id: 0,
+            start,
+            duration: 1,
+            depth: 0,
+        };
+        let mut buffer = SpanBuffer::new(2);
+
+        for start in 0..3 {
+            buffer.push(span(start));
+        }
+
+        assert_eq!(buffer.take(), vec![span(1), span(2)]);
+        assert!(buffer.take().is_empty());
+    }
+
+    #[test]
+    fn chrome_trace_format() {
+        let spans = [ProfileSpan {
+            name: "render",
+            thread_id: 2,
+            start: 10,
+            duration: 5,
+            depth: 0,
+        }];
+
+        let trace: serde_json::Value =
+            serde_json::from_slice(&chrome_trace(&spans).unwrap()).unwrap();
+
+        assert_eq!(
+            trace,
+            json!({
+                "traceEvents": [
+                    { "name": "render", "ph": "X", "ts": 10, "dur": 5, "pid": 0, "tid": 2 }
+                ]
+            })
+        );
+    }
+}
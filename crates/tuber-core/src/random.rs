@@ -0,0 +1,189 @@
+//! A seedable pseudo-random number generator, stored as a shared resource so
+//! every system draws from the same sequence.
+
+use std::f32::consts::TAU;
+
+use tuber_math::vector::{Vector2, Vector3};
+
+/// A `SplitMix64` generator: small, fast and producing the same sequence for a
+/// given seed on every platform
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Creates a generator seeded from the current time
+    #[must_use]
+    pub fn from_time() -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos() as u64);
+        Self::new(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `[0, 1)`
+    pub fn next_f32(&mut self) -> f32 {
+        // The 24 most significant bits fit exactly in the mantissa of a f32
+        #[allow(clippy::cast_precision_loss)]
+        let value = (self.next_u64() >> 40) as f32;
+        value / 16_777_216.0
+    }
+
+    /// Returns a number in `[min, max)`
+    pub fn range_f32(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Returns an integer in `[min, max)`, or `min` if the range is empty
+    pub fn range_i64(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+
+        #[allow(clippy::cast_sign_loss)]
+        let span = max.wrapping_sub(min) as u64;
+        #[allow(clippy::cast_possible_wrap)]
+        let offset = self.below(span) as i64;
+        min.wrapping_add(offset)
+    }
+
+    /// Returns an index in `[0, length)`, or 0 if the length is 0
+    pub fn index(&mut self, length: usize) -> usize {
+        #[allow(clippy::cast_possible_truncation)]
+        let index = self.below(length as u64) as usize;
+        index
+    }
+
+    /// Returns true with the given probability
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+
+        items.get(self.index(items.len()))
+    }
+
+    /// Picks an item with a probability proportional to its weight, items with
+    /// a weight of zero or less are never picked
+    pub fn weighted_choice<'a, T>(&mut self, items: &'a [(T, f32)]) -> Option<&'a T> {
+        let total_weight: f32 = items.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        if total_weight <= 0.0 {
+            return None;
+        }
+
+        let mut target = self.next_f32() * total_weight;
+        let mut last_candidate = None;
+        for (item, weight) in items.iter().filter(|(_, weight)| *weight > 0.0) {
+            if target < *weight {
+                return Some(item);
+            }
+            target -= weight;
+            last_candidate = Some(item);
+        }
+
+        // Rounding errors can leave the target slightly above the last weight
+        last_candidate
+    }
+
+    /// Returns a vector of length 1 pointing in a uniformly random direction
+    pub fn unit_vector2(&mut self) -> Vector2<f32> {
+        let angle = self.range_f32(0.0, TAU);
+        Vector2::new(angle.cos(), angle.sin())
+    }
+
+    /// Returns a vector of length 1 pointing in a uniformly random direction
+    pub fn unit_vector3(&mut self) -> Vector3<f32> {
+        let z = self.range_f32(-1.0, 1.0);
+        let angle = self.range_f32(0.0, TAU);
+        let radius = (1.0 - z * z).sqrt();
+        Vector3::new(radius * angle.cos(), radius * angle.sin(), z)
+    }
+
+    /// Returns an integer in `[0, bound)`, or 0 if the bound is 0
+    fn below(&mut self, bound: u64) -> u64 {
+        #[allow(clippy::cast_possible_truncation)]
+        let value = ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64;
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut random = Random::new(42);
+        let mut other_random = Random::new(42);
+
+        let sequence: Vec<u64> = (0..4).map(|_| random.next_u64()).collect();
+        let other_sequence: Vec<u64> = (0..4).map(|_| other_random.next_u64()).collect();
+
+        assert_eq!(sequence, other_sequence);
+        assert_ne!(sequence[0], sequence[1]);
+        assert_ne!(Random::new(43).next_u64(), sequence[0]);
+    }
+
+    #[test]
+    fn ranges() {
+        let mut random = Random::new(3);
+
+        for _ in 0..1000 {
+            assert!((-2.0..3.0).contains(&random.range_f32(-2.0, 3.0)));
+            assert!((-5..5).contains(&random.range_i64(-5, 5)));
+            assert!(random.index(3) < 3);
+        }
+        assert_eq!(random.range_i64(4, 4), 4);
+        assert!(random.choose::<u32>(&[]).is_none());
+    }
+
+    #[test]
+    fn weighted_choice() {
+        let mut random = Random::new(11);
+        let items = [("never", 0.0), ("rare", 1.0), ("common", 9.0)];
+
+        let common_count = (0..1000)
+            .filter(|_| *random.weighted_choice(&items).unwrap() == "common")
+            .count();
+
+        assert!((850..950).contains(&common_count));
+        assert!((0..1000).all(|_| *random.weighted_choice(&items).unwrap() != "never"));
+        assert!(random.weighted_choice(&[("none", 0.0)]).is_none());
+    }
+
+    #[test]
+    fn unit_vectors() {
+        let mut random = Random::new(5);
+
+        for _ in 0..100 {
+            assert!((random.unit_vector2().norm() - 1.0).abs() < 0.001);
+            assert!((random.unit_vector3().norm() - 1.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn next_f32_range() {
+        let mut random = Random::new(7);
+
+        assert!((0..1000).all(|_| (0.0..1.0).contains(&random.next_f32())));
+    }
+}
@@ -0,0 +1,495 @@
+//! The component registry maps names to component and shared resource types,
+//! so they can be stored in files such as scenes.
+
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::EntityIndex;
+
+use crate::inspect::{Inspect, InspectField};
+use crate::transform::Transform;
+use crate::{CoreError, CoreResult};
+
+type ComponentInserter = Box<dyn Fn(&mut Ecs, EntityIndex, Value) -> CoreResult<()>>;
+type ComponentRemover = Box<dyn Fn(&mut Ecs, EntityIndex)>;
+type ComponentSerializer = Box<dyn Fn(&Ecs, EntityIndex) -> Option<CoreResult<Value>>>;
+type SharedResourceInserter = Box<dyn Fn(&mut Ecs, Value) -> CoreResult<()>>;
+type SharedResourceSerializer = Box<dyn Fn(&Ecs) -> Option<CoreResult<Value>>>;
+
+struct RegisteredComponent {
+    name: String,
+    persistent: bool,
+    fields: &'static [ComponentField],
+    inspect_fields: &'static [InspectField],
+    insert: ComponentInserter,
+    remove: ComponentRemover,
+    serialize: ComponentSerializer,
+}
+
+struct RegisteredSharedResource {
+    name: String,
+    persistent: bool,
+    insert: SharedResourceInserter,
+    serialize: SharedResourceSerializer,
+}
+
+/// Marks the component and shared resource types stored in save games
+pub trait Persistent {}
+
+/// A field of a component type, described by `#[derive(Component)]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentField {
+    /// The name of the field, or its index for tuple structs
+    pub name: &'static str,
+    pub type_name: &'static str,
+}
+
+/// A component type carrying its registration metadata, implemented with
+/// `#[derive(Component)]`
+pub trait Component: Serialize + DeserializeOwned + 'static {
+    const NAME: &'static str;
+    const PERSISTENT: bool = false;
+
+    fn fields() -> &'static [ComponentField];
+}
+
+pub use tuber_derive::Component;
+
+#[doc(hidden)]
+pub use inventory;
+
+/// The registration of a component type, submitted by `#[derive(Component)]`
+/// so [`ComponentRegistry::new`] registers the component without a call to
+/// [`ComponentRegistry::register`]
+pub struct ComponentRegistration {
+    name: &'static str,
+    register: fn(&mut ComponentRegistry),
+}
+
+impl ComponentRegistration {
+    #[must_use]
+    pub const fn of<C: Component>() -> Self {
+        Self {
+            name: C::NAME,
+            register: ComponentRegistry::register::<C>,
+        }
+    }
+}
+
+inventory::collect!(ComponentRegistration);
+
+#[derive(Default)]
+pub struct ComponentRegistry {
+    components: Vec<RegisteredComponent>,
+    shared_resources: Vec<RegisteredSharedResource>,
+}
+
+impl ComponentRegistry {
+    /// Creates a registry containing the components of tuber-core and every
+    /// component deriving `Component`, sorted by name
+    #[must_use]
+    pub fn new() -> Self {
+        let mut registry = Self::default();
+        registry.register_component::<Transform>("Transform");
+
+        let mut registrations = inventory::iter::<ComponentRegistration>
+            .into_iter()
+            .collect::<Vec<_>>();
+        registrations.sort_by_key(|registration| registration.name);
+        for registration in registrations {
+            (registration.register)(&mut registry);
+        }
+
+        registry
+    }
+
+    /// Registers a component with the metadata of its `Component`
+    /// implementation. Derived components are already registered by
+    /// [`ComponentRegistry::new`] unless they are generic, this is meant for
+    /// generic components and manual `Component` implementations.
+    pub fn register<C: Component>(&mut self) {
+        self.register_component_type::<C>(C::NAME, C::PERSISTENT, C::fields());
+    }
+
+    /// Registers a component along with the editors of its fields, so it can
+    /// be edited in the entity inspector
+    pub fn register_inspectable<C: Component + Inspect>(&mut self) {
+        self.register::<C>();
+        if let Some(component) = self.components.last_mut() {
+            component.inspect_fields = C::inspect_fields();
+        }
+    }
+
+    pub fn register_component<C>(&mut self, name: &str)
+    where
+        C: Serialize + DeserializeOwned + 'static,
+    {
+        self.register_component_type::<C>(name, false, &[]);
+    }
+
+    /// Registers a component that is stored in save games
+    pub fn register_persistent_component<C>(&mut self, name: &str)
+    where
+        C: Persistent + Serialize + DeserializeOwned + 'static,
+    {
+        self.register_component_type::<C>(name, true, &[]);
+    }
+
+    pub fn register_shared_resource<R>(&mut self, name: &str)
+    where
+        R: Serialize + DeserializeOwned + 'static,
+    {
+        self.register_shared_resource_type::<R>(name, false);
+    }
+
+    /// Registers a shared resource that is stored in save games
+    pub fn register_persistent_shared_resource<R>(&mut self, name: &str)
+    where
+        R: Persistent + Serialize + DeserializeOwned + 'static,
+    {
+        self.register_shared_resource_type::<R>(name, true);
+    }
+
+    fn register_component_type<C>(
+        &mut self,
+        name: &str,
+        persistent: bool,
+        fields: &'static [ComponentField],
+    ) where
+        C: Serialize + DeserializeOwned + 'static,
+    {
+        let insert_name = name.to_string();
+        let serialize_name = name.to_string();
+        self.components.retain(|component| component.name != name);
+        self.components.push(RegisteredComponent {
+            name: name.into(),
+            persistent,
+            fields,
+            inspect_fields: &[],
+            insert: Box::new(move |ecs, entity, value| {
+                let component: C = serde_json::from_value(value)
+                    .map_err(|e| CoreError::ComponentParseError(insert_name.clone(), e))?;
+                ecs.add_component(component, entity);
+                Ok(())
+            }),
+            remove: Box::new(Ecs::remove_component::<C>),
+            serialize: Box::new(move |ecs, entity| {
+                let (_, (component,)) = ecs.query_one_by_id::<(&C,)>(entity)?;
+                Some(
+                    serde_json::to_value(&*component).map_err(|e| {
+                        CoreError::ComponentSerializationError(serialize_name.clone(), e)
+                    }),
+                )
+            }),
+        });
+    }
+
+    fn register_shared_resource_type<R>(&mut self, name: &str, persistent: bool)
+    where
+        R: Serialize + DeserializeOwned + 'static,
+    {
+        let insert_name = name.to_string();
+        let serialize_name = name.to_string();
+        self.shared_resources
+            .retain(|shared_resource| shared_resource.name != name);
+        self.shared_resources.push(RegisteredSharedResource {
+            name: name.into(),
+            persistent,
+            insert: Box::new(move |ecs, value| {
+                let shared_resource: R = serde_json::from_value(value)
+                    .map_err(|e| CoreError::ComponentParseError(insert_name.clone(), e))?;
+                ecs.insert_shared_resource(shared_resource);
+                Ok(())
+            }),
+            serialize: Box::new(move |ecs| {
+                let shared_resource = ecs.shared_resource::<R>()?;
+                Some(
+                    serde_json::to_value(&*shared_resource).map_err(|e| {
+                        CoreError::ComponentSerializationError(serialize_name.clone(), e)
+                    }),
+                )
+            }),
+        });
+    }
+
+    #[must_use]
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.components
+            .iter()
+            .any(|component| component.name == name)
+            || self
+                .shared_resources
+                .iter()
+                .any(|shared_resource| shared_resource.name == name)
+    }
+
+    /// Returns the names of the registered components, in registration order
+    pub fn component_names(&self) -> impl Iterator<Item = &str> {
+        self.components
+            .iter()
+            .map(|component| component.name.as_str())
+    }
+
+    /// Returns the fields of a component registered with its `Component`
+    /// derive, components registered by name have no field information
+    #[must_use]
+    pub fn component_fields(&self, name: &str) -> Option<&'static [ComponentField]> {
+        self.components
+            .iter()
+            .find(|component| component.name == name)
+            .map(|component| component.fields)
+    }
+
+    /// Returns the editors of the fields of a component registered with
+    /// `register_inspectable`, other components have none
+    #[must_use]
+    pub fn inspect_fields(&self, name: &str) -> &'static [InspectField] {
+        self.components
+            .iter()
+            .find(|component| component.name == name)
+            .map_or(&[], |component| component.inspect_fields)
+    }
+
+    /// Returns the names of the registered shared resources, in registration
+    /// order
+    pub fn shared_resource_names(&self) -> impl Iterator<Item = &str> {
+        self.shared_resources
+            .iter()
+            .map(|shared_resource| shared_resource.name.as_str())
+    }
+
+    /// Returns an error naming the first unregistered component or shared
+    /// resource
+    pub fn ensure_registered<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a String>,
+    ) -> CoreResult<()> {
+        match names.into_iter().find(|name| !self.is_registered(name)) {
+            Some(name) => Err(CoreError::ComponentNotRegistered(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Deserializes a component and adds it to an entity
+    pub fn insert_component(
+        &self,
+        ecs: &mut Ecs,
+        entity: EntityIndex,
+        name: &str,
+        value: Value,
+    ) -> CoreResult<()> {
+        let component = self
+            .components
+            .iter()
+            .find(|component| component.name == name)
+            .ok_or_else(|| CoreError::ComponentNotRegistered(name.into()))?;
+        (component.insert)(ecs, entity, value)
+    }
+
+    pub fn remove_component(
+        &self,
+        ecs: &mut Ecs,
+        entity: EntityIndex,
+        name: &str,
+    ) -> CoreResult<()> {
+        let component = self
+            .components
+            .iter()
+            .find(|component| component.name == name)
+            .ok_or_else(|| CoreError::ComponentNotRegistered(name.into()))?;
+        (component.remove)(ecs, entity);
+        Ok(())
+    }
+
+    /// Serializes the registered components of an entity
+    pub fn serialize_components(
+        &self,
+        ecs: &Ecs,
+        entity: EntityIndex,
+    ) -> CoreResult<BTreeMap<String, Value>> {
+        self.serialize_entity(ecs, entity, false)
+    }
+
+    /// Serializes a component of an entity, if the entity has it
+    pub fn serialize_component(
+        &self,
+        ecs: &Ecs,
+        entity: EntityIndex,
+        name: &str,
+    ) -> CoreResult<Option<Value>> {
+        let component = self
+            .components
+            .iter()
+            .find(|component| component.name == name)
+            .ok_or_else(|| CoreError::ComponentNotRegistered(name.into()))?;
+        (component.serialize)(ecs, entity).transpose()
+    }
+
+    /// Serializes the persistent components of an entity
+    pub fn serialize_persistent_components(
+        &self,
+        ecs: &Ecs,
+        entity: EntityIndex,
+    ) -> CoreResult<BTreeMap<String, Value>> {
+        self.serialize_entity(ecs, entity, true)
+    }
+
+    fn serialize_entity(
+        &self,
+        ecs: &Ecs,
+        entity: EntityIndex,
+        persistent_only: bool,
+    ) -> CoreResult<BTreeMap<String, Value>> {
+        self.components
+            .iter()
+            .filter(|component| component.persistent || !persistent_only)
+            .filter_map(|component| {
+                (component.serialize)(ecs, entity)
+                    .map(|value| value.map(|value| (component.name.clone(), value)))
+            })
+            .collect()
+    }
+
+    /// Deserializes a shared resource and inserts it, replacing the shared
+    /// resource of the same type
+    pub fn insert_shared_resource(
+        &self,
+        ecs: &mut Ecs,
+        name: &str,
+        value: Value,
+    ) -> CoreResult<()> {
+        let shared_resource = self
+            .shared_resources
+            .iter()
+            .find(|shared_resource| shared_resource.name == name)
+            .ok_or_else(|| CoreError::ComponentNotRegistered(name.into()))?;
+        (shared_resource.insert)(ecs, value)
+    }
+
+    /// Serializes the registered shared resources present in the ECS
+    pub fn serialize_shared_resources(&self, ecs: &Ecs) -> CoreResult<BTreeMap<String, Value>> {
+        self.serialize_ecs_shared_resources(ecs, false)
+    }
+
+    /// Serializes the persistent shared resources present in the ECS
+    pub fn serialize_persistent_shared_resources(
+        &self,
+        ecs: &Ecs,
+    ) -> CoreResult<BTreeMap<String, Value>> {
+        self.serialize_ecs_shared_resources(ecs, true)
+    }
+
+    fn serialize_ecs_shared_resources(
+        &self,
+        ecs: &Ecs,
+        persistent_only: bool,
+    ) -> CoreResult<BTreeMap<String, Value>> {
+        self.shared_resources
+            .iter()
+            .filter(|shared_resource| shared_resource.persistent || !persistent_only)
+            .filter_map(|shared_resource| {
+                (shared_resource.serialize)(ecs)
+                    .map(|value| value.map(|value| (shared_resource.name.clone(), value)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Component)]
+    #[component(crate = "crate", name = "PlayerStats", persistent)]
+    struct Stats {
+        level: u32,
+        experience: Vec<f32>,
+    }
+
+    #[test]
+    fn insert_and_serialize_component() {
+        let mut registry = ComponentRegistry::new();
+        registry.register_component::<Health>("Health");
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert(());
+
+        registry
+            .insert_component(&mut ecs, entity, "Health", json!(12))
+            .unwrap();
+
+        let (_, (health,)) = ecs.query_one_by_id::<(&Health,)>(entity).unwrap();
+        assert_eq!(*health, Health(12));
+        drop(health);
+        let components = registry.serialize_components(&ecs, entity).unwrap();
+        assert_eq!(components.len(), 1);
+        assert_eq!(components["Health"], json!(12));
+    }
+
+    #[test]
+    fn insert_unregistered_component() {
+        let registry = ComponentRegistry::new();
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert(());
+
+        assert!(matches!(
+            registry.insert_component(&mut ecs, entity, "Health", json!(12)),
+            Err(CoreError::ComponentNotRegistered(name)) if name == "Health"
+        ));
+    }
+
+    #[test]
+    fn insert_and_serialize_shared_resource() {
+        let mut registry = ComponentRegistry::new();
+        registry.register_shared_resource::<Health>("Health");
+        let mut ecs = Ecs::default();
+
+        registry
+            .insert_shared_resource(&mut ecs, "Health", json!(3))
+            .unwrap();
+
+        assert_eq!(*ecs.shared_resource::<Health>().unwrap(), Health(3));
+        assert_eq!(
+            registry.serialize_shared_resources(&ecs).unwrap()["Health"],
+            json!(3)
+        );
+    }
+
+    #[test]
+    fn register_derived_component() {
+        let registry = ComponentRegistry::new();
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert((Stats {
+            level: 2,
+            experience: vec![],
+        },));
+
+        assert!(registry.is_registered("PlayerStats"));
+        assert_eq!(
+            registry.component_fields("PlayerStats").unwrap(),
+            &[
+                ComponentField {
+                    name: "level",
+                    type_name: "u32"
+                },
+                ComponentField {
+                    name: "experience",
+                    type_name: "Vec<f32>"
+                }
+            ]
+        );
+        assert_eq!(
+            registry
+                .serialize_persistent_components(&ecs, entity)
+                .unwrap()["PlayerStats"],
+            json!({ "level": 2, "experience": [] })
+        );
+    }
+}
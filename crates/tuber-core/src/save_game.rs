@@ -0,0 +1,314 @@
+//! Save games store the persistent components and shared resources of the
+//! world in slots, one JSON file per slot in the platform save directory.
+//!
+//! Only the types registered with
+//! [`ComponentRegistry::register_persistent_component`] or
+//! [`ComponentRegistry::register_persistent_shared_resource`] are saved.
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::info;
+use serde_derive::{Deserialize, Serialize};
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::EntityIndex;
+
+use crate::registry::ComponentRegistry;
+use crate::scene::Scene;
+use crate::{CoreError, CoreResult};
+
+/// The version of the save file layout, increased when it changes in a way
+/// older engine versions cannot read
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+const SAVE_FILE_EXTENSION: &str = "json";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub format_version: u32,
+    /// The version of the game that wrote the save, to migrate old saves
+    pub game_version: String,
+    /// The time the save was written at, in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub header: SaveHeader,
+    pub world: Scene,
+}
+
+impl SaveFile {
+    /// Instantiates the saved entities and shared resources
+    pub fn restore(
+        &self,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<Vec<EntityIndex>> {
+        self.world.instantiate(ecs, registry)
+    }
+}
+
+pub struct SaveGame {
+    directory: PathBuf,
+    game_version: String,
+}
+
+impl SaveGame {
+    /// Stores the saves in a directory named after the application in the
+    /// platform save directory
+    pub fn new(application_name: &str, game_version: &str) -> CoreResult<Self> {
+        let directory = platform_data_directory()
+            .ok_or(CoreError::SaveDirectoryNotFound)?
+            .join(application_name)
+            .join("saves");
+        Ok(Self::with_directory(directory, game_version))
+    }
+
+    #[must_use]
+    pub fn with_directory(directory: PathBuf, game_version: &str) -> Self {
+        Self {
+            directory,
+            game_version: game_version.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Saves the persistent state of the world in a slot, replacing the
+    /// previous save of the slot
+    pub fn write(&self, slot: &str, ecs: &Ecs, registry: &ComponentRegistry) -> CoreResult<()> {
+        let save_file = SaveFile {
+            header: SaveHeader {
+                format_version: SAVE_FORMAT_VERSION,
+                game_version: self.game_version.clone(),
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs()),
+            },
+            world: Scene::from_ecs_persistent(ecs, registry)?,
+        };
+        let json =
+            serde_json::to_vec_pretty(&save_file).map_err(CoreError::SaveFileSerializationError)?;
+
+        let file_path = self.slot_path(slot)?;
+        info!("Writing save file \"{}\"", file_path.display());
+        std::fs::create_dir_all(&self.directory).map_err(CoreError::SaveFileWriteError)?;
+        // Writing to a temporary file first keeps the previous save intact if
+        // the game stops while saving
+        let temporary_file_path = file_path.with_extension("tmp");
+        std::fs::write(&temporary_file_path, json).map_err(CoreError::SaveFileWriteError)?;
+        std::fs::rename(&temporary_file_path, &file_path).map_err(CoreError::SaveFileWriteError)
+    }
+
+    /// Reads the save of a slot, saves written with a newer format version
+    /// are rejected
+    pub fn read(&self, slot: &str) -> CoreResult<SaveFile> {
+        let file_path = self.slot_path(slot)?;
+        info!("Reading save file \"{}\"", file_path.display());
+        let file = std::fs::read(&file_path).map_err(CoreError::SaveFileOpenError)?;
+        let save_file: SaveFile =
+            serde_json::from_slice(&file).map_err(CoreError::SaveFileParseError)?;
+        if save_file.header.format_version > SAVE_FORMAT_VERSION {
+            return Err(CoreError::SaveFormatVersionUnsupported(
+                save_file.header.format_version,
+            ));
+        }
+
+        Ok(save_file)
+    }
+
+    #[must_use]
+    pub fn exists(&self, slot: &str) -> bool {
+        self.slot_path(slot)
+            .is_ok_and(|file_path| file_path.is_file())
+    }
+
+    /// Returns the names of the slots having a save, sorted alphabetically
+    pub fn slots(&self) -> CoreResult<Vec<String>> {
+        let entries = match std::fs::read_dir(&self.directory) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(CoreError::SaveFileOpenError(e)),
+        };
+
+        let mut slots = vec![];
+        for entry in entries {
+            let path = entry.map_err(CoreError::SaveFileOpenError)?.path();
+            if path.extension().and_then(|extension| extension.to_str())
+                != Some(SAVE_FILE_EXTENSION)
+            {
+                continue;
+            }
+
+            if let Some(slot) = path.file_stem().and_then(|stem| stem.to_str()) {
+                slots.push(slot.to_owned());
+            }
+        }
+
+        slots.sort();
+        Ok(slots)
+    }
+
+    pub fn delete(&self, slot: &str) -> CoreResult<()> {
+        std::fs::remove_file(self.slot_path(slot)?).map_err(CoreError::SaveFileWriteError)
+    }
+
+    /// Returns the path of the save file of a slot. Slot names containing a
+    /// path separator or naming a parent directory are rejected so saves
+    /// can't be written outside the save directory.
+    fn slot_path(&self, slot: &str) -> CoreResult<PathBuf> {
+        if slot.is_empty() || slot == "." || slot == ".." || slot.contains(['/', '\\']) {
+            return Err(CoreError::InvalidSaveSlot(slot.into()));
+        }
+
+        Ok(self.directory.join(format!("{slot}.{SAVE_FILE_EXTENSION}")))
+    }
+}
+
+/// Returns the directory applications store their user data in:
+/// `%APPDATA%` on Windows, `~/Library/Application Support` on macOS and
+/// `$XDG_DATA_HOME` or `~/.local/share` on other platforms
+fn platform_data_directory() -> Option<PathBuf> {
+    let non_empty_variable = |name: &str| {
+        std::env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    if cfg!(target_os = "windows") {
+        non_empty_variable("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        non_empty_variable("HOME").map(|home| home.join("Library/Application Support"))
+    } else {
+        non_empty_variable("XDG_DATA_HOME")
+            .or_else(|| non_empty_variable("HOME").map(|home| home.join(".local/share")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Persistent;
+    use crate::transform::Transform;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+    impl Persistent for Health {}
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Score(u64);
+    impl Persistent for Score {}
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::new();
+        registry.register_persistent_component::<Health>("Health");
+        registry.register_persistent_shared_resource::<Score>("Score");
+        registry
+    }
+
+    fn save_game(test_name: &str) -> SaveGame {
+        let directory = std::env::temp_dir()
+            .join(format!("tuber-save-game-{}", std::process::id()))
+            .join(test_name);
+        let _ = std::fs::remove_dir_all(&directory);
+        SaveGame::with_directory(directory, "1.2.0")
+    }
+
+    #[test]
+    fn write_and_read() {
+        let save_game = save_game("write_and_read");
+        let registry = registry();
+        let mut ecs = Ecs::default();
+        ecs.insert((Health(3), Transform::default()));
+        ecs.insert((Transform::default(),));
+        ecs.insert_shared_resource(Score(120));
+
+        save_game.write("slot1", &ecs, &registry).unwrap();
+        let save_file = save_game.read("slot1").unwrap();
+
+        assert_eq!(save_file.header.format_version, SAVE_FORMAT_VERSION);
+        assert_eq!(save_file.header.game_version, "1.2.0");
+        assert_eq!(save_file.world.entities.len(), 1);
+        assert_eq!(save_file.world.entities[0].components.len(), 1);
+
+        let mut restored_ecs = Ecs::default();
+        save_file.restore(&mut restored_ecs, &registry).unwrap();
+        let (_, (health,)) = restored_ecs.query_one::<(&Health,)>().unwrap();
+        assert_eq!(*health, Health(3));
+        assert_eq!(
+            *restored_ecs.shared_resource::<Score>().unwrap(),
+            Score(120)
+        );
+        let _ = std::fs::remove_dir_all(save_game.directory());
+    }
+
+    #[test]
+    fn slots_and_delete() {
+        let save_game = save_game("slots_and_delete");
+        let registry = registry();
+        let ecs = Ecs::default();
+        assert!(save_game.slots().unwrap().is_empty());
+
+        save_game.write("b", &ecs, &registry).unwrap();
+        save_game.write("a", &ecs, &registry).unwrap();
+        assert_eq!(save_game.slots().unwrap(), vec!["a", "b"]);
+
+        save_game.delete("a").unwrap();
+        assert!(!save_game.exists("a"));
+        assert_eq!(save_game.slots().unwrap(), vec!["b"]);
+        let _ = std::fs::remove_dir_all(save_game.directory());
+    }
+
+    #[test]
+    fn slot_names_with_dots() {
+        let save_game = save_game("slot_names_with_dots");
+        let registry = registry();
+        let ecs = Ecs::default();
+
+        save_game.write("save.v1", &ecs, &registry).unwrap();
+        save_game.write("save.v2", &ecs, &registry).unwrap();
+
+        assert!(save_game.directory().join("save.v1.json").is_file());
+        assert_eq!(save_game.slots().unwrap(), vec!["save.v1", "save.v2"]);
+        let _ = std::fs::remove_dir_all(save_game.directory());
+    }
+
+    #[test]
+    fn invalid_slot_names() {
+        let save_game = save_game("invalid_slot_names");
+        let registry = registry();
+        let ecs = Ecs::default();
+
+        for slot in ["", "..", "../escaped", "saves/slot1", "saves\\slot1"] {
+            assert!(matches!(
+                save_game.write(slot, &ecs, &registry),
+                Err(CoreError::InvalidSaveSlot(_))
+            ));
+            assert!(!save_game.exists(slot));
+        }
+        assert!(!save_game.directory().exists());
+    }
+
+    #[test]
+    fn read_newer_format_version() {
+        let save_game = save_game("read_newer_format_version");
+        std::fs::create_dir_all(save_game.directory()).unwrap();
+        std::fs::write(
+            save_game.directory().join("slot1.json"),
+            r#"{
+                "header": { "format_version": 99, "game_version": "2.0.0", "timestamp": 0 },
+                "world": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            save_game.read("slot1"),
+            Err(CoreError::SaveFormatVersionUnsupported(99))
+        ));
+        let _ = std::fs::remove_dir_all(save_game.directory());
+    }
+}
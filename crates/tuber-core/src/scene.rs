@@ -0,0 +1,213 @@
+//! Scenes describe entities and shared resources in JSON files, so level
+//! content can be loaded instead of being built in code.
+//!
+//! Components and shared resources are identified by the name they are
+//! registered with in the [`ComponentRegistry`]:
+//!
+//! ```json
+//! {
+//!     "entities": [
+//!         { "components": { "Transform": { ... } } }
+//!     ],
+//!     "shared_resources": { ... }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use log::info;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::EntityIndex;
+
+use crate::registry::ComponentRegistry;
+use crate::vfs::Vfs;
+use crate::{CoreError, CoreResult};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    #[serde(default)]
+    pub components: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    #[serde(default)]
+    pub entities: Vec<SceneEntity>,
+    #[serde(default)]
+    pub shared_resources: BTreeMap<String, Value>,
+}
+
+impl Scene {
+    pub fn from_json(json: &[u8]) -> CoreResult<Self> {
+        serde_json::from_slice(json).map_err(CoreError::SceneParseError)
+    }
+
+    pub fn load(vfs: &dyn Vfs, file_path: &Path) -> CoreResult<Self> {
+        info!("Loading scene from file \"{}\"", file_path.display());
+        let file = vfs.read(file_path).map_err(CoreError::SceneFileOpenError)?;
+        Self::from_json(&file)
+    }
+
+    /// Captures the registered components of every entity and the registered
+    /// shared resources. Entities without registered components are skipped.
+    pub fn from_ecs(ecs: &Ecs, registry: &ComponentRegistry) -> CoreResult<Self> {
+        let mut entities = vec![];
+        for entity in 0..ecs.entity_count() {
+            let components = registry.serialize_components(ecs, entity)?;
+            if !components.is_empty() {
+                entities.push(SceneEntity { components });
+            }
+        }
+
+        Ok(Self {
+            entities,
+            shared_resources: registry.serialize_shared_resources(ecs)?,
+        })
+    }
+
+    /// Captures the persistent components and shared resources, the entities
+    /// without persistent components are skipped
+    pub fn from_ecs_persistent(ecs: &Ecs, registry: &ComponentRegistry) -> CoreResult<Self> {
+        let mut entities = vec![];
+        for entity in 0..ecs.entity_count() {
+            let components = registry.serialize_persistent_components(ecs, entity)?;
+            if !components.is_empty() {
+                entities.push(SceneEntity { components });
+            }
+        }
+
+        Ok(Self {
+            entities,
+            shared_resources: registry.serialize_persistent_shared_resources(ecs)?,
+        })
+    }
+
+    pub fn to_json(&self) -> CoreResult<Vec<u8>> {
+        serde_json::to_vec_pretty(self).map_err(CoreError::SceneSerializationError)
+    }
+
+    /// Writes the scene to a file of the OS file system
+    pub fn save(&self, file_path: &Path) -> CoreResult<()> {
+        info!("Saving scene to file \"{}\"", file_path.display());
+        std::fs::write(file_path, self.to_json()?).map_err(CoreError::SceneFileWriteError)
+    }
+
+    /// Inserts the entities and shared resources of the scene into the ECS and
+    /// returns the indices of the created entities. Nothing is inserted if the
+    /// scene uses an unregistered component.
+    pub fn instantiate(
+        &self,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<Vec<EntityIndex>> {
+        registry.ensure_registered(
+            self.entities
+                .iter()
+                .flat_map(|entity| entity.components.keys())
+                .chain(self.shared_resources.keys()),
+        )?;
+
+        for (name, value) in &self.shared_resources {
+            registry.insert_shared_resource(ecs, name, value.clone())?;
+        }
+
+        let mut created_entities = Vec::with_capacity(self.entities.len());
+        for scene_entity in &self.entities {
+            let entity = ecs.insert(());
+            for (name, value) in &scene_entity.components {
+                registry.insert_component(ecs, entity, name, value.clone())?;
+            }
+            created_entities.push(entity);
+        }
+
+        Ok(created_entities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::Transform;
+    use crate::vfs::InMemoryVfs;
+    use serde_derive::{Deserialize, Serialize};
+    use tuber_math::vector::Vector3;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Gravity(f32);
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::new();
+        registry.register_shared_resource::<Gravity>("Gravity");
+        registry
+    }
+
+    const SCENE: &str = r#"{
+        "entities": [
+            {
+                "components": {
+                    "Transform": {
+                        "translation": { "x": 1.0, "y": 2.0, "z": 0.0 },
+                        "angle": { "x": 0.0, "y": 0.0, "z": 0.0 },
+                        "rotation_center": { "x": 0.0, "y": 0.0, "z": 0.0 },
+                        "scale": { "x": 1.0, "y": 1.0, "z": 1.0 }
+                    }
+                }
+            },
+            {}
+        ],
+        "shared_resources": { "Gravity": 9.81 }
+    }"#;
+
+    #[test]
+    fn load_and_instantiate() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("scenes/level.json", SCENE);
+        let mut ecs = Ecs::default();
+
+        let scene = Scene::load(&vfs, Path::new("scenes/level.json")).unwrap();
+        let entities = scene.instantiate(&mut ecs, &registry()).unwrap();
+
+        assert_eq!(entities.len(), 2);
+        let (_, (transform,)) = ecs.query_one_by_id::<(&Transform,)>(entities[0]).unwrap();
+        assert_eq!(transform.translation, Vector3::new(1.0, 2.0, 0.0));
+        assert!(ecs.query_one_by_id::<(&Transform,)>(entities[1]).is_none());
+        assert_eq!(*ecs.shared_resource::<Gravity>().unwrap(), Gravity(9.81));
+    }
+
+    #[test]
+    fn instantiate_unregistered_component() {
+        let scene =
+            Scene::from_json(br#"{ "entities": [{ "components": { "Health": 3 } }] }"#).unwrap();
+        let mut ecs = Ecs::default();
+
+        assert!(matches!(
+            scene.instantiate(&mut ecs, &registry()),
+            Err(CoreError::ComponentNotRegistered(name)) if name == "Health"
+        ));
+        assert_eq!(ecs.entity_count(), 0);
+    }
+
+    #[test]
+    fn from_ecs_round_trip() {
+        let mut ecs = Ecs::default();
+        ecs.insert((Transform::default(),));
+        ecs.insert((Gravity(1.0),));
+        ecs.insert_shared_resource(Gravity(9.81));
+        let registry = registry();
+
+        let scene = Scene::from_ecs(&ecs, &registry).unwrap();
+        let reloaded_scene = Scene::from_json(&scene.to_json().unwrap()).unwrap();
+
+        assert_eq!(scene.entities.len(), 1);
+        assert_eq!(reloaded_scene, scene);
+        let mut reloaded_ecs = Ecs::default();
+        reloaded_scene
+            .instantiate(&mut reloaded_ecs, &registry)
+            .unwrap();
+        let (_, (transform,)) = reloaded_ecs.query_one::<(&Transform,)>().unwrap();
+        assert_eq!(*transform, Transform::default());
+    }
+}
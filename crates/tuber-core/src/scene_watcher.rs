@@ -0,0 +1,574 @@
+//! Reloading of the scenes and prefabs whose file changed, so level tweaks
+//! show up without restarting the game.
+//!
+//! The entities of a changed file are updated in place: only the components
+//! whose description changed are replaced, so runtime-only components and the
+//! runtime state of untouched components are preserved.
+//!
+//! The files of other assets, such as scripts, can be watched too. The
+//! watcher only reports them as changed, reloading them is up to the owner of
+//! the asset store.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use serde_json::Value;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::{EntityIndex, Parent};
+
+use crate::prefab::{FlattenedEntity, Prefab};
+use crate::registry::ComponentRegistry;
+use crate::scene::Scene;
+use crate::vfs::Vfs;
+use crate::{CoreError, CoreResult};
+
+const DEFAULT_POLL_INTERVAL: f64 = 1.0;
+
+enum WatchedKind {
+    Scene,
+    Prefab { overrides: BTreeMap<String, Value> },
+}
+
+struct WatchedFile {
+    path: PathBuf,
+    kind: WatchedKind,
+    content: Vec<u8>,
+    /// The description of the instantiated entities as of the last load
+    descriptions: Vec<FlattenedEntity>,
+    shared_resources: BTreeMap<String, Value>,
+    entities: Vec<EntityIndex>,
+}
+
+struct WatchedAsset {
+    identifier: String,
+    path: PathBuf,
+    content: Vec<u8>,
+}
+
+pub struct SceneWatcher {
+    watched_files: Vec<WatchedFile>,
+    watched_assets: Vec<WatchedAsset>,
+    /// The identifiers of the watched assets whose file changed since the
+    /// last call to `drain_changed_assets`
+    changed_assets: Vec<String>,
+    /// The time between two checks of the watched files, in seconds
+    poll_interval: f64,
+    elapsed_time: f64,
+}
+
+impl Default for SceneWatcher {
+    fn default() -> Self {
+        Self {
+            watched_files: vec![],
+            watched_assets: vec![],
+            changed_assets: vec![],
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            elapsed_time: 0.0,
+        }
+    }
+}
+
+impl SceneWatcher {
+    #[must_use]
+    pub fn with_poll_interval(mut self, poll_interval: f64) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Instantiates a scene whose entities are updated when its file changes
+    pub fn load_scene(
+        &mut self,
+        vfs: &dyn Vfs,
+        file_path: &Path,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<Vec<EntityIndex>> {
+        self.watch(vfs, file_path, WatchedKind::Scene, ecs, registry)
+    }
+
+    /// Instantiates a prefab whose entities are updated when its file changes,
+    /// and returns the index of the prefab's entity
+    pub fn load_prefab(
+        &mut self,
+        vfs: &dyn Vfs,
+        file_path: &Path,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+        overrides: BTreeMap<String, Value>,
+    ) -> CoreResult<EntityIndex> {
+        let entities = self.watch(
+            vfs,
+            file_path,
+            WatchedKind::Prefab { overrides },
+            ecs,
+            registry,
+        )?;
+        Ok(entities[0])
+    }
+
+    /// Watches the file of an asset, the asset is reported by
+    /// `drain_changed_assets` once its file changes
+    pub fn watch_asset(
+        &mut self,
+        vfs: &dyn Vfs,
+        identifier: &str,
+        file_path: &Path,
+    ) -> CoreResult<()> {
+        if self
+            .watched_assets
+            .iter()
+            .any(|watched_asset| watched_asset.identifier == identifier)
+        {
+            return Ok(());
+        }
+
+        let content = vfs.read(file_path).map_err(CoreError::AssetFileOpenError)?;
+        self.watched_assets.push(WatchedAsset {
+            identifier: identifier.into(),
+            path: file_path.into(),
+            content,
+        });
+        Ok(())
+    }
+
+    /// Returns the identifiers of the watched assets whose file changed since
+    /// the last call
+    pub fn drain_changed_assets(&mut self) -> impl Iterator<Item = String> + '_ {
+        self.changed_assets.drain(..)
+    }
+
+    /// Checks the watched files once the poll interval has elapsed
+    pub fn update(
+        &mut self,
+        delta_time: f64,
+        vfs: &dyn Vfs,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> Vec<PathBuf> {
+        self.elapsed_time += delta_time;
+        if self.elapsed_time < self.poll_interval {
+            return vec![];
+        }
+
+        self.elapsed_time = 0.0;
+        self.poll(vfs, ecs, registry)
+    }
+
+    /// Reloads the watched files whose content changed and returns their
+    /// paths. Files that can't be read or parsed are skipped until they are
+    /// fixed.
+    pub fn poll(
+        &mut self,
+        vfs: &dyn Vfs,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> Vec<PathBuf> {
+        let mut reloaded_files = vec![];
+        for watched_file in &mut self.watched_files {
+            let content = match vfs.read(&watched_file.path) {
+                Ok(content) if content != watched_file.content => content,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Couldn't read {}: {}", watched_file.path.display(), e);
+                    continue;
+                }
+            };
+
+            info!("Reloading {}", watched_file.path.display());
+            match watched_file.reload(content, ecs, registry) {
+                Ok(()) => reloaded_files.push(watched_file.path.clone()),
+                Err(e) => warn!("Couldn't reload {}: {:?}", watched_file.path.display(), e),
+            }
+        }
+
+        self.poll_assets(vfs);
+        reloaded_files
+    }
+
+    fn poll_assets(&mut self, vfs: &dyn Vfs) {
+        for watched_asset in &mut self.watched_assets {
+            match vfs.read(&watched_asset.path) {
+                Ok(content) if content != watched_asset.content => {
+                    info!("Asset {} changed", watched_asset.identifier);
+                    watched_asset.content = content;
+                    if !self.changed_assets.contains(&watched_asset.identifier) {
+                        self.changed_assets.push(watched_asset.identifier.clone());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Couldn't read {}: {}", watched_asset.path.display(), e),
+            }
+        }
+    }
+
+    fn watch(
+        &mut self,
+        vfs: &dyn Vfs,
+        file_path: &Path,
+        kind: WatchedKind,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<Vec<EntityIndex>> {
+        let content = vfs.read(file_path).map_err(CoreError::SceneFileOpenError)?;
+        let (descriptions, shared_resources) = describe(&kind, &content)?;
+        registry.ensure_registered(
+            descriptions
+                .iter()
+                .flat_map(|description| description.components.keys())
+                .chain(shared_resources.keys()),
+        )?;
+
+        for (name, value) in &shared_resources {
+            registry.insert_shared_resource(ecs, name, value.clone())?;
+        }
+
+        let mut entities = vec![];
+        for description in &descriptions {
+            let entity = spawn(description, &entities, ecs, registry)?;
+            entities.push(entity);
+        }
+
+        self.watched_files.push(WatchedFile {
+            path: file_path.into(),
+            kind,
+            content,
+            descriptions,
+            shared_resources,
+            entities: entities.clone(),
+        });
+        Ok(entities)
+    }
+}
+
+impl WatchedFile {
+    /// Updates the entities to match the new content of the file. The new
+    /// content is validated before any entity is touched, so a file that
+    /// can't be loaded leaves them as they were.
+    fn reload(
+        &mut self,
+        content: Vec<u8>,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+    ) -> CoreResult<()> {
+        let (descriptions, shared_resources) = describe(&self.kind, &content)?;
+        registry.ensure_registered(
+            descriptions
+                .iter()
+                .flat_map(|description| description.components.keys())
+                .chain(shared_resources.keys()),
+        )?;
+        validate(&descriptions, &shared_resources, registry)?;
+
+        for (name, value) in &shared_resources {
+            if self.shared_resources.get(name) != Some(value) {
+                registry.insert_shared_resource(ecs, name, value.clone())?;
+            }
+        }
+
+        for (index, description) in descriptions.iter().enumerate() {
+            if let (Some(&entity), Some(previous_description)) =
+                (self.entities.get(index), self.descriptions.get(index))
+            {
+                update(
+                    entity,
+                    previous_description,
+                    description,
+                    &self.entities,
+                    ecs,
+                    registry,
+                )?;
+            } else {
+                let entity = spawn(description, &self.entities, ecs, registry)?;
+                self.entities.push(entity);
+            }
+        }
+
+        if self.entities.len() > descriptions.len() {
+            ecs.delete_by_ids(&self.entities[descriptions.len()..]);
+            self.entities.truncate(descriptions.len());
+        }
+
+        self.content = content;
+        self.descriptions = descriptions;
+        self.shared_resources = shared_resources;
+        Ok(())
+    }
+}
+
+fn describe(
+    kind: &WatchedKind,
+    content: &[u8],
+) -> CoreResult<(Vec<FlattenedEntity>, BTreeMap<String, Value>)> {
+    match kind {
+        WatchedKind::Scene => {
+            let scene = Scene::from_json(content)?;
+            let descriptions = scene
+                .entities
+                .into_iter()
+                .map(|entity| FlattenedEntity {
+                    components: entity.components,
+                    parent: None,
+                })
+                .collect();
+            Ok((descriptions, scene.shared_resources))
+        }
+        WatchedKind::Prefab { overrides } => {
+            let prefab = Prefab::from_json(content)?;
+            Ok((prefab.flatten(overrides), BTreeMap::new()))
+        }
+    }
+}
+
+/// Checks that every component and shared resource can be deserialized by
+/// instantiating them in a scratch ECS
+fn validate(
+    descriptions: &[FlattenedEntity],
+    shared_resources: &BTreeMap<String, Value>,
+    registry: &ComponentRegistry,
+) -> CoreResult<()> {
+    let mut ecs = Ecs::default();
+    for (name, value) in shared_resources {
+        registry.insert_shared_resource(&mut ecs, name, value.clone())?;
+    }
+
+    for description in descriptions {
+        let entity = ecs.insert(());
+        for (name, value) in &description.components {
+            registry.insert_component(&mut ecs, entity, name, value.clone())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn spawn(
+    description: &FlattenedEntity,
+    entities: &[EntityIndex],
+    ecs: &mut Ecs,
+    registry: &ComponentRegistry,
+) -> CoreResult<EntityIndex> {
+    let entity = ecs.insert(());
+    for (name, value) in &description.components {
+        registry.insert_component(ecs, entity, name, value.clone())?;
+    }
+
+    if let Some(parent) = description.parent {
+        ecs.add_component(Parent(entities[parent]), entity);
+    }
+
+    Ok(entity)
+}
+
+fn update(
+    entity: EntityIndex,
+    previous_description: &FlattenedEntity,
+    description: &FlattenedEntity,
+    entities: &[EntityIndex],
+    ecs: &mut Ecs,
+    registry: &ComponentRegistry,
+) -> CoreResult<()> {
+    for name in previous_description.components.keys() {
+        if !description.components.contains_key(name) {
+            registry.remove_component(ecs, entity, name)?;
+        }
+    }
+
+    for (name, value) in &description.components {
+        if previous_description.components.get(name) != Some(value) {
+            registry.insert_component(ecs, entity, name, value.clone())?;
+        }
+    }
+
+    if previous_description.parent != description.parent {
+        match description.parent {
+            Some(parent) => ecs.add_component(Parent(entities[parent]), entity),
+            None => ecs.remove_component::<Parent>(entity),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vfs::InMemoryVfs;
+    use serde_derive::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Speed(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(f32);
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::new();
+        registry.register_component::<Health>("Health");
+        registry.register_component::<Speed>("Speed");
+        registry
+    }
+
+    fn health(ecs: &Ecs, entity: EntityIndex) -> Option<u32> {
+        ecs.query_one_by_id::<(&Health,)>(entity)
+            .map(|(_, (health,))| health.0)
+    }
+
+    #[test]
+    fn reload_scene_in_place() {
+        let registry = registry();
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [
+                { "components": { "Health": 3, "Speed": 1 } },
+                { "components": { "Health": 5 } }
+            ] }"#,
+        );
+        let mut ecs = Ecs::default();
+        let mut watcher = SceneWatcher::default();
+        let entities = watcher
+            .load_scene(&vfs, Path::new("level.json"), &mut ecs, &registry)
+            .unwrap();
+        ecs.add_component(Velocity(2.0), entities[0]);
+        ecs.add_component(Health(1), entities[1]);
+
+        assert!(watcher.poll(&vfs, &mut ecs, &registry).is_empty());
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [
+                { "components": { "Health": 4 } },
+                { "components": { "Health": 5 } },
+                { "components": { "Health": 6 } }
+            ] }"#,
+        );
+        let reloaded_files = watcher.poll(&vfs, &mut ecs, &registry);
+
+        assert_eq!(reloaded_files, vec![PathBuf::from("level.json")]);
+        assert_eq!(health(&ecs, entities[0]), Some(4));
+        assert!(ecs.query_one_by_id::<(&Speed,)>(entities[0]).is_none());
+        assert!(ecs.query_one_by_id::<(&Velocity,)>(entities[0]).is_some());
+        assert_eq!(health(&ecs, entities[1]), Some(1));
+        assert_eq!(ecs.query::<(&Health,)>().count(), 3);
+    }
+
+    #[test]
+    fn reload_prefab_children() {
+        let registry = registry();
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "snake.json",
+            r#"{ "components": { "Health": 3 }, "children": [{ "components": { "Speed": 1 } }] }"#,
+        );
+        let mut ecs = Ecs::default();
+        let mut watcher = SceneWatcher::default().with_poll_interval(0.5);
+        let snake = watcher
+            .load_prefab(
+                &vfs,
+                Path::new("snake.json"),
+                &mut ecs,
+                &registry,
+                BTreeMap::new(),
+            )
+            .unwrap();
+
+        vfs.insert_file("snake.json", r#"{ "components": { "Health": 3 } }"#);
+        assert!(watcher.update(0.25, &vfs, &mut ecs, &registry).is_empty());
+        assert_eq!(ecs.query::<(&Parent,)>().count(), 1);
+        assert_eq!(watcher.update(0.25, &vfs, &mut ecs, &registry).len(), 1);
+
+        assert_eq!(ecs.query::<(&Parent,)>().count(), 0);
+        assert_eq!(health(&ecs, snake), Some(3));
+    }
+
+    #[test]
+    fn reload_invalid_file() {
+        let registry = registry();
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [{ "components": { "Health": 3 } }] }"#,
+        );
+        let mut ecs = Ecs::default();
+        let mut watcher = SceneWatcher::default();
+        let entities = watcher
+            .load_scene(&vfs, Path::new("level.json"), &mut ecs, &registry)
+            .unwrap();
+
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [{ "components": { "Mana": 3 } }] }"#,
+        );
+
+        assert!(watcher.poll(&vfs, &mut ecs, &registry).is_empty());
+        assert_eq!(health(&ecs, entities[0]), Some(3));
+    }
+
+    #[test]
+    fn reload_partially_invalid_file() {
+        let registry = registry();
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [{ "components": { "Health": 3 } }] }"#,
+        );
+        let mut ecs = Ecs::default();
+        let mut watcher = SceneWatcher::default();
+        let entities = watcher
+            .load_scene(&vfs, Path::new("level.json"), &mut ecs, &registry)
+            .unwrap();
+
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [
+                { "components": { "Health": 4 } },
+                { "components": { "Health": 5 } },
+                { "components": { "Health": "invalid" } }
+            ] }"#,
+        );
+        assert!(watcher.poll(&vfs, &mut ecs, &registry).is_empty());
+        assert_eq!(health(&ecs, entities[0]), Some(3));
+        assert_eq!(ecs.query::<(&Health,)>().count(), 1);
+
+        vfs.insert_file(
+            "level.json",
+            r#"{ "entities": [
+                { "components": { "Health": 4 } },
+                { "components": { "Health": 5 } }
+            ] }"#,
+        );
+        assert_eq!(watcher.poll(&vfs, &mut ecs, &registry).len(), 1);
+        assert_eq!(health(&ecs, entities[0]), Some(4));
+        assert_eq!(ecs.query::<(&Health,)>().count(), 2);
+    }
+
+    #[test]
+    fn watch_asset() {
+        let registry = registry();
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("player.rhai", "fn update(entity, delta_time) {}");
+        let mut ecs = Ecs::default();
+        let mut watcher = SceneWatcher::default();
+        watcher
+            .watch_asset(&vfs, "player", Path::new("player.rhai"))
+            .unwrap();
+
+        watcher.poll(&vfs, &mut ecs, &registry);
+        assert_eq!(watcher.drain_changed_assets().count(), 0);
+        vfs.insert_file("player.rhai", "fn update(entity, delta_time) { 1 }");
+        watcher.poll(&vfs, &mut ecs, &registry);
+        watcher.poll(&vfs, &mut ecs, &registry);
+
+        assert_eq!(
+            watcher.drain_changed_assets().collect::<Vec<_>>(),
+            vec!["player".to_string()]
+        );
+        assert!(matches!(
+            watcher.watch_asset(&vfs, "enemy", Path::new("enemy.rhai")),
+            Err(CoreError::AssetFileOpenError(_))
+        ));
+    }
+}
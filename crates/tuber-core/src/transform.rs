@@ -1,8 +1,10 @@
+use serde_derive::{Deserialize, Serialize};
+use tuber_ecs::ecs::Ecs;
 use tuber_math::matrix::Matrix4f;
 use tuber_math::quaternion::Quaternion;
 use tuber_math::vector::Vector3;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Transform {
     pub translation: Vector3<f32>,
     pub angle: Vector3<f32>,
@@ -21,6 +23,59 @@ impl Default for Transform {
     }
 }
 
+impl Transform {
+    /// Interpolates between two transforms, rotating along the shortest path.
+    /// Interpolated angles are in [-π, π].
+    #[must_use]
+    pub fn lerp(a: &Transform, b: &Transform, t: f32) -> Transform {
+        let angle = if a.angle == b.angle {
+            a.angle
+        } else {
+            Quaternion::from_euler(&a.angle)
+                .slerp(&Quaternion::from_euler(&b.angle), t)
+                .to_euler()
+        };
+
+        Transform {
+            translation: a.translation + (b.translation - a.translation) * t,
+            angle,
+            rotation_center: a.rotation_center + (b.rotation_center - a.rotation_center) * t,
+            scale: a.scale + (b.scale - a.scale) * t,
+        }
+    }
+}
+
+/// The transform of an entity at the previous simulation step. Entities
+/// having one are rendered between their previous and current transforms, so
+/// their movement looks smooth when frames are rendered more often than the
+/// simulation steps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviousTransform(pub Transform);
+
+impl PreviousTransform {
+    /// Returns the transform to render an entity with, `interpolation` being
+    /// the [`StepInterpolation`] of the frame
+    #[must_use]
+    pub fn interpolate(&self, current: &Transform, interpolation: f32) -> Transform {
+        Transform::lerp(&self.0, current, interpolation)
+    }
+}
+
+/// How far the rendered frame is between the previous simulation step and the
+/// current one, in [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StepInterpolation(pub f32);
+
+/// Saves the transforms of the entities having a [`PreviousTransform`], to be
+/// run before the simulation step moves them
+pub fn store_previous_transforms(ecs: &mut Ecs) {
+    for (_, (transform, mut previous_transform)) in
+        ecs.query::<(&Transform, &mut PreviousTransform)>()
+    {
+        previous_transform.0 = *transform;
+    }
+}
+
 pub trait AsMatrix4 {
     fn as_matrix4(&self) -> Matrix4f;
 }
@@ -36,3 +91,66 @@ impl AsMatrix4 for Transform {
             * Matrix4f::new_translation(&-translate_to_rotation_center)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    #[test]
+    fn lerp() {
+        let a = Transform {
+            angle: (0.0, 0.0, 0.9 * PI).into(),
+            ..Transform::default()
+        };
+        let b = Transform {
+            translation: (10.0, -4.0, 0.0).into(),
+            angle: (0.0, 0.0, -0.9 * PI).into(),
+            scale: (3.0, 1.0, 1.0).into(),
+            ..Transform::default()
+        };
+
+        let halfway = Transform::lerp(&a, &b, 0.5);
+
+        assert!(approx_eq(halfway.translation.x, 5.0));
+        assert!(approx_eq(halfway.translation.y, -2.0));
+        assert!(approx_eq(halfway.scale.x, 2.0));
+        // Goes through π rather than 0, the shortest path
+        assert!(approx_eq(halfway.angle.z.abs(), PI));
+        assert_eq!(Transform::lerp(&a, &b, 1.0).translation, b.translation);
+    }
+
+    #[test]
+    fn store_and_interpolate_previous_transforms() {
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert((
+            Transform::default(),
+            PreviousTransform(Transform::default()),
+        ));
+
+        ecs.query_one_by_id::<(&mut Transform,)>(entity)
+            .unwrap()
+            .1
+             .0
+            .translation
+            .x = 4.0;
+        store_previous_transforms(&mut ecs);
+        ecs.query_one_by_id::<(&mut Transform,)>(entity)
+            .unwrap()
+            .1
+             .0
+            .translation
+            .x = 8.0;
+
+        let (_, (transform, previous_transform)) = ecs
+            .query_one_by_id::<(&Transform, &PreviousTransform)>(entity)
+            .unwrap();
+        let rendered = previous_transform.interpolate(&transform, 0.25);
+        assert!(approx_eq(rendered.translation.x, 5.0));
+    }
+}
@@ -0,0 +1,323 @@
+//! Tweens: components interpolating a field of another component of the same
+//! entity over time.
+
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemResult;
+use tuber_ecs::EntityIndex;
+use tuber_math::color::Color;
+use tuber_math::interpolation::lerp;
+use tuber_math::vector::Vector3;
+
+pub use tuber_math::easing::Easing;
+
+use crate::DeltaTime;
+
+/// A value that can be interpolated by a tween
+pub trait Tweenable: Copy {
+    /// Interpolates between self and to, t being in [0, 1]
+    #[must_use]
+    fn interpolate(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        lerp(*self, *to, t)
+    }
+}
+
+impl Tweenable for Vector3<f32> {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        *self + (*to - *self) * t
+    }
+}
+
+/// Colors are tweened as RGBA arrays
+impl Tweenable for [f32; 4] {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        let mut value = *self;
+        for (component, to) in value.iter_mut().zip(to) {
+            *component = component.interpolate(to, t);
+        }
+        value
+    }
+}
+
+impl Tweenable for Color {
+    fn interpolate(&self, to: &Self, t: f32) -> Self {
+        self.lerp(to, t)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    /// Plays the given number of cycles
+    Times(u32),
+    Forever,
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Times(1)
+    }
+}
+
+/// Interpolates the field of a component of type C returned by the accessor
+pub struct Tween<C, T: Tweenable> {
+    accessor: fn(&mut C) -> &mut T,
+    from: T,
+    to: T,
+    /// The duration of a cycle, in seconds
+    duration: f64,
+    easing: Easing,
+    repeat: Repeat,
+    /// Plays every other cycle backwards
+    yoyo: bool,
+    elapsed_time: f64,
+    completed_cycles: u32,
+    finished: bool,
+}
+
+impl<C, T: Tweenable> Tween<C, T> {
+    #[must_use]
+    pub fn new(accessor: fn(&mut C) -> &mut T, from: T, to: T, duration: f64) -> Self {
+        Self {
+            accessor,
+            from,
+            to,
+            duration,
+            easing: Easing::default(),
+            repeat: Repeat::default(),
+            yoyo: false,
+            elapsed_time: 0.0,
+            completed_cycles: 0,
+            finished: false,
+        }
+    }
+
+    #[must_use]
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    #[must_use]
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    #[must_use]
+    pub fn with_yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Returns the interpolated value at the current time
+    #[must_use]
+    pub fn value(&self) -> T {
+        let (cycle, progress) = if self.finished {
+            (self.completed_cycles.saturating_sub(1), 1.0)
+        } else if self.duration <= 0.0 {
+            (self.completed_cycles, 0.0)
+        } else {
+            #[allow(clippy::cast_possible_truncation)]
+            let progress = (self.elapsed_time / self.duration) as f32;
+            (self.completed_cycles, progress)
+        };
+
+        let progress = if self.yoyo && cycle % 2 == 1 {
+            1.0 - progress
+        } else {
+            progress
+        };
+
+        self.from.interpolate(&self.to, self.easing.apply(progress))
+    }
+
+    /// Advances the tween by the given time in seconds, returns true if it
+    /// finished during this step
+    pub fn advance(&mut self, delta_time: f64) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        self.elapsed_time += delta_time;
+        while self.elapsed_time >= self.duration {
+            self.elapsed_time -= self.duration;
+            self.completed_cycles += 1;
+            if let Repeat::Times(cycles) = self.repeat {
+                if self.completed_cycles >= cycles {
+                    self.elapsed_time = 0.0;
+                    self.finished = true;
+                    return true;
+                }
+            }
+
+            if self.duration <= 0.0 {
+                break;
+            }
+        }
+
+        false
+    }
+
+    /// Writes the current value into the tweened field of the component
+    pub fn apply(&self, component: &mut C) {
+        *(self.accessor)(component) = self.value();
+    }
+}
+
+/// Sent when a tween of the entity finishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TweenCompleted {
+    pub entity: EntityIndex,
+}
+
+/// The shared resource the tween completion events are stored in until they
+/// are drained
+#[derive(Debug, Default)]
+pub struct TweenEvents {
+    events: Vec<TweenCompleted>,
+}
+
+impl TweenEvents {
+    pub fn drain(&mut self) -> Vec<TweenCompleted> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// Advances the tweens of type `Tween<C, T>` and applies them to their
+/// entity's C component
+pub fn update_tweens<C: 'static, T: Tweenable + 'static>(ecs: &mut Ecs) -> SystemResult {
+    let delta_time = match ecs.shared_resource::<DeltaTime>() {
+        Some(delta_time) => delta_time.0,
+        None => return Ok(()),
+    };
+
+    let mut completed_tweens = vec![];
+    for (entity, (mut tween, mut component)) in ecs.query::<(&mut Tween<C, T>, &mut C)>() {
+        if tween.is_finished() {
+            continue;
+        }
+
+        if tween.advance(delta_time) {
+            completed_tweens.push(TweenCompleted { entity });
+        }
+
+        tween.apply(&mut component);
+    }
+
+    if completed_tweens.is_empty() {
+        return Ok(());
+    }
+
+    if ecs.shared_resource::<TweenEvents>().is_none() {
+        ecs.insert_shared_resource(TweenEvents::default());
+    }
+
+    if let Some(mut tween_events) = ecs.shared_resource_mut::<TweenEvents>() {
+        tween_events.events.extend(completed_tweens);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::Transform;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    fn translation(transform: &mut Transform) -> &mut Vector3<f32> {
+        &mut transform.translation
+    }
+
+    #[test]
+    fn tween() {
+        let mut tween = Tween::new(
+            translation,
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(10.0, 20.0, 0.0),
+            2.0,
+        );
+
+        assert!(!tween.advance(0.5));
+        assert_eq!(tween.value(), Vector3::new(2.5, 5.0, 0.0));
+        assert!(tween.advance(1.5));
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), Vector3::new(10.0, 20.0, 0.0));
+        assert!(!tween.advance(1.0));
+    }
+
+    #[test]
+    fn tween_repeat_yoyo() {
+        let mut tween = Tween::new(|value: &mut f32| value, 0.0, 1.0, 1.0)
+            .with_repeat(Repeat::Times(3))
+            .with_yoyo(true);
+
+        tween.advance(1.25);
+        assert!(approx_eq(tween.value(), 0.75));
+        tween.advance(1.0);
+        assert!(approx_eq(tween.value(), 0.25));
+        assert!(tween.advance(1.0));
+        assert!(approx_eq(tween.value(), 1.0));
+    }
+
+    #[test]
+    fn tween_repeat_forever() {
+        let mut tween =
+            Tween::new(|value: &mut f32| value, 0.0, 1.0, 1.0).with_repeat(Repeat::Forever);
+
+        assert!(!tween.advance(100.5));
+        assert!(!tween.is_finished());
+        assert!(approx_eq(tween.value(), 0.5));
+    }
+
+    #[test]
+    fn tween_color() {
+        let color = [0.0, 1.0, 0.0, 1.0].interpolate(&[1.0, 0.0, 0.0, 0.5], 0.5);
+
+        for (component, expected) in color.iter().zip([0.5, 0.5, 0.0, 0.75]) {
+            assert!(approx_eq(*component, expected));
+        }
+    }
+
+    #[test]
+    fn update_tweens_system() {
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert((
+            Transform::default(),
+            Tween::new(
+                translation,
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(4.0, 0.0, 0.0),
+                1.0,
+            ),
+        ));
+        ecs.insert_shared_resource(DeltaTime(0.5));
+
+        update_tweens::<Transform, Vector3<f32>>(&mut ecs).unwrap();
+        {
+            let (_, (transform,)) = ecs.query_one::<(&Transform,)>().unwrap();
+            assert_eq!(transform.translation, Vector3::new(2.0, 0.0, 0.0));
+        }
+        assert!(ecs.shared_resource::<TweenEvents>().is_none());
+
+        update_tweens::<Transform, Vector3<f32>>(&mut ecs).unwrap();
+
+        let (_, (transform,)) = ecs.query_one::<(&Transform,)>().unwrap();
+        assert_eq!(transform.translation, Vector3::new(4.0, 0.0, 0.0));
+        assert_eq!(
+            ecs.shared_resource_mut::<TweenEvents>().unwrap().drain(),
+            vec![TweenCompleted { entity }]
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! The vfs module abstracts the file accesses of the engine so assets and
+//! configuration files can come from somewhere else than the OS file system.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{self, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use zip::read::ZipFile;
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+/// A read-only file system.
+///
+/// Paths are relative to the root of the file system.
+pub trait Vfs {
+    /// Reads the whole content of a file
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Returns the size of a file in bytes
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.read(path).map(|content| content.len() as u64)
+    }
+
+    /// Returns true if the path points to a file
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Returns true if the path points to a directory
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Returns the paths of the entries of a directory
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+}
+
+/// A file system backed by a directory of the OS file system
+pub struct OsVfs {
+    root: PathBuf,
+}
+
+impl OsVfs {
+    #[must_use]
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+}
+
+impl Default for OsVfs {
+    /// Creates a file system rooted at the application directory, or at the
+    /// current directory if the application directory is inaccessible.
+    fn default() -> Self {
+        Self::new(crate::application_directory().unwrap_or_default())
+    }
+}
+
+impl Vfs for OsVfs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        std::fs::read(self.root.join(path))
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        Ok(std::fs::metadata(self.root.join(path))?.len())
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.root.join(path).is_file()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.root.join(path).is_dir()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        std::fs::read_dir(self.root.join(path))?
+            .map(|entry| Ok(path.join(entry?.file_name())))
+            .collect()
+    }
+}
+
+/// A file system whose files are stored in memory
+#[derive(Default)]
+pub struct InMemoryVfs {
+    files: HashMap<PathBuf, Vec<u8>>,
+}
+
+impl InMemoryVfs {
+    /// Inserts a file, its parent directories are created implicitly
+    pub fn insert_file(&mut self, path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) {
+        self.files.insert(path.into(), content.into());
+    }
+}
+
+impl Vfs for InMemoryVfs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files.get(path).cloned().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File not found: {}", path.display()),
+            )
+        })
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.files
+            .get(path)
+            .map(|content| content.len() as u64)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("File not found: {}", path.display()),
+                )
+            })
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains_key(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        is_dir(self.files.keys(), path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        read_dir(self.files.keys(), path)
+    }
+}
+
+/// A file system stored in a zip archive, such as the packed assets of a
+/// release build
+pub struct ZipVfs<R: Read + Seek = File> {
+    archive: Mutex<ZipArchive<R>>,
+    files: BTreeSet<PathBuf>,
+}
+
+impl ZipVfs<File> {
+    /// Opens the zip archive at the given path of the OS file system
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> ZipVfs<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        let archive = ZipArchive::new(reader)?;
+        let files = archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(PathBuf::from)
+            .collect();
+
+        Ok(Self {
+            archive: Mutex::new(archive),
+            files,
+        })
+    }
+}
+
+impl<R: Read + Seek> ZipVfs<R> {
+    /// Runs a function on a file of the archive
+    fn with_file<T>(
+        &self,
+        path: &Path,
+        function: impl FnOnce(&mut ZipFile) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let not_found = || {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File not found: {}", path.display()),
+            )
+        };
+        if !self.files.contains(path) {
+            return Err(not_found());
+        }
+
+        let name = path.to_string_lossy().replace('\\', "/");
+        let mut archive = self.archive.lock().unwrap();
+        let mut file = archive.by_name(&name).map_err(|error| match error {
+            ZipError::FileNotFound => not_found(),
+            error => error.into(),
+        })?;
+        function(&mut file)
+    }
+}
+
+impl<R: Read + Seek> Vfs for ZipVfs<R> {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.with_file(path, |file| {
+            let mut content = vec![];
+            file.read_to_end(&mut content)?;
+            Ok(content)
+        })
+    }
+
+    fn file_size(&self, path: &Path) -> io::Result<u64> {
+        self.with_file(path, |file| Ok(file.size()))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.files.contains(path)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        is_dir(self.files.iter(), path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        read_dir(self.files.iter(), path)
+    }
+}
+
+/// Returns true if one of the file paths is inside the directory
+fn is_dir<'a>(mut file_paths: impl Iterator<Item = &'a PathBuf>, path: &Path) -> bool {
+    file_paths.any(|file_path| file_path != path && file_path.starts_with(path))
+}
+
+/// Returns the entries of a directory from the paths of the files of a file
+/// system whose directories are implicit
+fn read_dir<'a>(
+    file_paths: impl Iterator<Item = &'a PathBuf> + Clone,
+    path: &Path,
+) -> io::Result<Vec<PathBuf>> {
+    if !is_dir(file_paths.clone(), path) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Directory not found: {}", path.display()),
+        ));
+    }
+
+    let entries: BTreeSet<PathBuf> = file_paths
+        .filter_map(|file_path| {
+            let entry_name = file_path.strip_prefix(path).ok()?.components().next()?;
+            Some(path.join(entry_name))
+        })
+        .collect();
+
+    Ok(entries.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vfs() -> InMemoryVfs {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("keymap.json", "{}");
+        vfs.insert_file("assets/font/asset.json", "font");
+        vfs.insert_file("assets/font/font.png", "png");
+        vfs.insert_file("assets/sprite/asset.json", "sprite");
+        vfs
+    }
+
+    #[test]
+    fn in_memory_read() {
+        let vfs = vfs();
+
+        assert_eq!(vfs.read(Path::new("keymap.json")).unwrap(), b"{}");
+        assert_eq!(
+            vfs.read(Path::new("assets/sprite/asset.json")).unwrap(),
+            b"sprite"
+        );
+        assert_eq!(
+            vfs.read(Path::new("assets/missing.json"))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn in_memory_is_file_is_dir() {
+        let vfs = vfs();
+
+        assert!(vfs.is_file(Path::new("assets/font/font.png")));
+        assert!(!vfs.is_file(Path::new("assets/font")));
+        assert!(vfs.is_dir(Path::new("assets/font")));
+        assert!(vfs.is_dir(Path::new("")));
+        assert!(!vfs.is_dir(Path::new("keymap.json")));
+        assert!(!vfs.is_dir(Path::new("scenes")));
+    }
+
+    #[test]
+    fn in_memory_read_dir() {
+        let vfs = vfs();
+
+        assert_eq!(
+            vfs.read_dir(Path::new("assets")).unwrap(),
+            vec![PathBuf::from("assets/font"), PathBuf::from("assets/sprite")]
+        );
+        assert_eq!(
+            vfs.read_dir(Path::new("")).unwrap(),
+            vec![PathBuf::from("assets"), PathBuf::from("keymap.json")]
+        );
+        assert!(vfs.read_dir(Path::new("scenes")).is_err());
+    }
+
+    #[test]
+    fn zip_archive() {
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+        for (path, content) in [
+            ("keymap.json", "{}"),
+            ("assets/font/asset.json", "font"),
+            ("assets/font/font.png", "png"),
+        ] {
+            writer
+                .start_file(path, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            io::Write::write_all(&mut writer, content.as_bytes()).unwrap();
+        }
+        writer
+            .add_directory("assets/empty/", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        let vfs = ZipVfs::new(writer.finish().unwrap()).unwrap();
+
+        assert_eq!(
+            vfs.read(Path::new("assets/font/asset.json")).unwrap(),
+            b"font"
+        );
+        assert_eq!(
+            vfs.read(Path::new("assets/missing.json"))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+        assert_eq!(vfs.file_size(Path::new("assets/font/font.png")).unwrap(), 3);
+        assert!(vfs.is_file(Path::new("keymap.json")));
+        assert!(vfs.is_dir(Path::new("assets/font")));
+        assert_eq!(
+            vfs.read_dir(Path::new("")).unwrap(),
+            vec![PathBuf::from("assets"), PathBuf::from("keymap.json")]
+        );
+        assert_eq!(
+            vfs.read_dir(Path::new("assets")).unwrap(),
+            vec![PathBuf::from("assets/font")]
+        );
+    }
+}
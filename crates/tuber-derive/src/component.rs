@@ -0,0 +1,123 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitStr, Path};
+
+struct ComponentAttributes {
+    name: String,
+    persistent: bool,
+    crate_path: Path,
+}
+
+impl ComponentAttributes {
+    fn parse(input: &DeriveInput) -> syn::Result<Self> {
+        let mut attributes = Self {
+            name: input.ident.to_string(),
+            persistent: false,
+            crate_path: syn::parse_quote!(::tuber_core),
+        };
+
+        for attribute in &input.attrs {
+            if !attribute.path().is_ident("component") {
+                continue;
+            }
+
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    attributes.name = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("persistent") {
+                    attributes.persistent = true;
+                } else if meta.path.is_ident("crate") {
+                    attributes.crate_path = meta.value()?.parse::<LitStr>()?.parse()?;
+                } else {
+                    return Err(meta.error("unsupported component attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attributes)
+    }
+}
+
+pub(crate) fn expand_component(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let ComponentAttributes {
+        name,
+        persistent,
+        crate_path,
+    } = ComponentAttributes::parse(input)?;
+    let fields = component_fields(input)?;
+    let field_names = fields.iter().map(|(name, _)| name);
+    let field_types = fields.iter().map(|(_, field_type)| field_type);
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let persistent_impl = persistent.then(|| {
+        quote! {
+            impl #impl_generics #crate_path::registry::Persistent
+                for #ident #type_generics #where_clause {}
+        }
+    });
+
+    // Generic components have no single type to register, they are
+    // registered manually for each of their instantiations
+    let registration = input.generics.params.is_empty().then(|| {
+        quote! {
+            #crate_path::registry::inventory::submit! {
+                #crate_path::registry::ComponentRegistration::of::<#ident>()
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #crate_path::registry::Component
+            for #ident #type_generics #where_clause
+        {
+            const NAME: &'static str = #name;
+            const PERSISTENT: bool = #persistent;
+
+            fn fields() -> &'static [#crate_path::registry::ComponentField] {
+                &[#(#crate_path::registry::ComponentField {
+                    name: #field_names,
+                    type_name: #field_types,
+                }),*]
+            }
+        }
+
+        #persistent_impl
+        #registration
+    })
+}
+
+/// Returns the name and the type of every field, tuple struct fields are
+/// named after their index
+fn component_fields(input: &DeriveInput) -> syn::Result<Vec<(String, String)>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Component can only be derived for structs",
+        ));
+    };
+
+    let fields = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .filter_map(|field| field.ident.as_ref().map(|ident| (ident.to_string(), field)))
+            .collect(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (index.to_string(), field))
+            .collect(),
+        Fields::Unit => vec![],
+    };
+
+    Ok(fields
+        .into_iter()
+        .map(|(name, field)| {
+            let field_type = &field.ty;
+            (name, quote!(#field_type).to_string().replace(' ', ""))
+        })
+        .collect())
+}
@@ -0,0 +1,164 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, Field, Fields, LitStr, Path, Type};
+
+const NUMBER_TYPES: [&str; 14] = [
+    "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128",
+    "usize",
+];
+
+#[derive(Default)]
+struct FieldAttributes {
+    skip: bool,
+    read_only: bool,
+    color: bool,
+    range: Option<(Option<Expr>, Option<Expr>)>,
+}
+
+impl FieldAttributes {
+    fn parse(field: &Field) -> syn::Result<Self> {
+        let mut attributes = Self::default();
+        for attribute in &field.attrs {
+            if !attribute.path().is_ident("inspect") {
+                continue;
+            }
+
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    attributes.skip = true;
+                } else if meta.path.is_ident("read_only") {
+                    attributes.read_only = true;
+                } else if meta.path.is_ident("color") {
+                    attributes.color = true;
+                } else if meta.path.is_ident("range") {
+                    let (mut min, mut max) = (None, None);
+                    meta.parse_nested_meta(|bound| {
+                        if bound.path.is_ident("min") {
+                            min = Some(bound.value()?.parse()?);
+                        } else if bound.path.is_ident("max") {
+                            max = Some(bound.value()?.parse()?);
+                        } else {
+                            return Err(bound.error("expected min or max"));
+                        }
+                        Ok(())
+                    })?;
+                    attributes.range = Some((min, max));
+                } else {
+                    return Err(meta.error("unsupported inspect attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attributes)
+    }
+}
+
+pub(crate) fn expand_inspect(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let mut crate_path: Path = syn::parse_quote!(::tuber_core);
+    for attribute in &input.attrs {
+        if !attribute.path().is_ident("inspect") {
+            continue;
+        }
+
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                crate_path = meta.value()?.parse::<LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported inspect attribute"))
+            }
+        })?;
+    }
+
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Inspect can only be derived for structs",
+        ));
+    };
+
+    let mut fields = vec![];
+    for (index, field) in data.fields.iter().enumerate() {
+        let attributes = FieldAttributes::parse(field)?;
+        if attributes.skip {
+            continue;
+        }
+
+        let name = match (&data.fields, &field.ident) {
+            (Fields::Named(_), Some(ident)) => ident.to_string(),
+            _ => index.to_string(),
+        };
+        let editor = field_editor(&field.ty, &attributes, &crate_path);
+        fields.push(quote! {
+            #crate_path::inspect::InspectField {
+                name: #name,
+                editor: #editor,
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #crate_path::inspect::Inspect
+            for #ident #type_generics #where_clause
+        {
+            fn inspect_fields() -> &'static [#crate_path::inspect::InspectField] {
+                &[#(#fields),*]
+            }
+        }
+    })
+}
+
+/// Picks the editor of a field from its attributes, or from its type
+fn field_editor(field_type: &Type, attributes: &FieldAttributes, crate_path: &Path) -> TokenStream {
+    let editor = quote!(#crate_path::inspect::FieldEditor);
+    if attributes.read_only {
+        return quote!(#editor::ReadOnly);
+    }
+
+    if attributes.color {
+        return quote!(#editor::Color);
+    }
+
+    if let Some((min, max)) = &attributes.range {
+        let min = bound(min.as_ref());
+        let max = bound(max.as_ref());
+        return quote!(#editor::Number { min: #min, max: #max });
+    }
+
+    match field_type {
+        Type::Path(path) => {
+            let Some(ident) = path
+                .path
+                .segments
+                .last()
+                .map(|segment| segment.ident.to_string())
+            else {
+                return quote!(#editor::ReadOnly);
+            };
+
+            match ident.as_str() {
+                "bool" => quote!(#editor::Checkbox),
+                "String" => quote!(#editor::Text),
+                "Vector2" => quote!(#editor::Vector { components: 2 }),
+                "Vector3" => quote!(#editor::Vector { components: 3 }),
+                "Vector4" => quote!(#editor::Vector { components: 4 }),
+                number if NUMBER_TYPES.contains(&number) => {
+                    quote!(#editor::Number { min: None, max: None })
+                }
+                _ => quote!(#editor::ReadOnly),
+            }
+        }
+        Type::Array(array) => {
+            let length = &array.len;
+            quote!(#editor::Vector { components: #length })
+        }
+        _ => quote!(#editor::ReadOnly),
+    }
+}
+
+fn bound(value: Option<&Expr>) -> TokenStream {
+    value.map_or_else(|| quote!(None), |value| quote!(Some(#value as f64)))
+}
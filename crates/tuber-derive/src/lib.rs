@@ -0,0 +1,78 @@
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+
+//! Derive macros of the tuber engine
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, LitStr, Path};
+
+mod component;
+mod inspect;
+mod system;
+
+/// Implements `tuber_core::registry::Component` and submits the component to
+/// the registrations collected by `ComponentRegistry::new`, which registers it
+/// under its type name. Generic components aren't submitted and are registered
+/// with `ComponentRegistry::register` instead.
+///
+/// The `#[component(...)]` attribute accepts:
+/// - `name = "..."` to register the component under another name
+/// - `persistent` to store the component in save games
+/// - `crate = "..."` to set the path of tuber-core, e.g. `tuber::core`
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    component::expand_component(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `tuber_core::inspect::Inspect`, describing the editor of every
+/// field in the entity inspector. The editor is inferred from the type of the
+/// field and can be changed with the `#[inspect(...)]` field attribute:
+/// - `range(min = ..., max = ...)` for a number field clamped to the range
+/// - `color` for an RGBA color picker
+/// - `read_only` to display the field without editing it
+/// - `skip` to hide the field
+///
+/// `#[inspect(crate = "...")]` on the struct sets the path of tuber-core.
+#[proc_macro_derive(Inspect, attributes(inspect))]
+pub fn derive_inspect(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    inspect::expand_inspect(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Turns a function taking its queries, shared resources and context as
+/// parameters into a system, along with a `<name>_access` function returning
+/// the types the system reads and writes.
+///
+/// The supported parameter types are:
+/// - `SystemQuery<Q>`, iterating the entities matching the query `Q`
+/// - `Res<T>` and `ResMut<T>`, the system is skipped if the shared resource
+///   is missing, `Option<Res<T>>` and `Option<ResMut<T>>` run it anyway
+/// - `&Ecs` and `&mut Ecs`, `&mut Ecs` can't be combined with the other
+///   parameters borrowing the Ecs
+/// - any other mutable reference, which receives the additional data of the
+///   system bundle, e.g. `&mut EngineContext`
+///
+/// `#[system(crate = "...")]` sets the path of tuber-ecs, e.g. `tuber::ecs`.
+#[proc_macro_attribute]
+pub fn system(attributes: TokenStream, item: TokenStream) -> TokenStream {
+    let function = parse_macro_input!(item as syn::ItemFn);
+    let mut crate_path: Path = syn::parse_quote!(::tuber_ecs);
+    let attribute_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("crate") {
+            crate_path = meta.value()?.parse::<LitStr>()?.parse()?;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported system attribute"))
+        }
+    });
+    parse_macro_input!(attributes with attribute_parser);
+
+    system::expand_system(&function, &crate_path)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,278 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{FnArg, GenericArgument, Ident, ItemFn, Path, PathArguments, ReturnType, Type};
+
+enum SystemParameter {
+    Query(Type),
+    SharedResource {
+        resource: Type,
+        mutable: bool,
+        optional: bool,
+    },
+    Ecs {
+        mutable: bool,
+    },
+    AdditionalData(Type),
+}
+
+impl SystemParameter {
+    fn parse(parameter_type: &Type) -> syn::Result<Self> {
+        if let Type::Reference(reference) = parameter_type {
+            let mutable = reference.mutability.is_some();
+            if last_segment_ident(&reference.elem).is_some_and(|ident| ident == "Ecs") {
+                return Ok(Self::Ecs { mutable });
+            }
+
+            if mutable {
+                return Ok(Self::AdditionalData((*reference.elem).clone()));
+            }
+        }
+
+        if let Some((ident, argument)) = single_type_argument(parameter_type) {
+            if ident == "SystemQuery" {
+                return Ok(Self::Query(argument));
+            }
+
+            if ident == "Res" || ident == "ResMut" {
+                return Ok(Self::SharedResource {
+                    resource: argument,
+                    mutable: ident == "ResMut",
+                    optional: false,
+                });
+            }
+
+            if ident == "Option" {
+                if let Some((ident, resource)) = single_type_argument(&argument) {
+                    if ident == "Res" || ident == "ResMut" {
+                        return Ok(Self::SharedResource {
+                            resource,
+                            mutable: ident == "ResMut",
+                            optional: true,
+                        });
+                    }
+                }
+            }
+        }
+
+        Err(syn::Error::new_spanned(
+            parameter_type,
+            "unsupported system parameter, expected SystemQuery, Res, ResMut, Ecs or a mutable reference to the additional data",
+        ))
+    }
+
+    /// Returns the statement binding the argument passed to the system
+    /// function, `skip` returning early if a shared resource is missing
+    fn binding(
+        &self,
+        argument: &Ident,
+        ecs: &Ident,
+        additional_data: &Ident,
+        skip: &TokenStream,
+    ) -> TokenStream {
+        match self {
+            Self::Query(query) => quote!(let #argument = #ecs.query::<#query>();),
+            Self::SharedResource {
+                resource,
+                mutable,
+                optional,
+            } => {
+                let getter = if *mutable {
+                    quote!(shared_resource_mut)
+                } else {
+                    quote!(shared_resource)
+                };
+                if *optional {
+                    quote!(let #argument = #ecs.#getter::<#resource>();)
+                } else {
+                    quote!(let Some(#argument) = #ecs.#getter::<#resource>() else { #skip; };)
+                }
+            }
+            Self::Ecs { mutable: true } => quote!(let #argument = &mut *#ecs;),
+            Self::Ecs { mutable: false } => quote!(let #argument = &*#ecs;),
+            Self::AdditionalData(_) => quote!(let #argument = &mut *#additional_data;),
+        }
+    }
+
+    /// Returns the `SystemAccess` builder call declaring the parameter
+    fn access(&self) -> Option<TokenStream> {
+        match self {
+            Self::Query(query) => Some(quote!(.with_query::<#query>())),
+            Self::SharedResource {
+                resource,
+                mutable: true,
+                ..
+            } => Some(quote!(.with_write::<#resource>())),
+            Self::SharedResource { resource, .. } => Some(quote!(.with_read::<#resource>())),
+            Self::Ecs { .. } => Some(quote!(.with_exclusive())),
+            Self::AdditionalData(_) => None,
+        }
+    }
+}
+
+pub(crate) fn expand_system(function: &ItemFn, crate_path: &Path) -> syn::Result<TokenStream> {
+    if !function.sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &function.sig.generics,
+            "systems can't be generic",
+        ));
+    }
+
+    let parameters = function
+        .sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            FnArg::Typed(input) => SystemParameter::parse(&input.ty),
+            FnArg::Receiver(receiver) => {
+                Err(syn::Error::new_spanned(receiver, "systems can't take self"))
+            }
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+    check_exclusive_ecs_borrow(function, &parameters)?;
+
+    let ecs = Ident::new("ecs", Span::mixed_site());
+    let additional_data = Ident::new("additional_data", Span::mixed_site());
+    let skip = match &function.sig.output {
+        ReturnType::Default => quote!(return),
+        ReturnType::Type(..) => quote!(return Ok(())),
+    };
+
+    let mut additional_data_type = None;
+    let mut bindings = vec![];
+    let mut arguments = vec![];
+    let mut accesses = vec![];
+    for (index, parameter) in parameters.iter().enumerate() {
+        let argument = format_ident!("argument_{}", index, span = Span::mixed_site());
+        if let SystemParameter::AdditionalData(data_type) = parameter {
+            if additional_data_type.replace(data_type).is_some() {
+                return Err(syn::Error::new_spanned(
+                    data_type,
+                    "systems take a single additional data parameter",
+                ));
+            }
+        }
+
+        bindings.push(parameter.binding(&argument, &ecs, &additional_data, &skip));
+        accesses.extend(parameter.access());
+        arguments.push(argument);
+    }
+
+    let attributes = &function.attrs;
+    let visibility = &function.vis;
+    let name = &function.sig.ident;
+    let access_name = format_ident!("{}_access", name);
+    let output = &function.sig.output;
+    let inner_function = ItemFn {
+        attrs: vec![],
+        vis: syn::Visibility::Inherited,
+        ..function.clone()
+    };
+    let (generics, additional_data_type) = if let Some(data_type) = additional_data_type {
+        (quote!(), quote!(#data_type))
+    } else {
+        let generic = Ident::new("AD", Span::mixed_site());
+        (quote!(<#generic>), quote!(#generic))
+    };
+
+    Ok(quote! {
+        #(#attributes)*
+        #[allow(unused_variables, clippy::needless_pass_by_value)]
+        #visibility fn #name #generics (
+            #ecs: &mut #crate_path::ecs::Ecs,
+            #additional_data: &mut #additional_data_type,
+        ) #output {
+            #inner_function
+
+            #(#bindings)*
+            #name(#(#arguments),*)
+        }
+
+        /// Returns the types the system reads and writes
+        #[allow(dead_code)]
+        #[must_use]
+        #visibility fn #access_name() -> #crate_path::system::SystemAccess {
+            #crate_path::system::SystemAccess::default()#(#accesses)*
+        }
+    })
+}
+
+/// Fails if the system takes `&mut Ecs` along with parameters borrowing the
+/// Ecs, as the bindings of the generated function would conflict
+fn check_exclusive_ecs_borrow(
+    function: &ItemFn,
+    parameters: &[SystemParameter],
+) -> syn::Result<()> {
+    let Some(exclusive_index) = parameters
+        .iter()
+        .position(|parameter| matches!(parameter, SystemParameter::Ecs { mutable: true }))
+    else {
+        return Ok(());
+    };
+
+    let mut error: Option<syn::Error> = None;
+    for (index, (input, parameter)) in function.sig.inputs.iter().zip(parameters).enumerate() {
+        if index == exclusive_index || matches!(parameter, SystemParameter::AdditionalData(_)) {
+            continue;
+        }
+
+        let parameter_error = syn::Error::new_spanned(
+            input,
+            "systems taking `&mut Ecs` can't take other parameters borrowing the Ecs, query the Ecs in the system instead",
+        );
+        match &mut error {
+            Some(error) => error.combine(parameter_error),
+            None => error = Some(parameter_error),
+        }
+    }
+
+    error.map_or(Ok(()), Err)
+}
+
+fn last_segment_ident(parameter_type: &Type) -> Option<&Ident> {
+    let Type::Path(path) = parameter_type else {
+        return None;
+    };
+    path.path.segments.last().map(|segment| &segment.ident)
+}
+
+/// Returns the name of a generic type along with its type argument, e.g.
+/// `Res` and `T` for `Res<T>`
+fn single_type_argument(parameter_type: &Type) -> Option<(Ident, Type)> {
+    let Type::Path(path) = parameter_type else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(argument) => Some((segment.ident.clone(), argument.clone())),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive_ecs_with_borrowing_parameters() {
+        let function: ItemFn = syn::parse_quote! {
+            fn system(ecs: &mut Ecs, gravity: Res<Gravity>, frames: &mut Frames) {}
+        };
+
+        let error = expand_system(&function, &syn::parse_quote!(::tuber_ecs)).unwrap_err();
+        assert!(error.to_string().contains("`&mut Ecs`"));
+        assert_eq!(error.into_iter().count(), 1);
+    }
+
+    #[test]
+    fn exclusive_ecs_with_additional_data() {
+        let function: ItemFn = syn::parse_quote! {
+            fn system(ecs: &mut Ecs, frames: &mut Frames) {}
+        };
+
+        assert!(expand_system(&function, &syn::parse_quote!(::tuber_ecs)).is_ok());
+    }
+}
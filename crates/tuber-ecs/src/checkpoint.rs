@@ -0,0 +1,195 @@
+//! Checkpoints are copies of the state of an [`Ecs`] which it can be restored
+//! to, such as the last frames kept for rollback netcode or instant replays.
+//!
+//! Only the component and shared resource types registered with
+//! [`Ecs::register_checkpointed_component`] and
+//! [`Ecs::register_checkpointed_shared_resource`] are copied, the other types
+//! keep their current values when a checkpoint is restored.
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, VecDeque};
+
+use crate::ecs::{Ecs, EntitiesBitsetType};
+
+pub(crate) type CloneFn = fn(&dyn Any) -> Box<dyn Any>;
+
+pub(crate) fn clone_boxed<T: Clone + 'static>(value: &dyn Any) -> Box<dyn Any> {
+    Box::new(value.downcast_ref::<T>().unwrap().clone())
+}
+
+pub(crate) struct ComponentsCheckpoint {
+    pub(crate) component_data: Vec<Option<Box<dyn Any>>>,
+    pub(crate) entities_bitset: EntitiesBitsetType,
+}
+
+#[derive(Default)]
+pub struct Checkpoint {
+    pub(crate) components: HashMap<TypeId, ComponentsCheckpoint>,
+    pub(crate) shared_resources: HashMap<TypeId, Box<dyn Any>>,
+    pub(crate) entity_count: usize,
+}
+
+impl Checkpoint {
+    /// Returns the entity count of the Ecs when the checkpoint was taken
+    #[must_use]
+    pub fn entity_count(&self) -> usize {
+        self.entity_count
+    }
+}
+
+/// The checkpoints of the last frames, kept to roll the Ecs back to one of
+/// them
+pub struct CheckpointHistory {
+    capacity: usize,
+    checkpoints: VecDeque<(u64, Checkpoint)>,
+}
+
+impl CheckpointHistory {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            checkpoints: VecDeque::with_capacity(capacity.max(1)),
+        }
+    }
+
+    /// Saves the state of the Ecs at a frame, reusing the storage of the oldest
+    /// checkpoint once the history is full. Frames must be saved in increasing
+    /// order.
+    pub fn save(&mut self, frame: u64, ecs: &Ecs) {
+        let mut checkpoint = if self.checkpoints.len() >= self.capacity {
+            self.checkpoints.pop_front().unwrap().1
+        } else {
+            Checkpoint::default()
+        };
+
+        ecs.checkpoint_into(&mut checkpoint);
+        self.checkpoints.push_back((frame, checkpoint));
+    }
+
+    #[must_use]
+    pub fn get(&self, frame: u64) -> Option<&Checkpoint> {
+        self.checkpoints
+            .iter()
+            .find(|(checkpoint_frame, _)| *checkpoint_frame == frame)
+            .map(|(_, checkpoint)| checkpoint)
+    }
+
+    #[must_use]
+    pub fn oldest_frame(&self) -> Option<u64> {
+        self.checkpoints.front().map(|(frame, _)| *frame)
+    }
+
+    #[must_use]
+    pub fn latest_frame(&self) -> Option<u64> {
+        self.checkpoints.back().map(|(frame, _)| *frame)
+    }
+
+    /// Restores the Ecs to a frame and forgets the later frames, which are
+    /// simulated again afterwards. Returns false if the frame isn't in the
+    /// history.
+    pub fn rollback(&mut self, frame: u64, ecs: &mut Ecs) -> bool {
+        let Some(checkpoint) = self.get(frame) else {
+            return false;
+        };
+
+        ecs.restore(checkpoint);
+        while self.latest_frame().is_some_and(|latest| latest > frame) {
+            self.checkpoints.pop_back();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position(i32);
+
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Score(u32);
+
+    fn ecs() -> Ecs {
+        let mut ecs = Ecs::default();
+        ecs.register_checkpointed_component::<Position>();
+        ecs.register_checkpointed_shared_resource::<Score>();
+        ecs.insert_shared_resource(Score(0));
+        ecs
+    }
+
+    #[test]
+    fn restore_checkpoint() {
+        let mut ecs = ecs();
+        let entity = ecs.insert((Position(1), Name("first")));
+        let checkpoint = ecs.checkpoint();
+
+        ecs.query_one_by_id::<(&mut Position,)>(entity)
+            .unwrap()
+            .1
+             .0
+             .0 = 5;
+        ecs.remove_component::<Name>(entity);
+        ecs.insert((Position(2), Name("second")));
+        *ecs.shared_resource_mut::<Score>().unwrap() = Score(3);
+        ecs.restore(&checkpoint);
+
+        assert_eq!(ecs.entity_count(), 1);
+        assert_eq!(ecs.query::<(&Position,)>().count(), 1);
+        assert_eq!(
+            *ecs.query_one_by_id::<(&Position,)>(entity).unwrap().1 .0,
+            Position(1)
+        );
+        assert!(ecs.query_one_by_id::<(&Name,)>(entity).is_none());
+        assert_eq!(*ecs.shared_resource::<Score>().unwrap(), Score(0));
+    }
+
+    #[test]
+    fn restore_removes_components_added_later() {
+        let mut ecs = ecs();
+        let entity = ecs.insert(());
+        let checkpoint = ecs.checkpoint();
+
+        ecs.add_component(Position(4), entity);
+        ecs.restore(&checkpoint);
+
+        assert!(ecs.query_one_by_id::<(&Position,)>(entity).is_none());
+    }
+
+    #[test]
+    fn restore_removes_shared_resources_inserted_later() {
+        let mut ecs = Ecs::default();
+        ecs.register_checkpointed_shared_resource::<Score>();
+        let checkpoint = ecs.checkpoint();
+
+        ecs.insert_shared_resource(Score(2));
+        ecs.restore(&checkpoint);
+
+        assert!(ecs.shared_resource::<Score>().is_none());
+    }
+
+    #[test]
+    fn rollback() {
+        let mut ecs = ecs();
+        let entity = ecs.insert((Position(0),));
+        let mut history = CheckpointHistory::new(3);
+
+        for frame in 0..5u8 {
+            ecs.add_component(Position(i32::from(frame)), entity);
+            history.save(u64::from(frame), &ecs);
+        }
+
+        assert_eq!(history.oldest_frame(), Some(2));
+        assert!(!history.rollback(1, &mut ecs));
+        assert!(history.rollback(3, &mut ecs));
+        assert_eq!(history.latest_frame(), Some(3));
+        assert_eq!(
+            *ecs.query_one_by_id::<(&Position,)>(entity).unwrap().1 .0,
+            Position(3)
+        );
+    }
+}
@@ -5,13 +5,14 @@ use std::cell::{Ref, RefCell, RefMut};
 use std::collections::{HashMap, HashSet};
 
 use crate::bitset::BitSet;
+use crate::checkpoint::{clone_boxed, Checkpoint, CloneFn, ComponentsCheckpoint};
 use crate::query::{ComponentTypeId, Query, QueryIterator, QueryIteratorByIds};
 use crate::EntityIndex;
 
 pub type Components = HashMap<TypeId, ComponentStore>;
 pub type Resources = HashMap<TypeId, RefCell<Box<dyn Any>>>;
 
-type EntitiesBitsetType = [u64; 1024];
+pub(crate) type EntitiesBitsetType = [u64; 1024];
 
 pub struct ComponentStore {
     pub(crate) component_data: Vec<Option<RefCell<Box<dyn Any>>>>,
@@ -58,6 +59,8 @@ pub struct Ecs {
     components: Components,
     shared_resources: Resources,
     next_index: EntityIndex,
+    checkpointed_components: HashMap<TypeId, CloneFn>,
+    checkpointed_shared_resources: HashMap<TypeId, CloneFn>,
 }
 
 impl Ecs {
@@ -117,10 +120,18 @@ impl Ecs {
         }
     }
 
+    /// Adds a component to an existing entity, replacing the component of the
+    /// same type it may already have
     pub fn add_component<C: 'static>(&mut self, component: C, entity_index: EntityIndex) {
-        if let Some(components) = self.components.get_mut(&TypeId::of::<C>()) {
-            components.add_to_entity(component, entity_index);
+        if entity_index >= self.next_index {
+            return;
         }
+
+        let entity_count = self.next_index;
+        self.components
+            .entry(TypeId::of::<C>())
+            .or_insert_with(|| ComponentStore::with_size(entity_count - 1))
+            .add_to_entity(component, entity_index);
     }
 
     #[must_use]
@@ -178,6 +189,118 @@ impl Ecs {
     pub fn entity_count(&self) -> usize {
         self.next_index
     }
+
+    /// Includes a component type in the checkpoints of the Ecs
+    pub fn register_checkpointed_component<C: Clone + 'static>(&mut self) {
+        self.checkpointed_components
+            .insert(TypeId::of::<C>(), clone_boxed::<C>);
+    }
+
+    /// Includes a shared resource type in the checkpoints of the Ecs
+    pub fn register_checkpointed_shared_resource<R: Clone + 'static>(&mut self) {
+        self.checkpointed_shared_resources
+            .insert(TypeId::of::<R>(), clone_boxed::<R>);
+    }
+
+    /// Copies the checkpointed components and shared resources
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint {
+        let mut checkpoint = Checkpoint::default();
+        self.checkpoint_into(&mut checkpoint);
+        checkpoint
+    }
+
+    /// Copies the checkpointed components and shared resources into an
+    /// existing checkpoint, reusing its storage
+    pub fn checkpoint_into(&self, checkpoint: &mut Checkpoint) {
+        checkpoint.entity_count = self.next_index;
+        checkpoint
+            .components
+            .retain(|type_id, _| self.components.contains_key(type_id));
+        for (type_id, clone) in &self.checkpointed_components {
+            let Some(component_store) = self.components.get(type_id) else {
+                continue;
+            };
+
+            let components_checkpoint =
+                checkpoint
+                    .components
+                    .entry(*type_id)
+                    .or_insert_with(|| ComponentsCheckpoint {
+                        component_data: vec![],
+                        entities_bitset: [0u64; 1024],
+                    });
+            components_checkpoint.component_data.clear();
+            components_checkpoint
+                .component_data
+                .extend(component_store.component_data.iter().map(|component| {
+                    component
+                        .as_ref()
+                        .map(|component| clone(&**component.borrow()))
+                }));
+            components_checkpoint.entities_bitset = component_store.entities_bitset;
+        }
+
+        checkpoint.shared_resources.clear();
+        for (type_id, clone) in &self.checkpointed_shared_resources {
+            if let Some(resource) = self.shared_resources.get(type_id) {
+                checkpoint
+                    .shared_resources
+                    .insert(*type_id, clone(&**resource.borrow()));
+            }
+        }
+    }
+
+    /// Restores the checkpointed components and shared resources, and removes
+    /// the entities and checkpointed shared resources inserted after the
+    /// checkpoint was taken
+    pub fn restore(&mut self, checkpoint: &Checkpoint) {
+        let entity_count = checkpoint.entity_count;
+        for (type_id, component_store) in &mut self.components {
+            if let Some(clone) = self.checkpointed_components.get(type_id) {
+                if let Some(components_checkpoint) = checkpoint.components.get(type_id) {
+                    component_store.component_data.clear();
+                    component_store.component_data.extend(
+                        components_checkpoint
+                            .component_data
+                            .iter()
+                            .map(|component| {
+                                component
+                                    .as_ref()
+                                    .map(|component| RefCell::new(clone(&**component)))
+                            }),
+                    );
+                    component_store.entities_bitset = components_checkpoint.entities_bitset;
+                } else {
+                    component_store.component_data.clear();
+                    component_store
+                        .component_data
+                        .resize_with(entity_count, || None);
+                    component_store.entities_bitset = [0u64; 1024];
+                }
+                continue;
+            }
+
+            for entity_index in entity_count..self.next_index {
+                component_store.entities_bitset.unset_bit(entity_index);
+            }
+            component_store
+                .component_data
+                .resize_with(entity_count, || None);
+        }
+
+        for type_id in self.checkpointed_shared_resources.keys() {
+            if !checkpoint.shared_resources.contains_key(type_id) {
+                self.shared_resources.remove(type_id);
+            }
+        }
+        for (type_id, resource) in &checkpoint.shared_resources {
+            let clone = self.checkpointed_shared_resources[type_id];
+            self.shared_resources
+                .insert(*type_id, RefCell::new(clone(&**resource)));
+        }
+        self.next_index = entity_count;
+    }
 }
 
 /// A type that can be used to define an entity
@@ -185,6 +308,15 @@ pub trait EntityDefinition {
     fn store_components(self, components: &mut Components, index: usize);
 }
 
+/// An entity without components, which are added afterwards
+impl EntityDefinition for () {
+    fn store_components(self, components: &mut Components, _index: usize) {
+        for component_storage in components.values_mut() {
+            component_storage.component_data.push(None);
+        }
+    }
+}
+
 macro_rules! impl_entity_definition_tuples {
     ($($t:tt => $i:tt,)*) => {
         impl<$($t: 'static,)*> EntityDefinition for ($($t,)*) {
@@ -307,4 +439,21 @@ mod tests {
 
         assert_eq!(*position, Position { x: 12.0, y: 1.0 });
     }
+
+    #[test]
+    pub fn ecs_add_component_to_empty_entity() {
+        let mut ecs = Ecs::default();
+        ecs.insert((Position { x: 12.0, y: 1.0 },));
+        let entity = ecs.insert(());
+
+        ecs.add_component(Position { x: 2.0, y: 3.0 }, entity);
+        ecs.add_component(Velocity { x: 1.0, y: 0.0 }, entity);
+
+        let (_, (position, velocity)) = ecs
+            .query_one_by_id::<(&Position, &Velocity)>(entity)
+            .unwrap();
+        assert_eq!(*position, Position { x: 2.0, y: 3.0 });
+        assert_float_absolute_eq!(velocity.x, 1.0);
+        assert_eq!(ecs.query::<(&Velocity,)>().count(), 1);
+    }
 }
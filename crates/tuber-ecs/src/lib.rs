@@ -8,6 +8,7 @@
 extern crate assert_float_eq;
 
 mod bitset;
+pub mod checkpoint;
 pub mod ecs;
 pub mod query;
 pub mod system;
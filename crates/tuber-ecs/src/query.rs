@@ -15,6 +15,9 @@ pub trait Query<'a> {
     fn fetch(index: EntityIndex, components: &'a Components) -> Option<Self::ResultType>;
     fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex>;
     fn type_ids() -> Vec<ComponentTypeId>;
+    /// Returns the type of the queried components along with whether they are
+    /// accessed mutably
+    fn component_accesses() -> Vec<(ComponentTypeId, bool)>;
 }
 
 macro_rules! impl_query_tuples {
@@ -40,6 +43,10 @@ macro_rules! impl_query_tuples {
             fn type_ids() -> Vec<ComponentTypeId> {
                 vec![$th::type_id(), $($t::type_id(),)*]
             }
+
+            fn component_accesses() -> Vec<(ComponentTypeId, bool)> {
+                vec![($th::type_id(), $th::MUTABLE), $(($t::type_id(), $t::MUTABLE),)*]
+            }
         }
     }
 }
@@ -157,6 +164,7 @@ pub mod accessors {
     pub trait Accessor<'a> {
         type RawType: 'a;
         type RefType: 'a;
+        const MUTABLE: bool = false;
 
         fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType>;
         fn matching_ids(entity_count: usize, components: &'a Components) -> HashSet<EntityIndex>;
@@ -188,6 +196,7 @@ pub mod accessors {
     impl<'a, T: 'static> Accessor<'a> for &mut T {
         type RawType = T;
         type RefType = RefMut<'a, T>;
+        const MUTABLE: bool = true;
 
         fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType> {
             Some(RefMut::map(
@@ -226,6 +235,7 @@ pub mod accessors {
     impl<'a, T: 'static + Accessor<'a>> Accessor<'a> for Opt<'a, T> {
         type RawType = T::RawType;
         type RefType = Option<T::RefType>;
+        const MUTABLE: bool = T::MUTABLE;
 
         fn fetch(index: usize, components: &'a Components) -> Option<Self::RefType> {
             Some(T::fetch(index, components))
@@ -255,4 +265,11 @@ impl ComponentTypeId {
     pub fn is_required(&self) -> bool {
         matches!(self, RequiredComponentTypeId(_))
     }
+
+    #[must_use]
+    pub fn component_type_id(&self) -> TypeId {
+        match self {
+            RequiredComponentTypeId(type_id) | OptionalComponentTypeId(type_id) => *type_id,
+        }
+    }
 }
@@ -1,10 +1,75 @@
+use std::any::TypeId;
+use std::cell::{Ref, RefMut};
+use std::collections::HashSet;
 use std::error::Error;
 
 use crate::ecs::Ecs;
+use crate::query::{Query, QueryIterator};
+
+pub use tuber_derive::system;
 
 type BoxedSystem<AD> = Box<dyn FnMut(&mut Ecs, &mut AD) -> SystemResult>;
 pub type SystemResult = Result<(), Box<dyn Error>>;
 
+/// The parameter types of the functions annotated with `#[system]`: the
+/// matching entities of a query and the shared resources of the ECS
+pub type SystemQuery<'a, Q> = QueryIterator<'a, Q>;
+pub type Res<'a, T> = Ref<'a, T>;
+pub type ResMut<'a, T> = RefMut<'a, T>;
+
+/// The component and shared resource types a system reads and writes, as
+/// inferred by `#[system]` from the parameters of the system
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemAccess {
+    pub reads: HashSet<TypeId>,
+    pub writes: HashSet<TypeId>,
+    /// Whether the system accesses the whole ECS
+    pub exclusive: bool,
+}
+
+impl SystemAccess {
+    #[must_use]
+    pub fn with_query<Q: for<'a> Query<'a>>(mut self) -> Self {
+        for (type_id, mutable) in Q::component_accesses() {
+            if mutable {
+                self.writes.insert(type_id.component_type_id());
+            } else {
+                self.reads.insert(type_id.component_type_id());
+            }
+        }
+        self
+    }
+
+    #[must_use]
+    pub fn with_read<T: 'static>(mut self) -> Self {
+        self.reads.insert(TypeId::of::<T>());
+        self
+    }
+
+    #[must_use]
+    pub fn with_write<T: 'static>(mut self) -> Self {
+        self.writes.insert(TypeId::of::<T>());
+        self
+    }
+
+    #[must_use]
+    pub fn with_exclusive(mut self) -> Self {
+        self.exclusive = true;
+        self
+    }
+
+    /// Returns whether one of the systems writes a type the other accesses,
+    /// so they can't run at the same time
+    #[must_use]
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.exclusive
+            || other.exclusive
+            || !self.writes.is_disjoint(&other.writes)
+            || !self.writes.is_disjoint(&other.reads)
+            || !self.reads.is_disjoint(&other.writes)
+    }
+}
+
 pub struct SystemBundle<AD> {
     systems: Vec<BoxedSystem<AD>>,
 }
@@ -149,6 +214,62 @@ mod tests {
         assert!(result_set.contains(&Value(47)));
     }
 
+    #[test]
+    fn system_access_conflicts() {
+        struct Position;
+        struct Velocity;
+        struct Gravity;
+
+        let movement = SystemAccess::default().with_query::<(&mut Position, &Velocity)>();
+        let gravity = SystemAccess::default()
+            .with_query::<(&mut Velocity,)>()
+            .with_read::<Gravity>();
+        let rendering = SystemAccess::default().with_query::<(&Position,)>();
+
+        assert!(movement.writes.contains(&TypeId::of::<Position>()));
+        assert!(movement.reads.contains(&TypeId::of::<Velocity>()));
+        assert!(movement.conflicts_with(&gravity));
+        assert!(!gravity.conflicts_with(&rendering));
+        assert!(rendering.conflicts_with(&SystemAccess::default().with_exclusive()));
+    }
+
+    #[test]
+    fn system_attribute() {
+        struct Velocity(f32);
+        struct Gravity(f32);
+        struct Frames(u32);
+
+        #[system(crate = "crate")]
+        fn apply_gravity(
+            velocities: SystemQuery<(&mut Velocity,)>,
+            gravity: Res<Gravity>,
+            frames: &mut Frames,
+        ) {
+            for (_, (mut velocity,)) in velocities {
+                velocity.0 -= gravity.0;
+            }
+            frames.0 += 1;
+        }
+
+        let mut ecs = Ecs::default();
+        let entity = ecs.insert((Velocity(0.0),));
+        let mut frames = Frames(0);
+        let mut system_bundle = SystemBundle::default();
+        system_bundle.add_system(apply_gravity);
+
+        system_bundle.step(&mut ecs, &mut frames).unwrap();
+        assert_eq!(frames.0, 0);
+        ecs.insert_shared_resource(Gravity(2.0));
+        system_bundle.step(&mut ecs, &mut frames).unwrap();
+
+        assert_eq!(frames.0, 1);
+        let (_, (velocity,)) = ecs.query_one_by_id::<(&Velocity,)>(entity).unwrap();
+        assert_float_absolute_eq!(velocity.0, -2.0);
+        let access = apply_gravity_access();
+        assert!(access.writes.contains(&TypeId::of::<Velocity>()));
+        assert!(access.reads.contains(&TypeId::of::<Gravity>()));
+    }
+
     #[test]
     fn system_bundle_with_additional_data() {
         struct ComponentA;
@@ -0,0 +1,102 @@
+//! Sounds played automatically when animations reach given frames.
+
+use log::warn;
+
+use tuber_audio::sound::Sound;
+use tuber_audio::spatial::AudioSource;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemResult;
+use tuber_graphics::animation::{AnimationEventReader, AnimationEvents};
+
+use crate::engine_context::EngineContext;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FrameSound {
+    state: Option<String>,
+    frame: usize,
+    sound: String,
+}
+
+/// The sound assets played when the animation of an entity enters some
+/// frames, e.g. footsteps on the frames a foot touches the ground
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnimationSounds {
+    frame_sounds: Vec<FrameSound>,
+}
+
+impl AnimationSounds {
+    /// Plays a sound when the animated sprite of the entity enters a frame
+    #[must_use]
+    pub fn with_frame_sound(mut self, frame: usize, sound: &str) -> Self {
+        self.frame_sounds.push(FrameSound {
+            state: None,
+            frame,
+            sound: sound.into(),
+        });
+        self
+    }
+
+    /// Plays a sound when the animation of a state of the entity's animation
+    /// state machine enters a frame
+    #[must_use]
+    pub fn with_state_frame_sound(mut self, state: &str, frame: usize, sound: &str) -> Self {
+        self.frame_sounds.push(FrameSound {
+            state: Some(state.into()),
+            frame,
+            sound: sound.into(),
+        });
+        self
+    }
+
+    fn sounds(&self, state: Option<&str>, frame: usize) -> Vec<String> {
+        self.frame_sounds
+            .iter()
+            .filter(|frame_sound| {
+                frame_sound.frame == frame && frame_sound.state.as_deref() == state
+            })
+            .map(|frame_sound| frame_sound.sound.clone())
+            .collect()
+    }
+}
+
+/// Plays the sounds of the frames entered by the animations since the reader
+/// last read the animation events, leaving the events to the other systems.
+/// The sounds are positioned in the world if the entity has an audio source.
+pub fn play_animation_sounds(
+    ecs: &mut Ecs,
+    context: &mut EngineContext,
+    reader: &mut AnimationEventReader,
+) -> SystemResult {
+    let events = match ecs.shared_resource::<AnimationEvents>() {
+        Some(animation_events) => reader.read(&animation_events).to_vec(),
+        None => return Ok(()),
+    };
+
+    for event in events {
+        let sounds: Vec<String> = match ecs.query_one_by_id::<(&AnimationSounds,)>(event.entity) {
+            Some((_, (animation_sounds,))) => {
+                animation_sounds.sounds(event.state.as_deref(), event.frame)
+            }
+            None => continue,
+        };
+
+        for sound in sounds {
+            let sound = match context.asset_store.asset::<Sound>(&sound) {
+                Ok(sound) => sound.clone(),
+                Err(e) => {
+                    warn!("Couldn't play animation sound {sound}: {e:?}");
+                    continue;
+                }
+            };
+
+            let playback = context.audio.play_sound(&sound);
+            if let Some((_, (mut audio_source,))) =
+                ecs.query_one_by_id::<(&mut AudioSource,)>(event.entity)
+            {
+                audio_source.playbacks.push(playback);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,9 +1,24 @@
+use tuber_audio::Audio;
 use tuber_core::asset::Store;
 use tuber_core::input::State;
+use tuber_core::inspector::Inspector;
+use tuber_core::logging::LogCapture;
+use tuber_core::registry::ComponentRegistry;
+use tuber_core::scene_watcher::SceneWatcher;
 use tuber_graphics::Graphics;
 
 pub struct EngineContext {
     pub graphics: Option<Graphics>,
     pub asset_store: Store,
+    pub audio: Audio,
     pub input_state: State,
+    /// The types components and shared resources of scenes can have
+    pub component_registry: ComponentRegistry,
+    /// Reloads the scenes and prefabs loaded through it and the assets it
+    /// watches when their file changes, in debug builds
+    pub scene_watcher: SceneWatcher,
+    /// Browses and edits the entities, disabled in release builds
+    pub inspector: Inspector,
+    /// The recent messages of the engine logger
+    pub log_capture: LogCapture,
 }
@@ -4,65 +4,181 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::module_name_repetitions)]
 
-use std::path::PathBuf;
+use std::path::Path;
 
-use log::info;
+use log::{info, warn};
 
 use engine_context::EngineContext;
 use state::{State, StateStack};
+use tuber_audio::sink::AudioSink;
+use tuber_audio::sound::{sound_loader, Sound};
+use tuber_audio::{Audio, AudioSettings};
 use tuber_core::asset::Store;
+use tuber_core::determinism::DeterminismSettings;
 use tuber_core::input::{Keymap, State as InputState};
-use tuber_core::{input, CoreError};
+use tuber_core::inspector::Inspector;
+use tuber_core::logging::{EngineLogger, LogCapture, LogSettings};
+use tuber_core::prefab::{prefab_loader, Prefab};
+use tuber_core::random::Random;
+use tuber_core::registry::ComponentRegistry;
+use tuber_core::scene_watcher::SceneWatcher;
+use tuber_core::transform::{store_previous_transforms, StepInterpolation};
+use tuber_core::vfs::{OsVfs, Vfs};
+use tuber_core::{input, profile_scope, CoreError, DeltaTime};
 use tuber_ecs::ecs::Ecs;
 use tuber_ecs::system::SystemBundle;
-use tuber_graphics::{Graphics, GraphicsAPI};
+use tuber_graphics::animation::AnimationEventReader;
+use tuber_graphics::aseprite::{aseprite_sheet_loader, AsepriteSheet};
+use tuber_graphics::sprite_atlas::{sprite_atlas_loader, SpriteAtlas};
+use tuber_graphics::texture::{texture_loader, TextureData};
+use tuber_graphics::{Graphics, GraphicsAPI, GraphicsError, GraphicsSettings};
 
+pub mod audio_events;
 pub mod engine_context;
 pub mod state;
 
+const KEYMAP_FILE: &str = "keymap.json";
+
 #[derive(Default)]
 pub struct EngineSettings {
     pub application_title: Option<String>,
     pub initial_state: Option<Box<dyn State>>,
+    /// The file system assets and configuration files are read from,
+    /// defaults to the application directory
+    pub vfs: Option<Box<dyn Vfs>>,
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+    /// The output the mixed sounds are sent to, such as an audio device. The
+    /// sounds are mixed and discarded if none is given.
+    pub audio_sink: Option<Box<dyn AudioSink>>,
+    pub logging: LogSettings,
+    /// Makes the simulation deterministic: the `Random` shared resource is
+    /// seeded, every step lasts the same time and scenes aren't hot-reloaded
+    pub determinism: Option<DeterminismSettings>,
 }
 
 pub struct Engine {
     state_stack: StateStack,
     ecs: Ecs,
     application_title: String,
+    graphics_settings: GraphicsSettings,
     context: EngineContext,
     system_bundles: Vec<SystemBundle<EngineContext>>,
+    determinism: Option<DeterminismSettings>,
+}
+
+fn create_ecs(determinism: Option<&DeterminismSettings>) -> Ecs {
+    let mut ecs = Ecs::default();
+    ecs.insert_shared_resource(determinism.map_or_else(Random::from_time, |determinism| {
+        Random::new(determinism.seed)
+    }));
+    ecs
+}
+
+/// Returns the systems bridging the engine's crates, such as the animation
+/// sounds
+fn engine_system_bundle(deterministic: bool) -> SystemBundle<EngineContext> {
+    let mut system_bundle = SystemBundle::default();
+    let mut animation_event_reader = AnimationEventReader::default();
+    system_bundle.add_system(move |ecs: &mut Ecs, context: &mut EngineContext| {
+        audio_events::play_animation_sounds(ecs, context, &mut animation_event_reader)
+    });
+    if cfg!(debug_assertions) && !deterministic {
+        system_bundle.add_system(reload_scenes);
+    }
+    system_bundle
+}
+
+/// Installs the engine logger, the returned capture stays empty if the
+/// application installed its own logger
+fn install_logger(settings: LogSettings) -> LogCapture {
+    let log_capture = LogCapture::default();
+    match EngineLogger::new(settings, log_capture.clone()).and_then(EngineLogger::install) {
+        Ok(()) => {}
+        Err(CoreError::LoggerAlreadyInstalled) => {
+            info!("A logger is already installed, the engine logger is disabled");
+        }
+        Err(e) => warn!("Failed to install the engine logger: {e:?}"),
+    }
+    log_capture
 }
 
-fn create_ecs() -> Ecs {
-    Ecs::default()
+fn reload_scenes(ecs: &mut Ecs, context: &mut EngineContext) {
+    let delta_time = match ecs.shared_resource::<DeltaTime>() {
+        Some(delta_time) => delta_time.0,
+        None => return,
+    };
+
+    context.scene_watcher.update(
+        delta_time,
+        context.asset_store.vfs(),
+        ecs,
+        &context.component_registry,
+    );
+    for identifier in context.scene_watcher.drain_changed_assets() {
+        info!("Reloading asset {identifier}");
+        if let Err(e) = context.asset_store.reload_asset(&identifier) {
+            warn!("Couldn't reload asset {identifier}: {e:?}");
+        }
+    }
 }
 
 impl Engine {
     #[must_use]
     pub fn new(settings: EngineSettings) -> Engine {
+        let log_capture = install_logger(settings.logging);
         info!("Creating tuber instance");
-        let mut asset_manager = Store::default();
-        asset_manager.load_assets_metadata().unwrap();
+        let vfs = settings.vfs.unwrap_or_else(|| Box::new(OsVfs::default()));
 
         let input_state = InputState::new(
-            Keymap::from_file(&Self::keymap_file_path().unwrap()).unwrap_or_default(),
+            Keymap::from_file(vfs.as_ref(), Path::new(KEYMAP_FILE)).unwrap_or_default(),
         );
 
+        let mut asset_manager = Store::new(vfs);
+        asset_manager.load_assets_metadata().unwrap();
+        asset_manager.register_asset_kind::<Sound>("sound");
+        asset_manager.register_loader(sound_loader);
+        asset_manager.register_asset_kind::<Prefab>("prefab");
+        asset_manager.register_loader(prefab_loader);
+        asset_manager.register_asset_kind::<TextureData>("texture");
+        asset_manager.register_loader(texture_loader);
+        asset_manager.register_asset_kind::<SpriteAtlas>("sprite_atlas");
+        asset_manager.register_loader(sprite_atlas_loader);
+        asset_manager.register_asset_kind::<AsepriteSheet>("aseprite_sheet");
+        asset_manager.register_loader(aseprite_sheet_loader);
+
+        let mut audio = Audio::new(&settings.audio);
+        if let Some(audio_sink) = settings.audio_sink {
+            audio.set_sink(audio_sink);
+        }
+        let system_bundles = vec![
+            tuber_core::default_system_bundle(),
+            tuber_graphics::default_system_bundle(),
+            tuber_audio::default_system_bundle(audio.mixer()),
+            engine_system_bundle(settings.determinism.is_some()),
+        ];
+
         let context = EngineContext {
             graphics: None,
             asset_store: asset_manager,
+            audio,
             input_state,
+            component_registry: ComponentRegistry::new(),
+            scene_watcher: SceneWatcher::default(),
+            inspector: Inspector::new(cfg!(debug_assertions)),
+            log_capture,
         };
 
         Self {
             state_stack: StateStack::new(settings.initial_state),
-            ecs: create_ecs(),
+            ecs: create_ecs(settings.determinism.as_ref()),
             application_title: settings
                 .application_title
                 .unwrap_or_else(|| "tuber Application".into()),
+            graphics_settings: settings.graphics,
             context,
-            system_bundles: vec![],
+            system_bundles,
+            determinism: settings.determinism,
         }
     }
 
@@ -78,6 +194,10 @@ impl Engine {
         &self.application_title
     }
 
+    pub fn graphics_settings(&self) -> &GraphicsSettings {
+        &self.graphics_settings
+    }
+
     pub fn push_initial_state(&mut self) {
         self.state_stack.push_initial_state(
             &mut self.ecs,
@@ -86,34 +206,66 @@ impl Engine {
         );
     }
 
+    /// Updates the current state and runs the systems, the delta time is
+    /// ignored in favor of the fixed timestep if the engine is deterministic.
+    /// The asset events not drained during the step are dropped at its end.
     pub fn step(&mut self, delta_time: f64) {
+        profile_scope!("step");
+        let delta_time = self
+            .determinism
+            .map_or(delta_time, |determinism| determinism.timestep);
+        store_previous_transforms(&mut self.ecs);
         self.state_stack.update_current_state(
             delta_time,
             &mut self.ecs,
             &mut self.system_bundles,
             &mut self.context,
         );
+        self.context.asset_store.clear_events();
+    }
+
+    /// Mixes the sounds played during the elapsed real time in seconds and
+    /// sends them to the audio sink, called by the runner every frame
+    pub fn update_audio(&mut self, delta_time: f64) {
+        profile_scope!("update_audio");
+        self.context.audio.update(delta_time);
+    }
+
+    /// Sets how far the next rendered frame is between the previous and the
+    /// current step, as a fraction of the timestep
+    pub fn set_step_interpolation(&mut self, interpolation: f64) {
+        #[allow(clippy::cast_possible_truncation)]
+        let interpolation = interpolation.clamp(0.0, 1.0) as f32;
+        self.ecs
+            .insert_shared_resource(StepInterpolation(interpolation));
     }
 
     pub fn handle_input(&mut self, input: &input::Input) {
         self.state_stack.handle_input(input, &mut self.context);
     }
 
-    #[allow(clippy::unused_self)]
-    pub fn on_window_resized(&mut self, _width: u32, _height: u32) {}
+    pub fn on_window_resized(&mut self, width: u32, height: u32) {
+        if let Some(graphics) = &mut self.context.graphics {
+            graphics.on_window_resized(width, height);
+        }
+    }
 
-    pub fn render(&mut self) {
+    pub fn on_scale_factor_changed(&mut self, scale_factor: f64, width: u32, height: u32) {
+        if let Some(graphics) = &mut self.context.graphics {
+            graphics.on_scale_factor_changed(scale_factor, width, height);
+        }
+    }
+
+    pub fn render(&mut self) -> Result<()> {
+        profile_scope!("render");
         self.state_stack
             .render_current_state(&mut self.ecs, &mut self.context);
         if let Some(graphics) = &mut self.context.graphics {
-            graphics.render_scene(&self.ecs).unwrap();
+            profile_scope!("render_scene");
+            graphics.render_scene(&self.ecs, &mut self.context.asset_store)?;
         }
-    }
 
-    fn keymap_file_path() -> Result<PathBuf> {
-        let mut path = tuber_core::application_directory()?;
-        path.push("keymap.json");
-        Ok(path)
+        Ok(())
     }
 }
 
@@ -126,6 +278,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug)]
 pub enum Error {
     CoreError(CoreError),
+    GraphicsError(GraphicsError),
 }
 
 impl From<CoreError> for Error {
@@ -133,3 +286,9 @@ impl From<CoreError> for Error {
         Error::CoreError(error)
     }
 }
+
+impl From<GraphicsError> for Error {
+    fn from(error: GraphicsError) -> Self {
+        Error::GraphicsError(error)
+    }
+}
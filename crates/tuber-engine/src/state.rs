@@ -1,5 +1,5 @@
 use tuber_core::input::Input;
-use tuber_core::DeltaTime;
+use tuber_core::{profile_scope, DeltaTime};
 use tuber_ecs::ecs::Ecs;
 use tuber_ecs::system::SystemBundle;
 
@@ -80,9 +80,13 @@ impl StateStack {
     ) {
         ecs.insert_shared_resource(DeltaTime(delta_time));
         let state = self.states.last_mut().expect("Expected current state");
-        state.update(ecs, engine_context);
+        {
+            profile_scope!("state_update");
+            state.update(ecs, engine_context);
+        }
 
         for system_bundle in system_bundles.iter_mut() {
+            profile_scope!("system_bundle");
             system_bundle.step(ecs, engine_context).unwrap();
         }
 
@@ -98,6 +102,7 @@ impl StateStack {
         ecs: &mut Ecs,
         engine_context: &'a mut EngineContext,
     ) {
+        profile_scope!("state_render");
         let state = self.states.last_mut().expect("Expected current state");
         state.render(ecs, engine_context);
     }
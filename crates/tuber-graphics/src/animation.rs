@@ -0,0 +1,339 @@
+//! Sprite-sheet animations: sprites cycling through regions of a texture.
+
+use tuber_core::DeltaTime;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemResult;
+use tuber_ecs::EntityIndex;
+
+use crate::texture::TextureRegion;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AnimationState {
+    pub current_frame: usize,
+    /// The time spent on the current frame, in seconds
+    pub elapsed_time: f64,
+    pub finished: bool,
+    /// Whether the first frame has been entered
+    pub started: bool,
+}
+
+/// A sprite displaying the frames of a sprite sheet one after the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatedSprite {
+    pub width: f32,
+    pub height: f32,
+    pub texture: String,
+    pub frames: Vec<TextureRegion>,
+    /// The time each frame is displayed, in seconds
+    pub frame_duration: f64,
+    /// The time each frame is displayed, in seconds, overriding
+    /// `frame_duration` for the frames it covers
+    pub frame_durations: Vec<f64>,
+    pub looping: bool,
+    pub state: AnimationState,
+}
+
+impl AnimatedSprite {
+    #[must_use]
+    pub fn new(
+        width: f32,
+        height: f32,
+        texture: &str,
+        frames: Vec<TextureRegion>,
+        frame_duration: f64,
+    ) -> Self {
+        Self {
+            width,
+            height,
+            texture: texture.into(),
+            frames,
+            frame_duration,
+            frame_durations: vec![],
+            looping: true,
+            state: AnimationState::default(),
+        }
+    }
+
+    /// Sets the duration of each frame, in seconds
+    #[must_use]
+    pub fn with_frame_durations(mut self, frame_durations: Vec<f64>) -> Self {
+        self.frame_durations = frame_durations;
+        self
+    }
+
+    /// Returns the time the given frame is displayed, in seconds
+    #[must_use]
+    pub fn frame_duration_of(&self, frame: usize) -> f64 {
+        self.frame_durations
+            .get(frame)
+            .copied()
+            .unwrap_or(self.frame_duration)
+    }
+
+    /// Returns the region of the texture of the frame being displayed
+    #[must_use]
+    pub fn current_region(&self) -> Option<TextureRegion> {
+        self.frames.get(self.state.current_frame).copied()
+    }
+
+    /// Restarts the animation from its first frame
+    pub fn reset(&mut self) {
+        self.state = AnimationState::default();
+    }
+
+    /// Advances the animation by the given time in seconds and returns the
+    /// frames entered, in order. A non-looping animation stops on its last
+    /// frame.
+    pub fn advance(&mut self, delta_time: f64) -> Vec<usize> {
+        let mut entered_frames = vec![];
+        if self.state.finished
+            || self.frames.is_empty()
+            || self.frame_duration_of(self.state.current_frame) <= 0.0
+        {
+            return entered_frames;
+        }
+
+        if !self.state.started {
+            self.state.started = true;
+            entered_frames.push(self.state.current_frame);
+        }
+
+        self.state.elapsed_time += delta_time;
+        loop {
+            let frame_duration = self.frame_duration_of(self.state.current_frame);
+            if frame_duration <= 0.0 || self.state.elapsed_time < frame_duration {
+                break;
+            }
+
+            self.state.elapsed_time -= frame_duration;
+            if self.state.current_frame + 1 < self.frames.len() {
+                self.state.current_frame += 1;
+            } else if self.looping {
+                self.state.current_frame = 0;
+            } else {
+                self.state.elapsed_time = 0.0;
+                self.state.finished = true;
+                break;
+            }
+
+            entered_frames.push(self.state.current_frame);
+        }
+
+        entered_frames
+    }
+}
+
+/// The event sent when an animation displays a new frame, used to
+/// synchronize sounds or effects with the animation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnimationFrameEntered {
+    pub entity: EntityIndex,
+    /// The state of the entity's animation state machine, if it has one
+    pub state: Option<String>,
+    pub frame: usize,
+}
+
+/// The shared resource the animation events are stored in. The events are
+/// kept for two steps so every system reads them through its own
+/// [`AnimationEventReader`], whatever the order the systems run in.
+#[derive(Debug, Default)]
+pub struct AnimationEvents {
+    events: Vec<AnimationFrameEntered>,
+    /// The number of events at the start of `events` sent before the
+    /// current step
+    previous_step_event_count: usize,
+    /// The number of events dropped since the first step
+    dropped_event_count: usize,
+}
+
+impl AnimationEvents {
+    /// Drops the events sent before the previous step, called at the start
+    /// of every step
+    pub fn update(&mut self) {
+        self.events.drain(..self.previous_step_event_count);
+        self.dropped_event_count += self.previous_step_event_count;
+        self.previous_step_event_count = self.events.len();
+    }
+}
+
+/// Reads the animation events without consuming them, returning each event
+/// once
+#[derive(Debug, Default)]
+pub struct AnimationEventReader {
+    read_event_count: usize,
+}
+
+impl AnimationEventReader {
+    /// Returns the events sent since the last call
+    pub fn read<'a>(&mut self, events: &'a AnimationEvents) -> &'a [AnimationFrameEntered] {
+        let first_unread = self
+            .read_event_count
+            .saturating_sub(events.dropped_event_count)
+            .min(events.events.len());
+        self.read_event_count = events.dropped_event_count + events.events.len();
+        &events.events[first_unread..]
+    }
+}
+
+pub(crate) fn push_animation_events(ecs: &mut Ecs, events: Vec<AnimationFrameEntered>) {
+    if events.is_empty() {
+        return;
+    }
+
+    if ecs.shared_resource::<AnimationEvents>().is_none() {
+        ecs.insert_shared_resource(AnimationEvents::default());
+    }
+
+    if let Some(mut animation_events) = ecs.shared_resource_mut::<AnimationEvents>() {
+        animation_events.events.extend(events);
+    }
+}
+
+/// Advances every animated sprite using the frame's delta time. The animation
+/// events of the step before the previous one are dropped beforehand.
+pub fn update_animated_sprites(ecs: &mut Ecs) -> SystemResult {
+    let delta_time = match ecs.shared_resource::<DeltaTime>() {
+        Some(delta_time) => delta_time.0,
+        None => return Ok(()),
+    };
+
+    if let Some(mut animation_events) = ecs.shared_resource_mut::<AnimationEvents>() {
+        animation_events.update();
+    }
+
+    let mut events = vec![];
+    for (entity, (mut animated_sprite,)) in ecs.query::<(&mut AnimatedSprite,)>() {
+        for frame in animated_sprite.advance(delta_time) {
+            events.push(AnimationFrameEntered {
+                entity,
+                state: None,
+                frame,
+            });
+        }
+    }
+
+    push_animation_events(ecs, events);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animated_sprite() -> AnimatedSprite {
+        AnimatedSprite::new(
+            16.0,
+            16.0,
+            "character",
+            vec![
+                TextureRegion::new(0.0, 0.0, 16.0, 16.0),
+                TextureRegion::new(16.0, 0.0, 16.0, 16.0),
+                TextureRegion::new(32.0, 0.0, 16.0, 16.0),
+            ],
+            0.1,
+        )
+    }
+
+    #[test]
+    fn advance() {
+        let mut animated_sprite = animated_sprite();
+
+        assert_eq!(animated_sprite.advance(0.05), vec![0]);
+        assert_eq!(animated_sprite.state.current_frame, 0);
+        assert_eq!(animated_sprite.advance(0.06), vec![1]);
+        assert_eq!(animated_sprite.state.current_frame, 1);
+        assert_eq!(
+            animated_sprite.current_region(),
+            Some(TextureRegion::new(16.0, 0.0, 16.0, 16.0))
+        );
+    }
+
+    #[test]
+    fn advance_looping() {
+        let mut animated_sprite = animated_sprite();
+
+        let entered_frames = animated_sprite.advance(0.35);
+
+        assert_eq!(entered_frames, vec![0, 1, 2, 0]);
+        assert_eq!(animated_sprite.state.current_frame, 0);
+        assert!(!animated_sprite.state.finished);
+    }
+
+    #[test]
+    fn advance_not_looping() {
+        let mut animated_sprite = animated_sprite();
+        animated_sprite.looping = false;
+
+        let entered_frames = animated_sprite.advance(0.35);
+
+        assert_eq!(entered_frames, vec![0, 1, 2]);
+        assert_eq!(animated_sprite.state.current_frame, 2);
+        assert!(animated_sprite.state.finished);
+
+        animated_sprite.reset();
+        assert_eq!(animated_sprite.state, AnimationState::default());
+    }
+
+    #[test]
+    fn advance_with_frame_durations() {
+        let mut animated_sprite = animated_sprite().with_frame_durations(vec![0.1, 0.3, 0.1]);
+
+        assert_eq!(animated_sprite.advance(0.15), vec![0, 1]);
+        assert_eq!(animated_sprite.advance(0.2), Vec::<usize>::new());
+        assert_eq!(animated_sprite.advance(0.1), vec![2]);
+        assert!((animated_sprite.state.elapsed_time - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn update_animated_sprites_system() {
+        let mut ecs = Ecs::default();
+        ecs.insert((animated_sprite(),));
+        ecs.insert_shared_resource(DeltaTime(0.15));
+
+        update_animated_sprites(&mut ecs).unwrap();
+
+        let (entity, (animated_sprite,)) = ecs.query_one::<(&AnimatedSprite,)>().unwrap();
+        assert_eq!(animated_sprite.state.current_frame, 1);
+        assert_eq!(
+            AnimationEventReader::default()
+                .read(&ecs.shared_resource::<AnimationEvents>().unwrap()),
+            [
+                AnimationFrameEntered {
+                    entity,
+                    state: None,
+                    frame: 0
+                },
+                AnimationFrameEntered {
+                    entity,
+                    state: None,
+                    frame: 1
+                }
+            ]
+        );
+    }
+    #[test]
+    fn read_animation_events() {
+        let event = |frame| AnimationFrameEntered {
+            entity: 0,
+            state: None,
+            frame,
+        };
+        let mut events = AnimationEvents::default();
+        let mut early_reader = AnimationEventReader::default();
+        let mut late_reader = AnimationEventReader::default();
+
+        events.events.push(event(0));
+        assert_eq!(early_reader.read(&events), [event(0)]);
+        events.update();
+        events.events.push(event(1));
+        assert_eq!(early_reader.read(&events), [event(1)]);
+        assert_eq!(late_reader.read(&events), [event(0), event(1)]);
+        events.update();
+
+        assert!(early_reader.read(&events).is_empty());
+        assert_eq!(events.events, [event(1)]);
+        events.update();
+        assert!(events.events.is_empty());
+        assert!(late_reader.read(&events).is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+//! State machines switching between sprite animations depending on
+//! parameters set by the game, e.g. a character going from "idle" to "run"
+//! when its speed isn't zero anymore.
+
+use std::collections::HashMap;
+
+use tuber_core::DeltaTime;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemResult;
+
+use crate::animation::{push_animation_events, AnimatedSprite, AnimationFrameEntered};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParameterValue {
+    Bool(bool),
+    Float(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    IsTrue,
+    IsFalse,
+    GreaterThan(f32),
+    LessThan(f32),
+}
+
+/// A condition on a parameter of the state machine, unset parameters never
+/// fulfill conditions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub parameter: String,
+    pub comparison: Comparison,
+}
+
+impl Condition {
+    #[must_use]
+    pub fn new(parameter: &str, comparison: Comparison) -> Self {
+        Self {
+            parameter: parameter.into(),
+            comparison,
+        }
+    }
+
+    fn is_fulfilled(&self, parameters: &HashMap<String, ParameterValue>) -> bool {
+        let value = match parameters.get(&self.parameter) {
+            Some(value) => *value,
+            None => return false,
+        };
+
+        match (self.comparison, value) {
+            (Comparison::IsTrue, ParameterValue::Bool(value)) => value,
+            (Comparison::IsFalse, ParameterValue::Bool(value)) => !value,
+            (Comparison::GreaterThan(threshold), ParameterValue::Float(value)) => value > threshold,
+            (Comparison::LessThan(threshold), ParameterValue::Float(value)) => value < threshold,
+            _ => false,
+        }
+    }
+}
+
+/// A transition taken when all its conditions are fulfilled
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// The state the transition starts from, any state if unset
+    pub from: Option<String>,
+    pub to: String,
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationStateMachine {
+    states: HashMap<String, AnimatedSprite>,
+    transitions: Vec<Transition>,
+    parameters: HashMap<String, ParameterValue>,
+    current_state: String,
+}
+
+impl AnimationStateMachine {
+    #[must_use]
+    pub fn new(initial_state: &str, initial_animation: AnimatedSprite) -> Self {
+        let mut states = HashMap::new();
+        states.insert(initial_state.to_string(), initial_animation);
+        Self {
+            states,
+            transitions: vec![],
+            parameters: HashMap::new(),
+            current_state: initial_state.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_state(mut self, name: &str, animation: AnimatedSprite) -> Self {
+        self.states.insert(name.into(), animation);
+        self
+    }
+
+    /// Adds a transition, transitions are evaluated in the order they were
+    /// added
+    #[must_use]
+    pub fn with_transition(
+        mut self,
+        from: Option<&str>,
+        to: &str,
+        conditions: Vec<Condition>,
+    ) -> Self {
+        self.transitions.push(Transition {
+            from: from.map(str::to_string),
+            to: to.into(),
+            conditions,
+        });
+        self
+    }
+
+    pub fn set_bool(&mut self, parameter: &str, value: bool) {
+        self.parameters
+            .insert(parameter.into(), ParameterValue::Bool(value));
+    }
+
+    pub fn set_float(&mut self, parameter: &str, value: f32) {
+        self.parameters
+            .insert(parameter.into(), ParameterValue::Float(value));
+    }
+
+    #[must_use]
+    pub fn parameter(&self, parameter: &str) -> Option<ParameterValue> {
+        self.parameters.get(parameter).copied()
+    }
+
+    #[must_use]
+    pub fn current_state(&self) -> &str {
+        &self.current_state
+    }
+
+    #[must_use]
+    pub fn current_animation(&self) -> &AnimatedSprite {
+        &self.states[&self.current_state]
+    }
+
+    /// Takes the first transition whose conditions are fulfilled, if any, then
+    /// advances the animation of the current state and returns the frames
+    /// entered
+    pub fn update(&mut self, delta_time: f64) -> Vec<usize> {
+        let next_state = self
+            .transitions
+            .iter()
+            .filter(|transition| {
+                transition
+                    .from
+                    .iter()
+                    .all(|from| *from == self.current_state)
+                    && transition.to != self.current_state
+                    && self.states.contains_key(&transition.to)
+            })
+            .find(|transition| {
+                transition
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.is_fulfilled(&self.parameters))
+            })
+            .map(|transition| transition.to.clone());
+
+        if let Some(next_state) = next_state {
+            self.current_state = next_state;
+            if let Some(animation) = self.states.get_mut(&self.current_state) {
+                animation.reset();
+            }
+        }
+
+        self.states
+            .get_mut(&self.current_state)
+            .map(|animation| animation.advance(delta_time))
+            .unwrap_or_default()
+    }
+}
+
+/// Updates every animation state machine using the frame's delta time
+pub fn update_animation_state_machines(ecs: &mut Ecs) -> SystemResult {
+    let delta_time = match ecs.shared_resource::<DeltaTime>() {
+        Some(delta_time) => delta_time.0,
+        None => return Ok(()),
+    };
+
+    let mut events = vec![];
+    for (entity, (mut state_machine,)) in ecs.query::<(&mut AnimationStateMachine,)>() {
+        for frame in state_machine.update(delta_time) {
+            events.push(AnimationFrameEntered {
+                entity,
+                state: Some(state_machine.current_state().into()),
+                frame,
+            });
+        }
+    }
+
+    push_animation_events(ecs, events);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::TextureRegion;
+
+    fn animation(y: f32) -> AnimatedSprite {
+        AnimatedSprite::new(
+            16.0,
+            16.0,
+            "character",
+            vec![
+                TextureRegion::new(0.0, y, 16.0, 16.0),
+                TextureRegion::new(16.0, y, 16.0, 16.0),
+            ],
+            0.1,
+        )
+    }
+
+    fn state_machine() -> AnimationStateMachine {
+        AnimationStateMachine::new("idle", animation(0.0))
+            .with_state("run", animation(16.0))
+            .with_state("jump", animation(32.0))
+            .with_transition(
+                None,
+                "jump",
+                vec![Condition::new("grounded", Comparison::IsFalse)],
+            )
+            .with_transition(
+                Some("idle"),
+                "run",
+                vec![
+                    Condition::new("grounded", Comparison::IsTrue),
+                    Condition::new("speed", Comparison::GreaterThan(0.1)),
+                ],
+            )
+            .with_transition(
+                None,
+                "idle",
+                vec![
+                    Condition::new("grounded", Comparison::IsTrue),
+                    Condition::new("speed", Comparison::LessThan(0.1)),
+                ],
+            )
+    }
+
+    #[test]
+    fn transitions() {
+        let mut state_machine = state_machine();
+        state_machine.set_bool("grounded", true);
+        state_machine.set_float("speed", 0.0);
+
+        state_machine.update(0.0);
+        assert_eq!(state_machine.current_state(), "idle");
+
+        state_machine.set_float("speed", 2.0);
+        state_machine.update(0.0);
+        assert_eq!(state_machine.current_state(), "run");
+
+        state_machine.set_bool("grounded", false);
+        state_machine.update(0.0);
+        assert_eq!(state_machine.current_state(), "jump");
+
+        state_machine.set_bool("grounded", true);
+        state_machine.update(0.0);
+        assert_eq!(state_machine.current_state(), "jump");
+
+        state_machine.set_float("speed", 0.0);
+        state_machine.update(0.0);
+        assert_eq!(state_machine.current_state(), "idle");
+    }
+
+    #[test]
+    fn unset_parameters_dont_fulfill_conditions() {
+        let mut state_machine = state_machine();
+
+        state_machine.update(0.0);
+
+        assert_eq!(state_machine.current_state(), "idle");
+    }
+
+    #[test]
+    fn transition_resets_animation() {
+        let mut state_machine = state_machine();
+        state_machine.set_bool("grounded", true);
+        state_machine.set_float("speed", 2.0);
+        state_machine.update(0.15);
+        assert_eq!(state_machine.current_animation().state.current_frame, 1);
+
+        state_machine.set_bool("grounded", false);
+        state_machine.update(0.05);
+
+        assert_eq!(state_machine.current_state(), "jump");
+        assert_eq!(state_machine.current_animation().state.current_frame, 0);
+        assert_eq!(
+            state_machine.current_animation().current_region(),
+            Some(TextureRegion::new(0.0, 32.0, 16.0, 16.0))
+        );
+    }
+
+    #[test]
+    fn default_system_bundle_updates_state_machines() {
+        let mut system_bundle = crate::default_system_bundle();
+        let mut ecs = Ecs::default();
+        ecs.insert_shared_resource(DeltaTime(0.15));
+        let mut state_machine = state_machine();
+        state_machine.set_bool("grounded", false);
+        let character = ecs.insert((state_machine,));
+
+        system_bundle.step(&mut ecs, &mut ()).unwrap();
+
+        let (_, (state_machine,)) = ecs
+            .query_one_by_id::<(&AnimationStateMachine,)>(character)
+            .unwrap();
+        assert_eq!(state_machine.current_state(), "jump");
+        assert_eq!(state_machine.current_animation().state.current_frame, 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! Character art exported from Aseprite: a texture and an animation per frame
+//! tag.
+//!
+//! Sheets are imported from the JSON file Aseprite exports along with the
+//! sprite sheet image, with the frame tags included in its metadata. The
+//! frames of a tag are played in the direction of the tag, each for the
+//! duration set in Aseprite.
+
+use std::collections::HashMap;
+
+use tuber_core::asset::Metadata;
+use tuber_core::vfs::Vfs;
+use tuber_core::CoreResult;
+
+use crate::animation::AnimatedSprite;
+use crate::animation_state_machine::AnimationStateMachine;
+use crate::sprite_atlas::{load_sprite_atlas, FrameTag, SpriteAtlas};
+use crate::GraphicsResult;
+
+/// The frame duration Aseprite uses when a frame doesn't specify one, in
+/// seconds
+const DEFAULT_FRAME_DURATION: f64 = 0.1;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AsepriteSheet {
+    atlas: SpriteAtlas,
+    animations: HashMap<String, AnimatedSprite>,
+}
+
+impl AsepriteSheet {
+    pub fn from_json(json: &[u8]) -> GraphicsResult<Self> {
+        Ok(Self::from_atlas(SpriteAtlas::from_json(json)?))
+    }
+
+    #[must_use]
+    pub fn from_atlas(atlas: SpriteAtlas) -> Self {
+        let animations = atlas
+            .tags()
+            .iter()
+            .map(|tag| (tag.name.clone(), tag_animation(&atlas, tag)))
+            .collect();
+        Self { atlas, animations }
+    }
+
+    /// Returns the texture of the sheet, see `SpriteAtlas::texture`
+    #[must_use]
+    pub fn texture(&self) -> &str {
+        self.atlas.texture()
+    }
+
+    #[must_use]
+    pub fn atlas(&self) -> &SpriteAtlas {
+        &self.atlas
+    }
+
+    pub fn tag_names(&self) -> impl Iterator<Item = &str> {
+        self.atlas.tags().iter().map(|tag| tag.name.as_str())
+    }
+
+    /// Returns the animation of the given tag, starting from its first frame
+    #[must_use]
+    pub fn animation(&self, tag: &str) -> Option<AnimatedSprite> {
+        self.animations.get(tag).cloned()
+    }
+
+    /// Creates an animation state machine with a state per tag, named after
+    /// the tag, starting in the given one. Transitions are left to the
+    /// caller.
+    #[must_use]
+    pub fn animation_state_machine(&self, initial_tag: &str) -> Option<AnimationStateMachine> {
+        let initial_animation = self.animation(initial_tag)?;
+        Some(
+            self.animations
+                .iter()
+                .filter(|(tag, _)| *tag != initial_tag)
+                .fold(
+                    AnimationStateMachine::new(initial_tag, initial_animation),
+                    |state_machine, (tag, animation)| {
+                        state_machine.with_state(tag, animation.clone())
+                    },
+                ),
+        )
+    }
+}
+
+fn tag_animation(atlas: &SpriteAtlas, tag: &FrameTag) -> AnimatedSprite {
+    let frames: Vec<_> = tag
+        .frame_indices()
+        .into_iter()
+        .map(|index| &atlas.frames()[index])
+        .collect();
+    let (width, height) = frames.first().map_or((0.0, 0.0), |frame| {
+        (frame.region.width, frame.region.height)
+    });
+    let frame_durations = frames
+        .iter()
+        .map(|frame| frame.duration.unwrap_or(DEFAULT_FRAME_DURATION))
+        .collect();
+
+    AnimatedSprite::new(
+        width,
+        height,
+        atlas.texture(),
+        frames.iter().map(|frame| frame.region).collect(),
+        DEFAULT_FRAME_DURATION,
+    )
+    .with_frame_durations(frame_durations)
+}
+
+/// Loads the sheet file named by the "file" metadata of the asset, the frames
+/// being regions of the texture asset named by its "texture" metadata
+pub fn aseprite_sheet_loader(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Box<AsepriteSheet>> {
+    Ok(Box::new(AsepriteSheet::from_atlas(load_sprite_atlas(
+        metadata, vfs,
+    )?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use tuber_core::vfs::InMemoryVfs;
+
+    use super::*;
+    use crate::texture::TextureRegion;
+    use crate::GraphicsError;
+
+    const SHEET: &str = r#"{
+        "frames": {
+            "knight 0.aseprite": { "frame": { "x": 0, "y": 0, "w": 16, "h": 24 }, "duration": 100 },
+            "knight 1.aseprite": { "frame": { "x": 16, "y": 0, "w": 16, "h": 24 }, "duration": 150 },
+            "knight 2.aseprite": { "frame": { "x": 32, "y": 0, "w": 16, "h": 24 }, "duration": 100 },
+            "knight 3.aseprite": { "frame": { "x": 48, "y": 0, "w": 16, "h": 24 }, "duration": 200 },
+            "knight 4.aseprite": { "frame": { "x": 0, "y": 24, "w": 16, "h": 24 }, "duration": 50 }
+        },
+        "meta": {
+            "app": "https://www.aseprite.org/",
+            "image": "knight.png",
+            "frameTags": [
+                { "name": "idle", "from": 0, "to": 1, "direction": "forward" },
+                { "name": "walk", "from": 2, "to": 4, "direction": "pingpong" },
+                { "name": "fall", "from": 3, "to": 4, "direction": "reverse" }
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn tag_animations() {
+        let sheet = AsepriteSheet::from_json(SHEET.as_bytes()).unwrap();
+
+        assert_eq!(sheet.texture(), "knight.png");
+        assert_eq!(
+            sheet.tag_names().collect::<Vec<_>>(),
+            ["idle", "walk", "fall"]
+        );
+
+        let idle = sheet.animation("idle").unwrap();
+        assert_eq!(idle.texture, "knight.png");
+        assert_eq!((idle.width, idle.height), (16.0, 24.0));
+        assert_eq!(idle.frame_durations, [0.1, 0.15]);
+        assert_eq!(idle.frames[1], TextureRegion::new(16.0, 0.0, 16.0, 24.0));
+
+        let walk = sheet.animation("walk").unwrap();
+        assert_eq!(walk.frames.len(), 4);
+        assert_eq!(walk.frames[3], TextureRegion::new(48.0, 0.0, 16.0, 24.0));
+        assert_eq!(walk.frame_durations, [0.1, 0.2, 0.05, 0.2]);
+
+        let fall = sheet.animation("fall").unwrap();
+        assert_eq!(fall.frames[0], TextureRegion::new(0.0, 24.0, 16.0, 24.0));
+
+        assert_eq!(sheet.animation("attack"), None);
+    }
+
+    #[test]
+    fn animation_state_machine() {
+        let sheet = AsepriteSheet::from_json(SHEET.as_bytes()).unwrap();
+
+        let state_machine = sheet.animation_state_machine("walk").unwrap();
+
+        assert_eq!(state_machine.current_state(), "walk");
+        assert_eq!(state_machine.current_animation().frames.len(), 4);
+        assert!(sheet.animation_state_machine("attack").is_none());
+    }
+
+    #[test]
+    fn tags_out_of_range_are_rejected() {
+        let json = r#"{
+            "frames": [{ "filename": "a", "frame": { "x": 0, "y": 0, "w": 8, "h": 8 } }],
+            "meta": { "image": "a.png", "frameTags": [{ "name": "run", "from": 0, "to": 3 }] }
+        }"#;
+
+        assert!(matches!(
+            AsepriteSheet::from_json(json.as_bytes()),
+            Err(GraphicsError::SpriteAtlasParseError(_))
+        ));
+    }
+
+    #[test]
+    fn load_aseprite_sheet() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("assets/knight/knight.json", SHEET.as_bytes().to_vec());
+        let mut metadata = HashMap::new();
+        metadata.insert("file".to_string(), "knight.json".to_string());
+        metadata.insert("texture".to_string(), "knight_texture".to_string());
+        let metadata = Metadata {
+            identifier: "knight".into(),
+            kind: "aseprite_sheet".into(),
+            metadata,
+            dependencies: vec![],
+            asset_path: PathBuf::from("assets/knight"),
+        };
+
+        let sheet = aseprite_sheet_loader(&metadata, &vfs).unwrap();
+
+        assert_eq!(sheet.animation("idle").unwrap().texture, "knight_texture");
+    }
+}
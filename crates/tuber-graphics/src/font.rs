@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use serde_derive::Deserialize;
+
+use crate::texture::TextureRegion;
+use crate::{GraphicsError, GraphicsResult};
+
+const DEFAULT_FONT: &[u8] = include_bytes!("../fonts/default_font.json");
+
+/// A font whose glyphs are regions of an atlas texture
+#[derive(Debug, Clone, Deserialize)]
+pub struct BitmapFont {
+    font_atlas_region: TextureRegion,
+    ignore_case: bool,
+    line_height: u32,
+    line_spacing: u32,
+    letter_spacing: u32,
+    glyphs: HashMap<char, BitmapGlyph>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct BitmapGlyph {
+    region: TextureRegion,
+}
+
+impl BitmapGlyph {
+    /// Returns the region of the glyph relative to the font atlas region
+    #[must_use]
+    pub fn region(&self) -> TextureRegion {
+        self.region
+    }
+}
+
+impl BitmapFont {
+    pub fn from_json(json: &[u8]) -> GraphicsResult<Self> {
+        serde_json::from_slice(json).map_err(|e| GraphicsError::FontParseError(e.to_string()))
+    }
+
+    /// Returns the font bundled with tuber-graphics
+    #[must_use]
+    pub fn default_font() -> Self {
+        Self::from_json(DEFAULT_FONT).expect("The default font is valid")
+    }
+
+    #[must_use]
+    pub fn font_atlas_region(&self) -> TextureRegion {
+        self.font_atlas_region
+    }
+
+    #[must_use]
+    pub fn glyph(&self, character: char) -> Option<&BitmapGlyph> {
+        if self.ignore_case {
+            return self
+                .glyphs
+                .get(&character.to_ascii_uppercase())
+                .or_else(|| self.glyphs.get(&character.to_ascii_lowercase()));
+        }
+
+        self.glyphs.get(&character)
+    }
+
+    #[must_use]
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    #[must_use]
+    pub fn line_spacing(&self) -> u32 {
+        self.line_spacing
+    }
+
+    #[must_use]
+    pub fn letter_spacing(&self) -> u32 {
+        self.letter_spacing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_font() {
+        let font = BitmapFont::default_font();
+
+        assert_eq!(font.line_height(), 7);
+        assert_eq!(font.line_spacing(), 1);
+        assert_eq!(font.letter_spacing(), 1);
+        assert_eq!(
+            font.glyph('B').unwrap().region(),
+            TextureRegion::new(6.0, 0.0, 5.0, 7.0)
+        );
+    }
+
+    #[test]
+    fn glyph_ignore_case() {
+        let font = BitmapFont::default_font();
+
+        assert_eq!(
+            font.glyph('b').unwrap().region(),
+            font.glyph('B').unwrap().region()
+        );
+        assert!(font.glyph('%').is_none());
+    }
+}
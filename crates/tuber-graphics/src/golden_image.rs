@@ -0,0 +1,154 @@
+//! Comparison of rendered images with reference "golden" images, so render
+//! regressions can be caught by automated tests.
+//!
+//! Golden images are stored as their width and height as little-endian u32
+//! followed by their RGBA8 pixels. Setting the `UPDATE_GOLDEN_IMAGES`
+//! environment variable makes `check_golden_image` overwrite them with the
+//! rendered images instead of comparing.
+
+use std::path::Path;
+
+use crate::texture::TextureData;
+use crate::{GraphicsError, GraphicsResult};
+
+const UPDATE_GOLDEN_IMAGES_VARIABLE: &str = "UPDATE_GOLDEN_IMAGES";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ImageDifference {
+    /// The number of pixels with at least one channel differing by more than
+    /// the tolerance
+    pub differing_pixels: usize,
+    pub max_channel_difference: u8,
+}
+
+/// Compares two images of the same size, ignoring channel differences up to
+/// the tolerance
+pub fn compare_images(
+    actual: &TextureData,
+    expected: &TextureData,
+    tolerance: u8,
+) -> GraphicsResult<ImageDifference> {
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        return Err(GraphicsError::ImageSizeMismatch);
+    }
+
+    let mut difference = ImageDifference::default();
+    for (actual_pixel, expected_pixel) in actual
+        .data
+        .chunks_exact(TextureData::BYTES_PER_PIXEL)
+        .zip(expected.data.chunks_exact(TextureData::BYTES_PER_PIXEL))
+    {
+        let pixel_difference = actual_pixel
+            .iter()
+            .zip(expected_pixel)
+            .map(|(a, b)| a.abs_diff(*b))
+            .max()
+            .unwrap_or(0);
+        difference.max_channel_difference = difference.max_channel_difference.max(pixel_difference);
+        if pixel_difference > tolerance {
+            difference.differing_pixels += 1;
+        }
+    }
+
+    Ok(difference)
+}
+
+pub fn save_golden_image(path: &Path, image: &TextureData) -> GraphicsResult<()> {
+    let mut bytes = Vec::with_capacity(8 + image.data.len());
+    bytes.extend_from_slice(&image.width.to_le_bytes());
+    bytes.extend_from_slice(&image.height.to_le_bytes());
+    bytes.extend_from_slice(&image.data);
+    std::fs::write(path, bytes).map_err(|e| GraphicsError::GoldenImageError(e.to_string()))
+}
+
+pub fn load_golden_image(path: &Path) -> GraphicsResult<TextureData> {
+    let bytes = std::fs::read(path).map_err(|e| GraphicsError::GoldenImageError(e.to_string()))?;
+    let invalid_image =
+        || GraphicsError::GoldenImageError(format!("{} is invalid", path.display()));
+    if bytes.len() < 8 {
+        return Err(invalid_image());
+    }
+
+    let (header, data) = bytes.split_at(8);
+    let width = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let height = u32::from_le_bytes(header[4..].try_into().unwrap());
+    if data.len() != width as usize * height as usize * TextureData::BYTES_PER_PIXEL {
+        return Err(invalid_image());
+    }
+
+    Ok(TextureData {
+        width,
+        height,
+        data: data.to_vec(),
+    })
+}
+
+/// Compares an image with the golden image stored at the given path, fails
+/// with `GoldenImageMismatch` if any pixel differs by more than the tolerance
+pub fn check_golden_image(path: &Path, actual: &TextureData, tolerance: u8) -> GraphicsResult<()> {
+    if std::env::var_os(UPDATE_GOLDEN_IMAGES_VARIABLE).is_some() {
+        return save_golden_image(path, actual);
+    }
+
+    let expected = load_golden_image(path)?;
+    let difference = compare_images(actual, &expected, tolerance)?;
+    if difference.differing_pixels > 0 {
+        return Err(GraphicsError::GoldenImageMismatch(difference));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> TextureData {
+        let mut image = TextureData::new(4, 4);
+        image.set_pixel(1, 2, [255, 0, 0, 255]);
+        image
+    }
+
+    #[test]
+    fn compare_identical_images() {
+        let difference = compare_images(&image(), &image(), 0).unwrap();
+
+        assert_eq!(difference, ImageDifference::default());
+    }
+
+    #[test]
+    fn compare_images_tolerance() {
+        let mut actual = image();
+        actual.set_pixel(1, 2, [250, 0, 0, 255]);
+        actual.set_pixel(3, 3, [0, 0, 20, 0]);
+
+        let difference = compare_images(&actual, &image(), 5).unwrap();
+
+        assert_eq!(
+            difference,
+            ImageDifference {
+                differing_pixels: 1,
+                max_channel_difference: 20,
+            }
+        );
+    }
+
+    #[test]
+    fn compare_images_size_mismatch() {
+        assert!(matches!(
+            compare_images(&image(), &TextureData::new(2, 2), 0),
+            Err(GraphicsError::ImageSizeMismatch)
+        ));
+    }
+
+    #[test]
+    fn save_and_load_golden_image() {
+        let path = std::env::temp_dir().join("tuber_golden_image_test.golden");
+
+        save_golden_image(&path, &image()).unwrap();
+        let loaded = load_golden_image(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, image());
+    }
+}
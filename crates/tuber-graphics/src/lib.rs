@@ -4,69 +4,595 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::module_name_repetitions)]
 
+use std::collections::HashSet;
+use std::num::NonZeroU32;
+use std::sync::mpsc;
+
 use futures::executor::block_on;
-use log::{info, trace};
+use log::{info, trace, warn};
 use raw_window_handle::HasRawWindowHandle;
 use wgpu::{
-    Adapter as WGPUAdapter, Backends as WGPUBackends,
+    Adapter as WGPUAdapter, AdapterInfo as WGPUAdapterInfo, Backends as WGPUBackends,
+    BufferDescriptor as WGPUBufferDescriptor, BufferUsages as WGPUBufferUsages,
     CommandEncoderDescriptor as WGPUCommandEncoderDescriptor, Device as WGPUDevice,
-    DeviceDescriptor as WGPUDeviceDescriptor, Instance as WGPUInstance, Limits as WGPULimits,
+    DeviceDescriptor as WGPUDeviceDescriptor, Extent3d as WGPUExtent3d, Features as WGPUFeatures,
+    ImageCopyBuffer as WGPUImageCopyBuffer, ImageCopyTexture as WGPUImageCopyTexture,
+    ImageDataLayout as WGPUImageDataLayout, Instance as WGPUInstance, Limits as WGPULimits,
+    Maintain as WGPUMaintain, MapMode as WGPUMapMode, Origin3d as WGPUOrigin3d,
     PowerPreference as WGPUPowerPreference, PresentMode as WGPUPresentMode, Queue as WGPUQueue,
-    RequestAdapterOptions as WGPURequestAdapterOptions, Surface as WGPUSurface,
+    RequestAdapterOptions as WGPURequestAdapterOptions,
+    RequestDeviceError as WGPURequestDeviceError, Surface as WGPUSurface,
     SurfaceConfiguration as WGPUSurfaceConfiguration, SurfaceError as WGPUSurfaceError,
-    TextureUsages as WGPUTextureUsages, TextureViewDescriptor as WGPUTextureViewDescriptor,
+    Texture as WGPUTexture, TextureAspect as WGPUTextureAspect,
+    TextureDescriptor as WGPUTextureDescriptor, TextureDimension as WGPUTextureDimension,
+    TextureFormat as WGPUTextureFormat, TextureUsages as WGPUTextureUsages,
+    TextureViewDescriptor as WGPUTextureViewDescriptor, COPY_BYTES_PER_ROW_ALIGNMENT,
 };
 
+use tuber_core::asset::Store;
 use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemBundle;
+
+use animation::AnimatedSprite;
+use sprite::Sprite;
+use texture::TextureData;
+use texture_cache::{TextureCache, TextureId};
+
+pub mod animation;
+pub mod animation_state_machine;
+pub mod aseprite;
+pub mod font;
+pub mod golden_image;
+pub mod mesh;
+pub mod particle;
+pub mod sprite;
+pub mod sprite_atlas;
+pub mod text_layout;
+pub mod texture;
+pub mod texture_atlas;
+pub mod texture_cache;
 
 pub type GraphicsResult<T> = Result<T, GraphicsError>;
 
 #[derive(Debug, Clone)]
 pub enum GraphicsError {
     SurfaceError(WGPUSurfaceError),
+    DeviceRequestError(WGPURequestDeviceError),
+    TextureAtlasOverflow,
+    FontParseError(String),
+    TextureParseError(String),
+    InvalidTextureData(String),
+    SpriteAtlasParseError(String),
+    SpriteAtlasFrameNotFound(String),
+    AssetLoadError(String),
+    AdapterNotFound,
+    NoOffscreenRenderTarget,
+    BufferMapError,
+    ImageSizeMismatch,
+    GoldenImageError(String),
+    GoldenImageMismatch(golden_image::ImageDifference),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Dx11,
+    Gl,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerPreference {
+    #[default]
+    LowPower,
+    HighPerformance,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphicsSettings {
+    /// Renders to an sRGB surface so colors computed in linear space are
+    /// converted on output. Disabling it restores the legacy behavior of
+    /// writing shader output to the surface as is.
+    pub gamma_correction: bool,
+    /// The only backend adapters are looked for with, any backend if unset
+    pub backend: Option<Backend>,
+    pub power_preference: PowerPreference,
+    /// Uses the software adapter, e.g. to work around broken drivers
+    pub force_fallback_adapter: bool,
+    /// Selects the first adapter whose name contains this string, see
+    /// `Graphics::enumerate_adapters`. The default selection is used if no
+    /// compatible adapter matches.
+    pub adapter_name: Option<String>,
+    /// The VRAM textures may use, in bytes, the least recently used textures
+    /// being unloaded when it is exceeded. Textures are never unloaded
+    /// automatically if unset.
+    pub vram_budget: Option<u64>,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            gamma_correction: true,
+            backend: None,
+            power_preference: PowerPreference::default(),
+            force_fallback_adapter: false,
+            adapter_name: None,
+            vram_budget: None,
+        }
+    }
 }
 
+impl GraphicsSettings {
+    #[must_use]
+    pub fn backends(&self) -> WGPUBackends {
+        match self.backend {
+            None => WGPUBackends::all(),
+            Some(Backend::Vulkan) => WGPUBackends::VULKAN,
+            Some(Backend::Metal) => WGPUBackends::METAL,
+            Some(Backend::Dx12) => WGPUBackends::DX12,
+            Some(Backend::Dx11) => WGPUBackends::DX11,
+            Some(Backend::Gl) => WGPUBackends::GL,
+        }
+    }
+
+    fn wgpu_power_preference(&self) -> WGPUPowerPreference {
+        match self.power_preference {
+            PowerPreference::LowPower => WGPUPowerPreference::LowPower,
+            PowerPreference::HighPerformance => WGPUPowerPreference::HighPerformance,
+        }
+    }
+
+    /// Returns the format albedo textures must be uploaded with so sampling
+    /// them returns linear colors
+    #[must_use]
+    pub fn albedo_texture_format(&self) -> WGPUTextureFormat {
+        if self.gamma_correction {
+            WGPUTextureFormat::Rgba8UnormSrgb
+        } else {
+            WGPUTextureFormat::Rgba8Unorm
+        }
+    }
+}
+
+/// Returns the systems updating the graphics components, such as animated
+/// sprites, animation state machines and particle emitters
+#[must_use]
+pub fn default_system_bundle<AD: 'static>() -> SystemBundle<AD> {
+    let mut system_bundle = SystemBundle::default();
+    system_bundle.add_system(|ecs: &mut Ecs, _: &mut AD| animation::update_animated_sprites(ecs));
+    system_bundle.add_system(|ecs: &mut Ecs, _: &mut AD| {
+        animation_state_machine::update_animation_state_machines(ecs)
+    });
+    system_bundle.add_system(|ecs: &mut Ecs, _: &mut AD| particle::update_particle_emitters(ecs));
+    system_bundle
+}
+
+/// What the device supports, features missing from the adapter being
+/// disabled rather than failing the device creation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphicsCapabilities {
+    /// Whether polygons can be rendered as lines
+    pub wireframe: bool,
+    pub max_texture_dimension: u32,
+    pub max_uniform_buffer_binding_size: u32,
+}
+
+/// The size of a window in physical pixels
 pub struct WindowSize {
     pub width: u32,
     pub height: u32,
 }
 
+impl WindowSize {
+    /// Returns the size in logical pixels, which UI layout is expressed in so
+    /// it keeps the same apparent size whatever the scale factor of the
+    /// display
+    #[must_use]
+    pub fn to_logical(&self, scale_factor: f64) -> (f64, f64) {
+        (
+            f64::from(self.width) / scale_factor,
+            f64::from(self.height) / scale_factor,
+        )
+    }
+}
+
 pub trait GraphicsAPI {
-    fn render_scene(&mut self, _ecs: &Ecs) -> GraphicsResult<()>;
+    /// Renders the scene, loading the textures it displays from the store
+    fn render_scene(&mut self, ecs: &Ecs, store: &mut Store) -> GraphicsResult<()>;
+}
+
+enum RenderTarget {
+    Surface(WGPUSurface),
+    /// The texture headless graphics render into
+    Offscreen(WGPUTexture),
 }
 
 pub struct Graphics {
     device: WGPUDevice,
     queue: WGPUQueue,
-    surface: WGPUSurface,
-    _window_size: WindowSize,
+    render_target: RenderTarget,
+    surface_format: WGPUTextureFormat,
+    settings: GraphicsSettings,
+    capabilities: GraphicsCapabilities,
+    window_size: WindowSize,
+    /// The ratio between physical and logical pixels of the display the window
+    /// is on, above 1 on high density displays
+    scale_factor: f64,
+    textures: TextureCache<WGPUTexture>,
+    /// The textures and sprites of the scene that couldn't be loaded, reported
+    /// once
+    reported_missing_textures: HashSet<String>,
 }
 
 impl Graphics {
-    pub fn new<Window>(window: &Window, window_size: WindowSize) -> Self
+    pub fn new<Window>(
+        window: &Window,
+        window_size: WindowSize,
+        settings: GraphicsSettings,
+    ) -> GraphicsResult<Self>
     where
         Window: HasRawWindowHandle,
     {
         info!("Initializing graphics API");
-        let instance = Self::create_wgpu_instance();
+        let instance = Self::create_wgpu_instance(&settings);
         let surface = Self::create_render_surface(&instance, window);
-        let adapter = Self::request_adapter(&instance, &surface);
+        let adapter = Self::request_adapter(&instance, &surface, &settings)?;
         Self::log_adapter_details(&adapter);
-        let (device, queue) = Self::request_device(&adapter);
-        Self::configure_surface(&window_size, &surface, &adapter, &device);
+        let (device, queue, capabilities) = Self::request_device(&adapter)?;
+        let surface_format = Self::select_surface_format(
+            &surface.get_supported_formats(&adapter),
+            settings.gamma_correction,
+        );
+        Self::configure_surface(&window_size, &surface, surface_format, &device);
         info!("Graphics API has been initialized successfully");
 
-        Self {
+        Ok(Self {
             device,
             queue,
-            surface,
-            _window_size: window_size,
+            render_target: RenderTarget::Surface(surface),
+            surface_format,
+            textures: TextureCache::new(settings.vram_budget),
+            reported_missing_textures: HashSet::new(),
+            settings,
+            capabilities,
+            window_size,
+            scale_factor: 1.0,
+        })
+    }
+
+    /// Creates graphics rendering into an offscreen texture of the given size
+    /// using the fallback adapter, so renders are reproducible across
+    /// machines. The rendered pixels are retrieved with `read_pixels`.
+    pub fn new_headless(size: WindowSize, settings: GraphicsSettings) -> GraphicsResult<Self> {
+        info!("Initializing headless graphics API");
+        let instance = Self::create_wgpu_instance(&settings);
+        let adapter = block_on(instance.request_adapter(&WGPURequestAdapterOptions {
+            power_preference: settings.wgpu_power_preference(),
+            force_fallback_adapter: true,
+            compatible_surface: None,
+        }))
+        .ok_or(GraphicsError::AdapterNotFound)?;
+        Self::log_adapter_details(&adapter);
+        let (device, queue, capabilities) = Self::request_device(&adapter)?;
+        let format = Self::select_surface_format(
+            &[
+                WGPUTextureFormat::Rgba8Unorm,
+                WGPUTextureFormat::Rgba8UnormSrgb,
+            ],
+            settings.gamma_correction,
+        );
+        let texture = device.create_texture(&WGPUTextureDescriptor {
+            label: Some("offscreen_render_target"),
+            size: WGPUExtent3d {
+                width: size.width,
+                height: size.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: WGPUTextureDimension::D2,
+            format,
+            usage: WGPUTextureUsages::RENDER_ATTACHMENT | WGPUTextureUsages::COPY_SRC,
+        });
+        info!("Headless graphics API has been initialized successfully");
+
+        Ok(Self {
+            device,
+            queue,
+            render_target: RenderTarget::Offscreen(texture),
+            surface_format: format,
+            textures: TextureCache::new(settings.vram_budget),
+            reported_missing_textures: HashSet::new(),
+            settings,
+            capabilities,
+            window_size: size,
+            scale_factor: 1.0,
+        })
+    }
+
+    /// Returns the pixels of the last render of headless graphics
+    pub fn read_pixels(&self) -> GraphicsResult<TextureData> {
+        let texture = match &self.render_target {
+            RenderTarget::Offscreen(texture) => texture,
+            RenderTarget::Surface(_) => return Err(GraphicsError::NoOffscreenRenderTarget),
+        };
+
+        let WindowSize { width, height } = self.window_size;
+        #[allow(clippy::cast_possible_truncation)]
+        let row_length = width * TextureData::BYTES_PER_PIXEL as u32;
+        let padded_row_length =
+            row_length.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = self.device.create_buffer(&WGPUBufferDescriptor {
+            label: Some("read_pixels_buffer"),
+            size: u64::from(padded_row_length) * u64::from(height),
+            usage: WGPUBufferUsages::COPY_DST | WGPUBufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut command_encoder =
+            self.device
+                .create_command_encoder(&WGPUCommandEncoderDescriptor {
+                    label: Some("read_pixels_command_encoder"),
+                });
+        command_encoder.copy_texture_to_buffer(
+            WGPUImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: WGPUOrigin3d::ZERO,
+                aspect: WGPUTextureAspect::All,
+            },
+            WGPUImageCopyBuffer {
+                buffer: &buffer,
+                layout: WGPUImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_row_length),
+                    rows_per_image: None,
+                },
+            },
+            WGPUExtent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(std::iter::once(command_encoder.finish()));
+
+        let buffer_slice = buffer.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        buffer_slice.map_async(WGPUMapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(WGPUMaintain::Wait);
+        receiver
+            .recv()
+            .map_err(|_| GraphicsError::BufferMapError)?
+            .map_err(|_| GraphicsError::BufferMapError)?;
+
+        let mut pixels = TextureData::new(width, height);
+        {
+            let mapped_buffer = buffer_slice.get_mapped_range();
+            let (row_length, padded_row_length) = (row_length as usize, padded_row_length as usize);
+            for (row, padded_row) in pixels
+                .data
+                .chunks_exact_mut(row_length)
+                .zip(mapped_buffer.chunks_exact(padded_row_length))
+            {
+                row.copy_from_slice(&padded_row[..row_length]);
+            }
+        }
+        buffer.unmap();
+
+        Ok(pixels)
+    }
+
+    /// Reconfigures the render surface for the new size of the window
+    pub fn on_window_resized(&mut self, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        self.window_size = WindowSize { width, height };
+        if let RenderTarget::Surface(surface) = &self.render_target {
+            Self::configure_surface(
+                &self.window_size,
+                surface,
+                self.surface_format,
+                &self.device,
+            );
+        }
+    }
+
+    /// Updates the scale factor when the window moves to a display with a
+    /// different density, along with the new physical size of the window
+    pub fn on_scale_factor_changed(&mut self, scale_factor: f64, width: u32, height: u32) {
+        self.scale_factor = scale_factor;
+        self.on_window_resized(width, height);
+    }
+
+    #[must_use]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    #[must_use]
+    pub fn window_size(&self) -> &WindowSize {
+        &self.window_size
+    }
+
+    /// Returns the size of the window in logical pixels
+    #[must_use]
+    pub fn logical_window_size(&self) -> (f64, f64) {
+        self.window_size.to_logical(self.scale_factor)
+    }
+
+    #[must_use]
+    pub fn settings(&self) -> &GraphicsSettings {
+        &self.settings
+    }
+
+    #[must_use]
+    pub fn capabilities(&self) -> GraphicsCapabilities {
+        self.capabilities
+    }
+
+    #[must_use]
+    pub fn surface_format(&self) -> WGPUTextureFormat {
+        self.surface_format
+    }
+
+    /// Uploads a texture to VRAM, replacing the texture with the same
+    /// identifier. The least recently used textures are unloaded if the VRAM
+    /// budget is exceeded, except the ones displayed by the frame being
+    /// rendered.
+    pub fn load_texture_in_vram(
+        &mut self,
+        id: TextureId,
+        texture_data: &TextureData,
+    ) -> GraphicsResult<()> {
+        texture_data.validate(self.capabilities.max_texture_dimension)?;
+        let size = WGPUExtent3d {
+            width: texture_data.width,
+            height: texture_data.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = self.device.create_texture(&WGPUTextureDescriptor {
+            label: Some(&id.0),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: WGPUTextureDimension::D2,
+            format: self.settings.albedo_texture_format(),
+            usage: WGPUTextureUsages::TEXTURE_BINDING | WGPUTextureUsages::COPY_DST,
+        });
+        #[allow(clippy::cast_possible_truncation)]
+        let row_length = texture_data.width * TextureData::BYTES_PER_PIXEL as u32;
+        self.queue.write_texture(
+            WGPUImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: WGPUOrigin3d::ZERO,
+                aspect: WGPUTextureAspect::All,
+            },
+            &texture_data.data,
+            WGPUImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(row_length),
+                rows_per_image: None,
+            },
+            size,
+        );
+
+        let vram_size = texture_data.data.len() as u64;
+        Self::destroy_textures(self.textures.insert(id, texture, vram_size));
+        Ok(())
+    }
+
+    fn destroy_textures(textures: Vec<(TextureId, WGPUTexture)>) {
+        for (id, texture) in textures {
+            trace!("Unloading texture {} from VRAM", id.0);
+            texture.destroy();
         }
     }
 
-    fn create_wgpu_instance() -> WGPUInstance {
+    /// Frees the VRAM used by a texture, returns whether it was loaded
+    pub fn unload_texture_from_vram(&mut self, id: &TextureId) -> bool {
+        match self.textures.remove(id) {
+            Some(texture) => {
+                texture.destroy();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the texture if it is in VRAM, marking it as recently used
+    pub fn texture_in_vram(&mut self, id: &TextureId) -> Option<&WGPUTexture> {
+        self.textures.get(id)
+    }
+
+    /// Returns the VRAM used by the loaded textures, in bytes
+    #[must_use]
+    pub fn vram_usage(&self) -> u64 {
+        self.textures.used()
+    }
+
+    /// Makes the textures displayed by the sprites of the scene resident in
+    /// VRAM, uploading the missing ones and marking the others as recently
+    /// used. They are pinned until the next frame so uploading some doesn't
+    /// unload the others.
+    fn prepare_scene_textures(&mut self, ecs: &Ecs, store: &mut Store) {
+        let mut textures: Vec<TextureId> = vec![];
+        let mut use_texture = |texture: &str| {
+            let texture = TextureId::from(texture);
+            if !textures.contains(&texture) {
+                textures.push(texture);
+            }
+        };
+        for (_, (sprite,)) in ecs.query::<(&Sprite,)>() {
+            match sprite.resolve(store) {
+                Ok((texture, _)) => use_texture(&texture),
+                Err(e) => self.report_missing_texture(
+                    format!("{:?}", sprite.source),
+                    &format!("Couldn't resolve the texture of a sprite: {e:?}"),
+                ),
+            }
+        }
+        for (_, (animated_sprite,)) in ecs.query::<(&AnimatedSprite,)>() {
+            use_texture(&animated_sprite.texture);
+        }
+
+        Self::destroy_textures(self.textures.pin(textures.iter().cloned()));
+        for texture in textures {
+            if self.textures.get(&texture).is_some() {
+                continue;
+            }
+
+            let result = store
+                .asset::<TextureData>(&texture.0)
+                .map_err(|e| GraphicsError::AssetLoadError(format!("{e:?}")))
+                .and_then(|texture_data| {
+                    trace!("Loading texture {} in VRAM", texture.0);
+                    self.load_texture_in_vram(texture.clone(), texture_data)
+                });
+            if let Err(e) = result {
+                self.report_missing_texture(
+                    texture.0.clone(),
+                    &format!("Couldn't load texture {}: {e:?}", texture.0),
+                );
+            }
+        }
+    }
+
+    /// Logs why a texture of the scene couldn't be loaded the first time it
+    /// fails
+    fn report_missing_texture(&mut self, texture: String, message: &str) {
+        if self.reported_missing_textures.insert(texture) {
+            warn!("{message}");
+        }
+    }
+
+    /// Changes the VRAM budget, unloading the least recently used textures
+    /// until it is respected
+    pub fn set_vram_budget(&mut self, budget: Option<u64>) {
+        self.settings.vram_budget = budget;
+        Self::destroy_textures(self.textures.set_budget(budget));
+    }
+
+    /// Returns the adapters available with the backends allowed by the
+    /// settings
+    #[must_use]
+    pub fn enumerate_adapters(settings: &GraphicsSettings) -> Vec<WGPUAdapterInfo> {
+        let instance = Self::create_wgpu_instance(settings);
+        instance
+            .enumerate_adapters(settings.backends())
+            .map(|adapter| adapter.get_info())
+            .collect()
+    }
+
+    fn create_wgpu_instance(settings: &GraphicsSettings) -> WGPUInstance {
         info!("Creating WGPU instance");
-        WGPUInstance::new(WGPUBackends::all())
+        WGPUInstance::new(settings.backends())
     }
 
     fn create_render_surface<Window>(instance: &WGPUInstance, window: &Window) -> WGPUSurface
@@ -79,43 +605,118 @@ impl Graphics {
         unsafe { instance.create_surface(&window) }
     }
 
-    fn request_adapter(instance: &WGPUInstance, surface: &WGPUSurface) -> WGPUAdapter {
+    fn request_adapter(
+        instance: &WGPUInstance,
+        surface: &WGPUSurface,
+        settings: &GraphicsSettings,
+    ) -> GraphicsResult<WGPUAdapter> {
+        if let Some(adapter_name) = &settings.adapter_name {
+            info!("Looking for video adapter named \"{adapter_name}\"");
+            let adapter = instance
+                .enumerate_adapters(settings.backends())
+                .find(|adapter| {
+                    adapter.get_info().name.contains(adapter_name.as_str())
+                        && adapter.is_surface_supported(surface)
+                });
+            if let Some(adapter) = adapter {
+                return Ok(adapter);
+            }
+
+            warn!("No compatible video adapter named \"{adapter_name}\", using the default one");
+        }
+
         info!("Requesting video adapter");
         block_on(instance.request_adapter(&WGPURequestAdapterOptions {
-            power_preference: WGPUPowerPreference::default(),
-            force_fallback_adapter: false,
+            power_preference: settings.wgpu_power_preference(),
+            force_fallback_adapter: settings.force_fallback_adapter,
             compatible_surface: Some(surface),
         }))
-        .unwrap()
+        .ok_or(GraphicsError::AdapterNotFound)
     }
 
-    fn request_device(adapter: &WGPUAdapter) -> (WGPUDevice, WGPUQueue) {
+    fn request_device(
+        adapter: &WGPUAdapter,
+    ) -> GraphicsResult<(WGPUDevice, WGPUQueue, GraphicsCapabilities)> {
         info!("Requesting device");
-        block_on(adapter.request_device(
+        let features = adapter.features() & WGPUFeatures::POLYGON_MODE_LINE;
+        if !features.contains(WGPUFeatures::POLYGON_MODE_LINE) {
+            warn!("The adapter doesn't support wireframe rendering, disabling it");
+        }
+
+        let limits = Self::select_limits(&adapter.limits());
+        let capabilities = GraphicsCapabilities {
+            wireframe: features.contains(WGPUFeatures::POLYGON_MODE_LINE),
+            max_texture_dimension: limits.max_texture_dimension_2d,
+            max_uniform_buffer_binding_size: limits.max_uniform_buffer_binding_size,
+        };
+        let (device, queue) = block_on(adapter.request_device(
             &WGPUDeviceDescriptor {
                 label: None,
-                limits: if cfg!(target_arch = "wasm32") {
-                    WGPULimits::downlevel_webgl2_defaults()
-                } else {
-                    WGPULimits::default()
-                },
-                ..Default::default()
+                features,
+                limits,
             },
             None,
         ))
-        .unwrap()
+        .map_err(GraphicsError::DeviceRequestError)?;
+
+        Ok((device, queue, capabilities))
+    }
+
+    /// Returns the most capable default limits the adapter supports, with
+    /// the largest texture dimensions it allows
+    fn select_limits(adapter_limits: &WGPULimits) -> WGPULimits {
+        let candidates = if cfg!(target_arch = "wasm32") {
+            vec![WGPULimits::downlevel_webgl2_defaults()]
+        } else {
+            vec![
+                WGPULimits::default(),
+                WGPULimits::downlevel_defaults(),
+                WGPULimits::downlevel_webgl2_defaults(),
+            ]
+        };
+
+        let limits = candidates
+            .iter()
+            .position(|limits| limits.check_limits(adapter_limits))
+            .map_or_else(
+                || {
+                    warn!("The adapter doesn't support the lowest default limits");
+                    WGPULimits::downlevel_webgl2_defaults()
+                },
+                |index| {
+                    if index > 0 {
+                        warn!("The adapter doesn't support the default limits, degrading them");
+                    }
+                    candidates[index].clone()
+                },
+            );
+
+        limits.using_resolution(adapter_limits.clone())
+    }
+
+    /// Picks an sRGB format if gamma correction is enabled and a linear one
+    /// otherwise, falling back to the preferred format of the surface
+    fn select_surface_format(
+        supported_formats: &[WGPUTextureFormat],
+        gamma_correction: bool,
+    ) -> WGPUTextureFormat {
+        supported_formats
+            .iter()
+            .copied()
+            .find(|format| format.describe().srgb == gamma_correction)
+            .unwrap_or(supported_formats[0])
     }
 
     fn configure_surface(
         window_size: &WindowSize,
         surface: &WGPUSurface,
-        adapter: &WGPUAdapter,
+        surface_format: WGPUTextureFormat,
         device: &WGPUDevice,
     ) {
-        info!("Configuring render surface");
+        info!("Configuring render surface with format {surface_format:?}");
         let surface_configuration = WGPUSurfaceConfiguration {
             usage: WGPUTextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_supported_formats(adapter)[0],
+            format: surface_format,
             width: window_size.width,
             height: window_size.height,
             present_mode: WGPUPresentMode::Fifo,
@@ -132,15 +733,39 @@ impl Graphics {
 }
 
 impl GraphicsAPI for Graphics {
-    fn render_scene(&mut self, _ecs: &Ecs) -> GraphicsResult<()> {
+    fn render_scene(&mut self, ecs: &Ecs, store: &mut Store) -> GraphicsResult<()> {
         trace!("Starting scene render");
-        let output = self
-            .surface
-            .get_current_texture()
-            .map_err(GraphicsError::SurfaceError)?;
-        let _view = output
-            .texture
-            .create_view(&WGPUTextureViewDescriptor::default());
+        self.prepare_scene_textures(ecs, store);
+        let (output, _view) = match &self.render_target {
+            RenderTarget::Surface(surface) => {
+                let output = match surface.get_current_texture() {
+                    Ok(output) => output,
+                    Err(WGPUSurfaceError::Lost | WGPUSurfaceError::Outdated) => {
+                        warn!("Render surface lost or outdated, reconfiguring it");
+                        Self::configure_surface(
+                            &self.window_size,
+                            surface,
+                            self.surface_format,
+                            &self.device,
+                        );
+                        return Ok(());
+                    }
+                    Err(WGPUSurfaceError::Timeout) => {
+                        warn!("Timed out acquiring the next frame, skipping it");
+                        return Ok(());
+                    }
+                    Err(e) => return Err(GraphicsError::SurfaceError(e)),
+                };
+                let view = output
+                    .texture
+                    .create_view(&WGPUTextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            RenderTarget::Offscreen(texture) => (
+                None,
+                texture.create_view(&WGPUTextureViewDescriptor::default()),
+            ),
+        };
         let command_encoder = self
             .device
             .create_command_encoder(&WGPUCommandEncoderDescriptor {
@@ -148,9 +773,226 @@ impl GraphicsAPI for Graphics {
             });
 
         self.queue.submit(std::iter::once(command_encoder.finish()));
-        output.present();
+        if let Some(output) = output {
+            output.present();
+        }
         trace!("Render finished");
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use tuber_core::asset::Metadata;
+
+    use super::*;
+    use crate::texture::TextureRegion;
+
+    #[test]
+    fn select_surface_format() {
+        let supported_formats = [
+            WGPUTextureFormat::Bgra8Unorm,
+            WGPUTextureFormat::Bgra8UnormSrgb,
+        ];
+
+        assert_eq!(
+            Graphics::select_surface_format(&supported_formats, true),
+            WGPUTextureFormat::Bgra8UnormSrgb
+        );
+        assert_eq!(
+            Graphics::select_surface_format(&supported_formats, false),
+            WGPUTextureFormat::Bgra8Unorm
+        );
+        assert_eq!(
+            Graphics::select_surface_format(&supported_formats[..1], true),
+            WGPUTextureFormat::Bgra8Unorm
+        );
+    }
+
+    #[test]
+    fn select_limits() {
+        let adapter_limits = WGPULimits {
+            max_texture_dimension_2d: 4096,
+            ..WGPULimits::downlevel_defaults()
+        };
+
+        let limits = Graphics::select_limits(&adapter_limits);
+
+        assert_eq!(limits.max_texture_dimension_2d, 4096);
+        assert_eq!(
+            limits.max_uniform_buffer_binding_size,
+            WGPULimits::downlevel_defaults().max_uniform_buffer_binding_size
+        );
+        assert!(limits.check_limits(&adapter_limits));
+        assert!(
+            Graphics::select_limits(&WGPULimits::default()).check_limits(&WGPULimits::default())
+        );
+    }
+
+    #[test]
+    fn backends() {
+        let mut settings = GraphicsSettings::default();
+        assert_eq!(settings.backends(), WGPUBackends::all());
+
+        settings.backend = Some(Backend::Vulkan);
+        assert_eq!(settings.backends(), WGPUBackends::VULKAN);
+    }
+
+    #[test]
+    fn logical_window_size() {
+        let window_size = WindowSize {
+            width: 1600,
+            height: 1200,
+        };
+
+        assert_eq!(window_size.to_logical(2.0), (800.0, 600.0));
+        assert_eq!(window_size.to_logical(1.0), (1600.0, 1200.0));
+    }
+
+    #[test]
+    #[ignore = "requires a fallback adapter, such as a software Vulkan or GL driver"]
+    fn headless_render() {
+        let mut graphics = Graphics::new_headless(
+            WindowSize {
+                width: 70,
+                height: 10,
+            },
+            GraphicsSettings::default(),
+        )
+        .unwrap();
+
+        graphics
+            .render_scene(&Ecs::default(), &mut Store::default())
+            .unwrap();
+        let pixels = graphics.read_pixels().unwrap();
+
+        assert_eq!((pixels.width, pixels.height), (70, 10));
+        assert_eq!(pixels.data.len(), 70 * 10 * TextureData::BYTES_PER_PIXEL);
+    }
+
+    /// Creates headless graphics with a VRAM budget of two 16x16 textures
+    fn graphics_with_vram_budget() -> Graphics {
+        let settings = GraphicsSettings {
+            vram_budget: Some(2 * 16 * 16 * TextureData::BYTES_PER_PIXEL as u64),
+            ..GraphicsSettings::default()
+        };
+        Graphics::new_headless(
+            WindowSize {
+                width: 8,
+                height: 8,
+            },
+            settings,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    #[ignore = "requires a fallback adapter, such as a software Vulkan or GL driver"]
+    fn vram_budget() {
+        let mut graphics = graphics_with_vram_budget();
+
+        graphics
+            .load_texture_in_vram("grass".into(), &TextureData::new(16, 16))
+            .unwrap();
+        graphics
+            .load_texture_in_vram("rock".into(), &TextureData::new(16, 16))
+            .unwrap();
+        assert!(graphics.texture_in_vram(&"grass".into()).is_some());
+        graphics
+            .load_texture_in_vram("water".into(), &TextureData::new(16, 16))
+            .unwrap();
+
+        assert!(graphics.texture_in_vram(&"rock".into()).is_none());
+        assert!(graphics.unload_texture_from_vram(&"grass".into()));
+        assert!(!graphics.unload_texture_from_vram(&"grass".into()));
+        assert_eq!(graphics.vram_usage(), 16 * 16 * 4);
+    }
+
+    #[test]
+    #[ignore = "requires a fallback adapter, such as a software Vulkan or GL driver"]
+    fn load_invalid_texture_in_vram() {
+        let mut graphics = graphics_with_vram_budget();
+        let mut truncated_texture = TextureData::new(16, 16);
+        truncated_texture.data.truncate(16);
+        let max_dimension = graphics.capabilities().max_texture_dimension;
+
+        for texture_data in [
+            TextureData::new(0, 16),
+            truncated_texture,
+            TextureData::new(max_dimension + 1, 1),
+        ] {
+            assert!(matches!(
+                graphics.load_texture_in_vram("grass".into(), &texture_data),
+                Err(GraphicsError::InvalidTextureData(_))
+            ));
+        }
+        assert_eq!(graphics.vram_usage(), 0);
+    }
+
+    fn store_with_textures(textures: &[&str]) -> Store {
+        let mut store = Store::default();
+        for &texture in textures {
+            let metadata = Metadata {
+                identifier: texture.into(),
+                kind: "texture".into(),
+                metadata: HashMap::new(),
+                dependencies: vec![],
+                asset_path: PathBuf::new(),
+            };
+            store
+                .insert_asset(metadata, TextureData::new(16, 16))
+                .unwrap();
+        }
+        store
+    }
+
+    fn scene(textures: &[&str]) -> Ecs {
+        let region = TextureRegion::new(0.0, 0.0, 16.0, 16.0);
+        let mut ecs = Ecs::default();
+        for texture in textures {
+            ecs.insert((Sprite::new(texture, region),));
+        }
+        ecs
+    }
+
+    #[test]
+    #[ignore = "requires a fallback adapter, such as a software Vulkan or GL driver"]
+    fn render_past_vram_budget() {
+        let mut graphics = graphics_with_vram_budget();
+        let mut store = store_with_textures(&["grass", "rock", "water"]);
+
+        graphics
+            .render_scene(&scene(&["grass", "rock"]), &mut store)
+            .unwrap();
+        assert_eq!(graphics.vram_usage(), 2 * 16 * 16 * 4);
+        graphics
+            .render_scene(&scene(&["grass", "water"]), &mut store)
+            .unwrap();
+
+        assert!(graphics.textures.contains(&"grass".into()));
+        assert!(graphics.textures.contains(&"water".into()));
+        assert!(!graphics.textures.contains(&"rock".into()));
+        assert_eq!(graphics.vram_usage(), 2 * 16 * 16 * 4);
+    }
+    #[test]
+    #[ignore = "requires a fallback adapter, such as a software Vulkan or GL driver"]
+    fn render_frame_exceeding_vram_budget() {
+        let mut graphics = graphics_with_vram_budget();
+        let mut store = store_with_textures(&["grass", "rock", "water"]);
+
+        graphics
+            .render_scene(&scene(&["grass", "rock", "water"]), &mut store)
+            .unwrap();
+        assert_eq!(graphics.vram_usage(), 3 * 16 * 16 * 4);
+        graphics
+            .render_scene(&scene(&["water"]), &mut store)
+            .unwrap();
+
+        assert!(graphics.textures.contains(&"water".into()));
+        assert_eq!(graphics.vram_usage(), 2 * 16 * 16 * 4);
+    }
+}
@@ -0,0 +1,363 @@
+//! Meshes and generators for primitive shapes, centered on the origin.
+
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+/// Triangles defined by indexed vertices, front faces being counter-clockwise
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+/// A horizontal ring of vertices of a surface of revolution
+struct Ring {
+    radius: f32,
+    y: f32,
+    /// The normal of the ring's vertices at angle 0
+    normal: [f32; 3],
+    v: f32,
+}
+
+impl Mesh {
+    #[must_use]
+    pub fn cube(size: f32) -> Self {
+        let half_size = size / 2.0;
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+        let corners = [
+            (-1.0, -1.0, [0.0, 1.0]),
+            (1.0, -1.0, [1.0, 1.0]),
+            (1.0, 1.0, [1.0, 0.0]),
+            (-1.0, 1.0, [0.0, 0.0]),
+        ];
+
+        let mut mesh = Mesh::default();
+        for (normal, u_axis, v_axis) in faces {
+            let first_index = mesh.next_index();
+            for (u, v, uv) in corners {
+                let position = std::array::from_fn(|axis| {
+                    (normal[axis] + u * u_axis[axis] + v * v_axis[axis]) * half_size
+                });
+                mesh.vertices.push(Vertex {
+                    position,
+                    normal,
+                    uv,
+                });
+            }
+            mesh.push_quad(
+                first_index + 3,
+                first_index + 2,
+                first_index,
+                first_index + 1,
+            );
+        }
+
+        mesh
+    }
+
+    /// Creates a square in the XZ plane facing up, split in subdivisions²
+    /// squares
+    #[must_use]
+    pub fn plane(size: f32, subdivisions: u32) -> Self {
+        let subdivisions = subdivisions.max(1);
+        let half_size = size / 2.0;
+        let mut mesh = Mesh::default();
+        for row in 0..=subdivisions {
+            for column in 0..=subdivisions {
+                let (u, v) = (fraction(column, subdivisions), fraction(row, subdivisions));
+                mesh.vertices.push(Vertex {
+                    position: [u * size - half_size, 0.0, v * size - half_size],
+                    normal: [0.0, 1.0, 0.0],
+                    uv: [u, v],
+                });
+            }
+        }
+
+        let row_length = subdivisions + 1;
+        for row in 0..subdivisions {
+            for column in 0..subdivisions {
+                let index = row * row_length + column;
+                mesh.push_quad(index, index + 1, index + row_length, index + row_length + 1);
+            }
+        }
+
+        mesh
+    }
+
+    /// Creates a UV sphere made of `sectors` vertical slices and `stacks`
+    /// horizontal ones
+    #[must_use]
+    pub fn sphere(radius: f32, sectors: u32, stacks: u32) -> Self {
+        let stacks = stacks.max(2);
+        let rings: Vec<Ring> = (0..=stacks)
+            .map(|stack| {
+                let polar_angle = PI * fraction(stack, stacks);
+                let (sin, cos) = polar_angle.sin_cos();
+                Ring {
+                    radius: radius * sin,
+                    y: radius * cos,
+                    normal: [sin, cos, 0.0],
+                    v: fraction(stack, stacks),
+                }
+            })
+            .collect();
+
+        let mut mesh = Mesh::default();
+        mesh.push_revolution(&rings, sectors);
+        mesh
+    }
+
+    /// Creates a closed cylinder whose axis is the Y axis
+    #[must_use]
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let half_height = height / 2.0;
+        let rings = [
+            Ring {
+                radius,
+                y: half_height,
+                normal: [1.0, 0.0, 0.0],
+                v: 0.0,
+            },
+            Ring {
+                radius,
+                y: -half_height,
+                normal: [1.0, 0.0, 0.0],
+                v: 1.0,
+            },
+        ];
+
+        let mut mesh = Mesh::default();
+        mesh.push_revolution(&rings, segments);
+        mesh.push_cap(radius, half_height, segments, true);
+        mesh.push_cap(radius, -half_height, segments, false);
+        mesh
+    }
+
+    /// Creates a capsule whose axis is the Y axis, made of a cylinder of the
+    /// given total height capped by hemispheres of `rings` stacks each
+    #[must_use]
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let rings = rings.max(1);
+        let half_cylinder_height = (height / 2.0 - radius).max(0.0);
+        let total_height = 2.0 * (half_cylinder_height + radius);
+        let hemisphere_rings = |first_ring: u32, y_offset: f32| {
+            (first_ring..=first_ring + rings).map(move |ring| {
+                let polar_angle = FRAC_PI_2 * fraction(ring, rings);
+                let (sin, cos) = polar_angle.sin_cos();
+                let y = radius * cos + y_offset;
+                Ring {
+                    radius: radius * sin,
+                    y,
+                    normal: [sin, cos, 0.0],
+                    v: (total_height / 2.0 - y) / total_height,
+                }
+            })
+        };
+        let capsule_rings: Vec<Ring> = hemisphere_rings(0, half_cylinder_height)
+            .chain(hemisphere_rings(rings, -half_cylinder_height))
+            .collect();
+
+        let mut mesh = Mesh::default();
+        mesh.push_revolution(&capsule_rings, segments);
+        mesh
+    }
+
+    fn next_index(&self) -> u32 {
+        u32::try_from(self.vertices.len()).expect("Too many vertices")
+    }
+
+    /// Pushes the two triangles of a quad, the vertices being given from
+    /// left to right then from top to bottom as seen from the front
+    fn push_quad(&mut self, top_left: u32, top_right: u32, bottom_left: u32, bottom_right: u32) {
+        self.indices.extend_from_slice(&[
+            top_left,
+            bottom_left,
+            top_right,
+            top_right,
+            bottom_left,
+            bottom_right,
+        ]);
+    }
+
+    /// Revolves the rings, given from top to bottom, around the Y axis
+    fn push_revolution(&mut self, rings: &[Ring], segments: u32) {
+        let segments = segments.max(3);
+        let first_index = self.next_index();
+        for ring in rings {
+            for segment in 0..=segments {
+                let angle = TAU * fraction(segment, segments);
+                let (sin, cos) = angle.sin_cos();
+                self.vertices.push(Vertex {
+                    position: [ring.radius * cos, ring.y, -ring.radius * sin],
+                    normal: [ring.normal[0] * cos, ring.normal[1], -ring.normal[0] * sin],
+                    uv: [fraction(segment, segments), ring.v],
+                });
+            }
+        }
+
+        let ring_length = segments + 1;
+        for (ring_index, ring) in (0..).zip(rings.windows(2)) {
+            for segment in 0..segments {
+                let top_left = first_index + ring_index * ring_length + segment;
+                let bottom_left = top_left + ring_length;
+                if ring[0].radius > 0.0 {
+                    self.indices
+                        .extend_from_slice(&[top_left, bottom_left, top_left + 1]);
+                }
+                if ring[1].radius > 0.0 {
+                    self.indices
+                        .extend_from_slice(&[top_left + 1, bottom_left, bottom_left + 1]);
+                }
+            }
+        }
+    }
+
+    /// Pushes a disc closing a surface of revolution
+    fn push_cap(&mut self, radius: f32, y: f32, segments: u32, facing_up: bool) {
+        let segments = segments.max(3);
+        let normal_y = if facing_up { 1.0 } else { -1.0 };
+        let center = self.next_index();
+        self.vertices.push(Vertex {
+            position: [0.0, y, 0.0],
+            normal: [0.0, normal_y, 0.0],
+            uv: [0.5, 0.5],
+        });
+        for segment in 0..=segments {
+            let angle = TAU * fraction(segment, segments);
+            let (sin, cos) = angle.sin_cos();
+            self.vertices.push(Vertex {
+                position: [radius * cos, y, -radius * sin],
+                normal: [0.0, normal_y, 0.0],
+                uv: [0.5 + 0.5 * cos, 0.5 - 0.5 * sin],
+            });
+        }
+
+        for segment in 0..segments {
+            let (current, next) = (center + 1 + segment, center + 2 + segment);
+            if facing_up {
+                self.indices.extend_from_slice(&[center, current, next]);
+            } else {
+                self.indices.extend_from_slice(&[center, next, current]);
+            }
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn fraction(numerator: u32, denominator: u32) -> f32 {
+    numerator as f32 / denominator as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+    }
+
+    fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+        [
+            a[1] * b[2] - a[2] * b[1],
+            a[2] * b[0] - a[0] * b[2],
+            a[0] * b[1] - a[1] * b[0],
+        ]
+    }
+
+    fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+        a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+    }
+
+    /// Checks the indices, normals, UVs and that the triangles face the same
+    /// way as their vertices' normals
+    fn assert_valid(mesh: &Mesh) {
+        assert_eq!(mesh.indices.len() % 3, 0);
+        for vertex in &mesh.vertices {
+            assert!((dot(vertex.normal, vertex.normal) - 1.0).abs() < 0.001);
+            assert!(vertex.uv.iter().all(|c| (0.0..=1.0).contains(c)));
+        }
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices[triangle[i] as usize]);
+            let face_normal = cross(sub(b.position, a.position), sub(c.position, a.position));
+            assert!(dot(face_normal, face_normal) > 0.0);
+            for vertex in [a, b, c] {
+                assert!(dot(face_normal, vertex.normal) > 0.0);
+            }
+        }
+    }
+
+    fn assert_bounds(mesh: &Mesh, min: [f32; 3], max: [f32; 3]) {
+        for axis in 0..3 {
+            let positions = mesh.vertices.iter().map(|vertex| vertex.position[axis]);
+            let mesh_min = positions.clone().fold(f32::MAX, f32::min);
+            let mesh_max = positions.fold(f32::MIN, f32::max);
+            assert!((mesh_min - min[axis]).abs() < 0.001);
+            assert!((mesh_max - max[axis]).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn cube() {
+        let mesh = Mesh::cube(2.0);
+
+        assert_valid(&mesh);
+        assert_eq!(mesh.vertices.len(), 24);
+        assert_eq!(mesh.indices.len(), 36);
+        assert_bounds(&mesh, [-1.0; 3], [1.0; 3]);
+    }
+
+    #[test]
+    fn plane() {
+        let mesh = Mesh::plane(4.0, 2);
+
+        assert_valid(&mesh);
+        assert_eq!(mesh.vertices.len(), 9);
+        assert_eq!(mesh.indices.len(), 24);
+        assert_bounds(&mesh, [-2.0, 0.0, -2.0], [2.0, 0.0, 2.0]);
+    }
+
+    #[test]
+    fn sphere() {
+        let mesh = Mesh::sphere(2.0, 16, 8);
+
+        assert_valid(&mesh);
+        assert_eq!(mesh.vertices.len(), 17 * 9);
+        assert_eq!(mesh.indices.len(), (16 * 8 * 2 - 2 * 16) * 3);
+        assert_bounds(&mesh, [-2.0; 3], [2.0; 3]);
+        for vertex in &mesh.vertices {
+            let distance = dot(vertex.position, vertex.position).sqrt();
+            assert!((distance - 2.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn cylinder() {
+        let mesh = Mesh::cylinder(1.0, 3.0, 12);
+
+        assert_valid(&mesh);
+        assert_eq!(mesh.indices.len(), (12 * 2 + 12 * 2) * 3);
+        assert_bounds(&mesh, [-1.0, -1.5, -1.0], [1.0, 1.5, 1.0]);
+    }
+
+    #[test]
+    fn capsule() {
+        let mesh = Mesh::capsule(1.0, 4.0, 12, 4);
+
+        assert_valid(&mesh);
+        assert_bounds(&mesh, [-1.0, -2.0, -1.0], [1.0, 2.0, 1.0]);
+    }
+}
@@ -0,0 +1,369 @@
+//! Particle emitters simulated on the CPU, so effects made of many short
+//! lived particles don't require spawning an entity per particle.
+
+use std::f32::consts::PI;
+
+use tuber_core::transform::Transform;
+use tuber_core::tween::Tweenable;
+use tuber_core::DeltaTime;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::query::accessors::Opt;
+use tuber_ecs::system::SystemResult;
+use tuber_math::color::Color;
+use tuber_math::vector::Vector3;
+
+/// A value varying over the lifetime of a particle, defined by keyframes
+/// linearly interpolated between
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve<T: Tweenable> {
+    keyframes: Vec<(f32, T)>,
+}
+
+impl<T: Tweenable> Curve<T> {
+    #[must_use]
+    pub fn constant(value: T) -> Self {
+        Self {
+            keyframes: vec![(0.0, value)],
+        }
+    }
+
+    #[must_use]
+    pub fn linear(from: T, to: T) -> Self {
+        Self {
+            keyframes: vec![(0.0, from), (1.0, to)],
+        }
+    }
+
+    /// Adds a keyframe at the given time of the lifetime, in [0, 1]
+    #[must_use]
+    pub fn with_keyframe(mut self, time: f32, value: T) -> Self {
+        let index = self.keyframes.partition_point(|&(t, _)| t <= time);
+        self.keyframes.insert(index, (time, value));
+        self
+    }
+
+    #[must_use]
+    pub fn sample(&self, time: f32) -> T {
+        let index = self.keyframes.partition_point(|&(t, _)| t <= time);
+        if index == 0 {
+            return self.keyframes[0].1;
+        }
+
+        let (previous_time, previous_value) = self.keyframes[index - 1];
+        match self.keyframes.get(index) {
+            Some(&(next_time, next_value)) => previous_value.interpolate(
+                &next_value,
+                (time - previous_time) / (next_time - previous_time),
+            ),
+            None => previous_value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmissionSpace {
+    /// Particles are left behind when the emitter moves
+    #[default]
+    World,
+    /// Particles move along with the emitter
+    Local,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    /// The position in world space, or relative to the emitter in local space
+    pub position: Vector3<f32>,
+    pub velocity: Vector3<f32>,
+    pub age: f32,
+    pub size: f32,
+    pub color: [f32; 4],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleEmitter {
+    pub texture: String,
+    /// The number of particles spawned per second
+    pub spawn_rate: f32,
+    pub max_particles: usize,
+    /// The lifetime of a particle, in seconds
+    pub lifetime: f32,
+    pub initial_velocity: Vector3<f32>,
+    /// The maximum angle in radians between the initial velocity of a particle
+    /// and `initial_velocity`, in the XY plane
+    pub velocity_spread: f32,
+    /// A factor applied to the velocity of the particles
+    pub velocity_over_lifetime: Curve<f32>,
+    pub size_over_lifetime: Curve<f32>,
+    pub color_over_lifetime: Curve<[f32; 4]>,
+    pub space: EmissionSpace,
+    pub emitting: bool,
+    particles: Vec<Particle>,
+    spawn_accumulator: f32,
+    random_state: u32,
+}
+
+impl ParticleEmitter {
+    #[must_use]
+    pub fn new(texture: &str, spawn_rate: f32, lifetime: f32) -> Self {
+        Self {
+            texture: texture.into(),
+            spawn_rate,
+            max_particles: 1000,
+            lifetime,
+            initial_velocity: Vector3::new(0.0, 0.0, 0.0),
+            velocity_spread: 0.0,
+            velocity_over_lifetime: Curve::constant(1.0),
+            size_over_lifetime: Curve::constant(1.0),
+            color_over_lifetime: Curve::constant(Color::WHITE.into()),
+            space: EmissionSpace::default(),
+            emitting: true,
+            particles: vec![],
+            spawn_accumulator: 0.0,
+            random_state: 0x9E37_79B9,
+        }
+    }
+
+    /// Sets the seed of the random number generator used for the spread of
+    /// the particles
+    #[must_use]
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.random_state = seed.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Returns the world position of a particle of the emitter
+    pub fn world_position(
+        &self,
+        particle: &Particle,
+        emitter_position: Vector3<f32>,
+    ) -> Vector3<f32> {
+        match self.space {
+            EmissionSpace::World => particle.position,
+            EmissionSpace::Local => emitter_position + particle.position,
+        }
+    }
+
+    /// Spawns particles at once, regardless of the spawn rate
+    pub fn burst(&mut self, count: usize, emitter_position: Vector3<f32>) {
+        for _ in 0..count {
+            self.spawn(emitter_position);
+        }
+    }
+
+    /// Ages, moves and spawns the particles
+    pub fn update(&mut self, delta_time: f32, emitter_position: Vector3<f32>) {
+        let lifetime = self.lifetime;
+        self.particles
+            .retain(|particle| particle.age + delta_time < lifetime);
+
+        for particle in &mut self.particles {
+            particle.age += delta_time;
+            let time = particle.age / lifetime;
+            particle.position +=
+                particle.velocity * (self.velocity_over_lifetime.sample(time) * delta_time);
+            particle.size = self.size_over_lifetime.sample(time);
+            particle.color = self.color_over_lifetime.sample(time);
+        }
+
+        if !self.emitting {
+            self.spawn_accumulator = 0.0;
+            return;
+        }
+
+        self.spawn_accumulator += self.spawn_rate * delta_time;
+        while self.spawn_accumulator >= 1.0 {
+            self.spawn_accumulator -= 1.0;
+            self.spawn(emitter_position);
+        }
+    }
+
+    fn spawn(&mut self, emitter_position: Vector3<f32>) {
+        if self.particles.len() >= self.max_particles {
+            return;
+        }
+
+        let angle = (self.next_random() * 2.0 - 1.0) * self.velocity_spread.min(PI);
+        let (sin, cos) = angle.sin_cos();
+        let velocity = Vector3::new(
+            self.initial_velocity.x * cos - self.initial_velocity.y * sin,
+            self.initial_velocity.x * sin + self.initial_velocity.y * cos,
+            self.initial_velocity.z,
+        );
+        let position = match self.space {
+            EmissionSpace::World => emitter_position,
+            EmissionSpace::Local => Vector3::new(0.0, 0.0, 0.0),
+        };
+
+        self.particles.push(Particle {
+            position,
+            velocity,
+            age: 0.0,
+            size: self.size_over_lifetime.sample(0.0),
+            color: self.color_over_lifetime.sample(0.0),
+        });
+    }
+
+    /// Returns a pseudo-random number in [0, 1] using xorshift
+    fn next_random(&mut self) -> f32 {
+        let mut x = self.random_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.random_state = x;
+        #[allow(clippy::cast_precision_loss)]
+        let random = x as f32 / u32::MAX as f32;
+        random
+    }
+}
+
+/// Simulates the particle emitters, emitting from their entity's translation
+pub fn update_particle_emitters(ecs: &mut Ecs) -> SystemResult {
+    let delta_time = match ecs.shared_resource::<DeltaTime>() {
+        Some(delta_time) => delta_time.0,
+        None => return Ok(()),
+    };
+
+    for (_, (mut emitter, transform)) in ecs.query::<(&mut ParticleEmitter, Opt<&Transform>)>() {
+        let emitter_position = transform.map_or_else(
+            || Vector3::new(0.0, 0.0, 0.0),
+            |transform| transform.translation,
+        );
+        #[allow(clippy::cast_possible_truncation)]
+        emitter.update(delta_time as f32, emitter_position);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    #[test]
+    fn curve_sample() {
+        let curve = Curve::linear(0.0, 1.0).with_keyframe(0.5, 4.0);
+
+        assert!(approx_eq(curve.sample(-1.0), 0.0));
+        assert!(approx_eq(curve.sample(0.25), 2.0));
+        assert!(approx_eq(curve.sample(0.5), 4.0));
+        assert!(approx_eq(curve.sample(0.75), 2.5));
+        assert!(approx_eq(curve.sample(2.0), 1.0));
+        assert!(approx_eq(Curve::constant(3.0).sample(0.5), 3.0));
+    }
+
+    #[test]
+    fn spawn_rate() {
+        let mut emitter = ParticleEmitter::new("spark", 10.0, 1.0);
+
+        emitter.update(0.25, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(emitter.particles().len(), 2);
+        emitter.update(0.25, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(emitter.particles().len(), 5);
+
+        emitter.emitting = false;
+        emitter.update(0.8, Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(emitter.particles().len(), 3);
+    }
+
+    #[test]
+    fn max_particles() {
+        let mut emitter = ParticleEmitter::new("spark", 0.0, 1.0);
+        emitter.max_particles = 5;
+
+        emitter.burst(10, Vector3::new(0.0, 0.0, 0.0));
+
+        assert_eq!(emitter.particles().len(), 5);
+    }
+
+    #[test]
+    fn over_lifetime_curves() {
+        let mut emitter = ParticleEmitter::new("spark", 0.0, 2.0);
+        emitter.initial_velocity = Vector3::new(10.0, 0.0, 0.0);
+        emitter.velocity_over_lifetime = Curve::constant(0.5);
+        emitter.size_over_lifetime = Curve::linear(1.0, 0.0);
+        emitter.color_over_lifetime = Curve::linear([1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]);
+        emitter.burst(1, Vector3::new(2.0, 0.0, 0.0));
+
+        emitter.update(1.0, Vector3::new(0.0, 0.0, 0.0));
+
+        let particle = emitter.particles()[0];
+        assert!(approx_eq(particle.position.x, 7.0));
+        assert!(approx_eq(particle.size, 0.5));
+        assert!(approx_eq(particle.color[3], 0.5));
+    }
+
+    #[test]
+    fn velocity_spread() {
+        let mut emitter = ParticleEmitter::new("spark", 0.0, 1.0).with_seed(42);
+        emitter.initial_velocity = Vector3::new(0.0, 1.0, 0.0);
+        emitter.velocity_spread = PI / 4.0;
+
+        emitter.burst(50, Vector3::new(0.0, 0.0, 0.0));
+
+        for particle in emitter.particles() {
+            assert!(approx_eq(particle.velocity.norm(), 1.0));
+            assert!(particle.velocity.y >= (PI / 4.0).cos() - 0.001);
+        }
+    }
+
+    #[test]
+    fn emission_space() {
+        let mut emitter = ParticleEmitter::new("spark", 0.0, 1.0);
+        emitter.burst(1, Vector3::new(3.0, 0.0, 0.0));
+        emitter.space = EmissionSpace::Local;
+        emitter.burst(1, Vector3::new(3.0, 0.0, 0.0));
+
+        let emitter_position = Vector3::new(5.0, 0.0, 0.0);
+        let world_particle = emitter.particles()[0];
+        let local_particle = emitter.particles()[1];
+        emitter.space = EmissionSpace::World;
+        assert!(approx_eq(
+            emitter.world_position(&world_particle, emitter_position).x,
+            3.0
+        ));
+        emitter.space = EmissionSpace::Local;
+        assert!(approx_eq(
+            emitter.world_position(&local_particle, emitter_position).x,
+            5.0
+        ));
+    }
+
+    #[test]
+    fn update_particle_emitters_system() {
+        let mut ecs = Ecs::default();
+        let transform = Transform {
+            translation: Vector3::new(4.0, 2.0, 0.0),
+            ..Default::default()
+        };
+        ecs.insert((ParticleEmitter::new("spark", 10.0, 1.0), transform));
+        ecs.insert_shared_resource(DeltaTime(0.5));
+
+        update_particle_emitters(&mut ecs).unwrap();
+
+        let (_, (emitter,)) = ecs.query_one::<(&ParticleEmitter,)>().unwrap();
+        assert_eq!(emitter.particles().len(), 5);
+        assert!(approx_eq(emitter.particles()[0].position.x, 4.0));
+    }
+
+    #[test]
+    fn default_system_bundle_updates_emitters() {
+        let mut system_bundle = crate::default_system_bundle();
+        let mut ecs = Ecs::default();
+        ecs.insert((ParticleEmitter::new("spark", 10.0, 1.0),));
+        ecs.insert_shared_resource(DeltaTime(0.5));
+
+        system_bundle.step(&mut ecs, &mut ()).unwrap();
+
+        let (_, (emitter,)) = ecs.query_one::<(&ParticleEmitter,)>().unwrap();
+        assert_eq!(emitter.particles().len(), 5);
+    }
+}
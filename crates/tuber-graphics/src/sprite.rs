@@ -0,0 +1,114 @@
+//! Static sprites, displaying a region of a texture.
+
+use tuber_core::asset::Store;
+
+use crate::sprite_atlas::SpriteAtlas;
+use crate::texture::TextureRegion;
+use crate::{GraphicsError, GraphicsResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpriteSource {
+    /// A region of a texture, in pixels
+    Texture {
+        texture: String,
+        region: TextureRegion,
+    },
+    /// A frame of a sprite atlas asset, looked up by name
+    AtlasFrame { atlas: String, frame: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprite {
+    pub source: SpriteSource,
+}
+
+impl Sprite {
+    #[must_use]
+    pub fn new(texture: &str, region: TextureRegion) -> Self {
+        Self {
+            source: SpriteSource::Texture {
+                texture: texture.into(),
+                region,
+            },
+        }
+    }
+
+    /// Creates a sprite displaying a frame of a sprite atlas asset, resolved
+    /// when the sprite is rendered
+    #[must_use]
+    pub fn from_atlas(atlas: &str, frame: &str) -> Self {
+        Self {
+            source: SpriteSource::AtlasFrame {
+                atlas: atlas.into(),
+                frame: frame.into(),
+            },
+        }
+    }
+
+    /// Returns the texture the sprite displays and the region of it,
+    /// loading the atlas of the sprite if needed
+    pub fn resolve(&self, store: &mut Store) -> GraphicsResult<(String, TextureRegion)> {
+        match &self.source {
+            SpriteSource::Texture { texture, region } => Ok((texture.clone(), *region)),
+            SpriteSource::AtlasFrame { atlas, frame } => {
+                let sprite_atlas = store
+                    .asset::<SpriteAtlas>(atlas)
+                    .map_err(|e| GraphicsError::AssetLoadError(format!("{atlas}: {e:?}")))?;
+                let region = sprite_atlas.frame(frame).ok_or_else(|| {
+                    GraphicsError::SpriteAtlasFrameNotFound(format!("{atlas}: {frame}"))
+                })?;
+                Ok((sprite_atlas.texture().to_string(), region))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite_atlas::tests::{metadata, HASH_ATLAS};
+
+    fn store() -> Store {
+        let mut store = Store::default();
+        store
+            .insert_asset(
+                metadata("hero"),
+                SpriteAtlas::from_json(HASH_ATLAS.as_bytes()).unwrap(),
+            )
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn resolve_atlas_frame() {
+        let mut store = store();
+
+        let (texture, region) = Sprite::from_atlas("hero", "hero_idle.png")
+            .resolve(&mut store)
+            .unwrap();
+
+        assert_eq!(texture, "hero.png");
+        assert_eq!(region, TextureRegion::new(0.0, 0.0, 16.0, 24.0));
+    }
+
+    #[test]
+    fn resolve_missing_frame() {
+        let mut store = store();
+
+        assert!(matches!(
+            Sprite::from_atlas("hero", "hero_run.png").resolve(&mut store),
+            Err(GraphicsError::SpriteAtlasFrameNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn resolve_texture_region() {
+        let region = TextureRegion::new(4.0, 4.0, 8.0, 8.0);
+
+        let resolved = Sprite::new("tiles.png", region)
+            .resolve(&mut Store::default())
+            .unwrap();
+
+        assert_eq!(resolved, ("tiles.png".to_string(), region));
+    }
+}
@@ -0,0 +1,359 @@
+//! Sprite atlases: named frames of a sprite sheet, described by the JSON
+//! files `TexturePacker` and Aseprite export.
+//!
+//! Both the hash format, mapping frame names to frames, and the array format,
+//! listing frames with a `filename` field, are supported. Frames keep the
+//! order of the file, along with the durations and the frame tags Aseprite
+//! exports.
+
+use std::collections::HashMap;
+use std::fmt::Formatter;
+
+use serde::de::{MapAccess, Visitor};
+use serde::Deserializer;
+use serde_derive::Deserialize;
+use tuber_core::asset::{metadata_entry, read_asset_file, Metadata};
+use tuber_core::vfs::Vfs;
+use tuber_core::{CoreError, CoreResult};
+
+use crate::texture::TextureRegion;
+use crate::{GraphicsError, GraphicsResult};
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SpriteAtlas {
+    texture: String,
+    frames: Vec<SpriteAtlasFrame>,
+    frame_indices: HashMap<String, usize>,
+    tags: Vec<FrameTag>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAtlasFrame {
+    pub name: String,
+    pub region: TextureRegion,
+    /// The time the frame is displayed, in seconds, if the atlas specifies it
+    pub duration: Option<f64>,
+}
+
+/// A named range of frames of an Aseprite export, usually an animation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FrameTag {
+    pub name: String,
+    /// The index of the first frame of the tag
+    pub from: usize,
+    /// The index of the last frame of the tag, included
+    pub to: usize,
+    #[serde(default)]
+    pub direction: TagDirection,
+}
+
+/// The order the frames of a tag are played in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagDirection {
+    #[default]
+    Forward,
+    Reverse,
+    /// Forward then backward, without repeating the first and last frames
+    Pingpong,
+    /// Backward then forward, without repeating the first and last frames
+    PingpongReverse,
+}
+
+impl FrameTag {
+    /// Returns the indices of the frames of the tag, in the order they are
+    /// played
+    #[must_use]
+    pub fn frame_indices(&self) -> Vec<usize> {
+        let forward: Vec<usize> = (self.from..=self.to).collect();
+        let backward: Vec<usize> = forward.iter().rev().copied().collect();
+        let inner_len = forward.len().saturating_sub(2);
+        match self.direction {
+            TagDirection::Forward => forward,
+            TagDirection::Reverse => backward,
+            TagDirection::Pingpong => forward
+                .iter()
+                .chain(backward.iter().skip(1).take(inner_len))
+                .copied()
+                .collect(),
+            TagDirection::PingpongReverse => backward
+                .iter()
+                .chain(forward.iter().skip(1).take(inner_len))
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AtlasFile {
+    frames: AtlasFrames,
+    meta: AtlasMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AtlasFrames {
+    Hash(OrderedFrames),
+    Array(Vec<NamedAtlasFrame>),
+}
+
+/// The frames of the hash format, in the order of the file, as Aseprite tags
+/// refer to frames by index
+struct OrderedFrames(Vec<(String, AtlasFrame)>);
+
+impl<'de> serde::Deserialize<'de> for OrderedFrames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct OrderedFramesVisitor;
+
+        impl<'de> Visitor<'de> for OrderedFramesVisitor {
+            type Value = OrderedFrames;
+
+            fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of frames")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut frames = Vec::with_capacity(map.size_hint().unwrap_or_default());
+                while let Some(entry) = map.next_entry()? {
+                    frames.push(entry);
+                }
+                Ok(OrderedFrames(frames))
+            }
+        }
+
+        deserializer.deserialize_map(OrderedFramesVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct NamedAtlasFrame {
+    filename: String,
+    #[serde(flatten)]
+    frame: AtlasFrame,
+}
+
+#[derive(Deserialize)]
+struct AtlasFrame {
+    frame: PixelRect,
+    #[serde(default)]
+    rotated: bool,
+    /// In milliseconds
+    duration: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct PixelRect {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+#[derive(Deserialize)]
+struct AtlasMeta {
+    image: String,
+    #[serde(default, rename = "frameTags")]
+    frame_tags: Vec<FrameTag>,
+}
+
+impl SpriteAtlas {
+    pub fn from_json(json: &[u8]) -> GraphicsResult<Self> {
+        let atlas_file: AtlasFile = serde_json::from_slice(json)
+            .map_err(|e| GraphicsError::SpriteAtlasParseError(e.to_string()))?;
+
+        let frames: Vec<(String, AtlasFrame)> = match atlas_file.frames {
+            AtlasFrames::Hash(OrderedFrames(frames)) => frames,
+            AtlasFrames::Array(frames) => frames
+                .into_iter()
+                .map(|frame| (frame.filename, frame.frame))
+                .collect(),
+        };
+
+        let mut atlas_frames = Vec::with_capacity(frames.len());
+        let mut frame_indices = HashMap::with_capacity(frames.len());
+        for (name, frame) in frames {
+            // Rotated frames are packed turned by 90 degrees, which regions
+            // can't express
+            if frame.rotated {
+                return Err(GraphicsError::SpriteAtlasParseError(format!(
+                    "frame {name} is rotated, rotated frames are not supported"
+                )));
+            }
+
+            let PixelRect { x, y, w, h } = frame.frame;
+            #[allow(clippy::cast_precision_loss)]
+            let region = TextureRegion::new(x as f32, y as f32, w as f32, h as f32);
+            frame_indices.insert(name.clone(), atlas_frames.len());
+            atlas_frames.push(SpriteAtlasFrame {
+                name,
+                region,
+                duration: frame.duration.map(|duration| f64::from(duration) / 1000.0),
+            });
+        }
+
+        let tags = atlas_file.meta.frame_tags;
+        if let Some(tag) = tags
+            .iter()
+            .find(|tag| tag.from > tag.to || tag.to >= atlas_frames.len())
+        {
+            return Err(GraphicsError::SpriteAtlasParseError(format!(
+                "frame tag {} covers frames {} to {} that don't exist",
+                tag.name, tag.from, tag.to
+            )));
+        }
+
+        Ok(Self {
+            texture: atlas_file.meta.image,
+            frames: atlas_frames,
+            frame_indices,
+            tags,
+        })
+    }
+
+    /// Returns the texture the frames are regions of: the image file named by
+    /// the description file, or the texture asset set by `with_texture`
+    #[must_use]
+    pub fn texture(&self) -> &str {
+        &self.texture
+    }
+
+    /// Sets the identifier of the texture asset the frames are regions of
+    #[must_use]
+    pub fn with_texture(mut self, texture: &str) -> Self {
+        self.texture = texture.into();
+        self
+    }
+
+    #[must_use]
+    pub fn frame(&self, name: &str) -> Option<TextureRegion> {
+        self.frame_indices
+            .get(name)
+            .map(|&index| self.frames[index].region)
+    }
+
+    pub fn frame_names(&self) -> impl Iterator<Item = &str> {
+        self.frames.iter().map(|frame| frame.name.as_str())
+    }
+
+    /// Returns the frames in the order of the atlas file
+    #[must_use]
+    pub fn frames(&self) -> &[SpriteAtlasFrame] {
+        &self.frames
+    }
+
+    #[must_use]
+    pub fn tags(&self) -> &[FrameTag] {
+        &self.tags
+    }
+
+    #[must_use]
+    pub fn tag(&self, name: &str) -> Option<&FrameTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+}
+
+/// Loads the atlas file named by the "file" metadata of the asset, the frames
+/// being regions of the texture asset named by its "texture" metadata
+pub fn sprite_atlas_loader(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Box<SpriteAtlas>> {
+    Ok(Box::new(load_sprite_atlas(metadata, vfs)?))
+}
+
+pub(crate) fn load_sprite_atlas(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<SpriteAtlas> {
+    let texture = metadata_entry(metadata, "texture")?;
+    let atlas = SpriteAtlas::from_json(&read_asset_file(metadata, vfs)?)
+        .map_err(|e| CoreError::AssetFileParseError(format!("{e:?}")))?;
+    Ok(atlas.with_texture(texture))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use tuber_core::vfs::InMemoryVfs;
+
+    use super::*;
+
+    pub(crate) const HASH_ATLAS: &str = r#"{
+        "frames": {
+            "hero_idle.png": {
+                "frame": { "x": 0, "y": 0, "w": 16, "h": 24 },
+                "rotated": false,
+                "trimmed": false
+            },
+            "hero_jump.png": {
+                "frame": { "x": 16, "y": 0, "w": 16, "h": 32 }
+            }
+        },
+        "meta": { "image": "hero.png", "size": { "w": 32, "h": 32 } }
+    }"#;
+
+    pub(crate) fn metadata(identifier: &str) -> Metadata {
+        let mut metadata = HashMap::new();
+        metadata.insert("file".to_string(), "hero.json".to_string());
+        metadata.insert("texture".to_string(), "hero_texture".to_string());
+        Metadata {
+            identifier: identifier.into(),
+            kind: "sprite_atlas".into(),
+            metadata,
+            dependencies: vec![],
+            asset_path: PathBuf::from("assets/hero"),
+        }
+    }
+
+    #[test]
+    fn parse_hash_format() {
+        let atlas = SpriteAtlas::from_json(HASH_ATLAS.as_bytes()).unwrap();
+
+        assert_eq!(atlas.texture(), "hero.png");
+        assert_eq!(
+            atlas.frame("hero_jump.png"),
+            Some(TextureRegion::new(16.0, 0.0, 16.0, 32.0))
+        );
+        assert_eq!(atlas.frame_names().count(), 2);
+        assert_eq!(atlas.frame("hero_run.png"), None);
+    }
+
+    #[test]
+    fn parse_array_format() {
+        let json = r#"{
+            "frames": [
+                { "filename": "coin 0", "frame": { "x": 0, "y": 8, "w": 8, "h": 8 } },
+                { "filename": "coin 1", "frame": { "x": 8, "y": 8, "w": 8, "h": 8 } }
+            ],
+            "meta": { "image": "coin.png" }
+        }"#;
+
+        let atlas = SpriteAtlas::from_json(json.as_bytes()).unwrap();
+
+        assert_eq!(
+            atlas.frame("coin 1"),
+            Some(TextureRegion::new(8.0, 8.0, 8.0, 8.0))
+        );
+    }
+
+    #[test]
+    fn rotated_frames_are_rejected() {
+        let json = r#"{
+            "frames": { "a": { "frame": { "x": 0, "y": 0, "w": 8, "h": 4 }, "rotated": true } },
+            "meta": { "image": "a.png" }
+        }"#;
+
+        assert!(matches!(
+            SpriteAtlas::from_json(json.as_bytes()),
+            Err(GraphicsError::SpriteAtlasParseError(_))
+        ));
+    }
+
+    #[test]
+    fn load_sprite_atlas() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file("assets/hero/hero.json", HASH_ATLAS.as_bytes().to_vec());
+
+        let atlas = sprite_atlas_loader(&metadata("hero"), &vfs).unwrap();
+
+        assert_eq!(atlas.frame_names().count(), 2);
+        assert_eq!(atlas.texture(), "hero_texture");
+    }
+}
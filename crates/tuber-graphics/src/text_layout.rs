@@ -0,0 +1,397 @@
+//! Layout of styled text with a bitmap font: word wrapping, alignment and
+//! line spacing.
+
+use tuber_math::color::Color;
+
+use crate::font::BitmapFont;
+use crate::texture::TextureRegion;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HorizontalAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerticalAlignment {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextStyle {
+    pub color: [f32; 4],
+    /// The size of the glyphs relative to their size in the font
+    pub scale: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE.into(),
+            scale: 1.0,
+        }
+    }
+}
+
+/// A piece of text sharing the same style
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSpan {
+    pub text: String,
+    pub style: TextStyle,
+}
+
+impl TextSpan {
+    #[must_use]
+    pub fn new(text: &str, style: TextStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextLayoutSettings {
+    /// The width after which lines are wrapped, lines are only broken on
+    /// newlines if unset. Words longer than this width overflow.
+    pub max_width: Option<f32>,
+    /// The height of the box the text is vertically aligned in
+    pub height: Option<f32>,
+    pub horizontal_alignment: HorizontalAlignment,
+    pub vertical_alignment: VerticalAlignment,
+    /// The space between two lines, defaults to the font's line spacing
+    pub line_spacing: Option<f32>,
+}
+
+/// A glyph positioned relatively to the top left corner of the text
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+    pub character: char,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub texture_region: TextureRegion,
+    pub style: TextStyle,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    pub width: f32,
+    pub height: f32,
+}
+
+type StyledCharacter = (char, TextStyle);
+
+/// Computes the position of every glyph of a text made of several spans
+#[must_use]
+pub fn layout_text(
+    font: &BitmapFont,
+    spans: &[TextSpan],
+    settings: &TextLayoutSettings,
+) -> TextLayout {
+    let characters: Vec<StyledCharacter> = spans
+        .iter()
+        .flat_map(|span| span.text.chars().map(move |c| (c, span.style)))
+        .collect();
+    let lines = break_lines(font, &characters, settings.max_width);
+    #[allow(clippy::cast_precision_loss)]
+    let line_spacing = settings.line_spacing.unwrap_or(font.line_spacing() as f32);
+
+    let line_widths: Vec<f32> = lines.iter().map(|line| line_width(font, line)).collect();
+    let line_heights: Vec<f32> = lines.iter().map(|line| line_height(font, line)).collect();
+    let width = line_widths.iter().copied().fold(0.0, f32::max);
+    #[allow(clippy::cast_precision_loss)]
+    let height =
+        line_heights.iter().sum::<f32>() + line_spacing * lines.len().saturating_sub(1) as f32;
+
+    let box_width = settings.max_width.unwrap_or(width);
+    let box_height = settings.height.unwrap_or(height);
+    let mut y = match settings.vertical_alignment {
+        VerticalAlignment::Top => 0.0,
+        VerticalAlignment::Middle => (box_height - height) / 2.0,
+        VerticalAlignment::Bottom => box_height - height,
+    };
+
+    let mut glyphs = vec![];
+    for ((line, line_width), line_height) in lines.iter().zip(line_widths).zip(line_heights) {
+        let mut x = match settings.horizontal_alignment {
+            HorizontalAlignment::Left => 0.0,
+            HorizontalAlignment::Center => (box_width - line_width) / 2.0,
+            HorizontalAlignment::Right => box_width - line_width,
+        };
+
+        for &(character, style) in line {
+            if let Some(glyph) = font.glyph(character) {
+                let region = glyph.region();
+                let glyph_height = region.height * style.scale;
+                glyphs.push(PositionedGlyph {
+                    character,
+                    x,
+                    y: y + line_height - glyph_height,
+                    width: region.width * style.scale,
+                    height: glyph_height,
+                    texture_region: region,
+                    style,
+                });
+            }
+
+            x += advance(font, character, style);
+        }
+
+        y += line_height + line_spacing;
+    }
+
+    TextLayout {
+        glyphs,
+        width,
+        height,
+    }
+}
+
+/// Returns the size a text would take once laid out with the default style
+#[must_use]
+pub fn measure_text(font: &BitmapFont, text: &str, settings: &TextLayoutSettings) -> (f32, f32) {
+    let layout = layout_text(font, &[TextSpan::new(text, TextStyle::default())], settings);
+    (layout.width, layout.height)
+}
+
+fn break_lines(
+    font: &BitmapFont,
+    characters: &[StyledCharacter],
+    max_width: Option<f32>,
+) -> Vec<Vec<StyledCharacter>> {
+    let mut lines = vec![];
+    for paragraph in characters.split(|&(c, _)| c == '\n') {
+        let mut line: Vec<StyledCharacter> = vec![];
+        let mut words = paragraph.split(|&(c, _)| c == ' ').peekable();
+        let mut separators = paragraph.iter().filter(|&&(c, _)| c == ' ');
+
+        while let Some(word) = words.next() {
+            let separator = if words.peek().is_some() {
+                separators.next().copied()
+            } else {
+                None
+            };
+
+            let mut candidate = line.clone();
+            candidate.extend_from_slice(word);
+            let overflows = matches!(
+                max_width,
+                Some(max_width) if line_width(font, &candidate) > max_width
+            );
+
+            if overflows && !line.is_empty() {
+                trim_trailing_spaces(&mut line);
+                lines.push(line);
+                line = word.to_vec();
+            } else {
+                line = candidate;
+            }
+
+            line.extend(separator);
+        }
+
+        trim_trailing_spaces(&mut line);
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn trim_trailing_spaces(line: &mut Vec<StyledCharacter>) {
+    while matches!(line.last(), Some((' ', _))) {
+        line.pop();
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn advance(font: &BitmapFont, character: char, style: TextStyle) -> f32 {
+    let glyph_width = font.glyph(character).map_or_else(
+        || font.line_height() as f32 / 2.0,
+        |glyph| glyph.region().width,
+    );
+    (glyph_width + font.letter_spacing() as f32) * style.scale
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn line_width(font: &BitmapFont, line: &[StyledCharacter]) -> f32 {
+    let width: f32 = line
+        .iter()
+        .map(|&(character, style)| advance(font, character, style))
+        .sum();
+    let trailing_letter_spacing = line.last().map_or(0.0, |&(_, style)| {
+        font.letter_spacing() as f32 * style.scale
+    });
+    width - trailing_letter_spacing
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn line_height(font: &BitmapFont, line: &[StyledCharacter]) -> f32 {
+    let scale = if line.is_empty() {
+        1.0
+    } else {
+        line.iter()
+            .map(|&(_, style)| style.scale)
+            .fold(0.0, f32::max)
+    };
+    font.line_height() as f32 * scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    fn line_starts(layout: &TextLayout) -> Vec<(char, f32, f32)> {
+        let mut starts = vec![];
+        let mut previous_y = None;
+        for glyph in &layout.glyphs {
+            match previous_y {
+                Some(y) if approx_eq(y, glyph.y) => {}
+                _ => starts.push((glyph.character, glyph.x, glyph.y)),
+            }
+            previous_y = Some(glyph.y);
+        }
+        starts
+    }
+
+    #[test]
+    fn measure_single_line() {
+        let font = BitmapFont::default_font();
+
+        let (width, height) = measure_text(&font, "AB", &TextLayoutSettings::default());
+
+        assert!(approx_eq(width, 11.0));
+        assert!(approx_eq(height, 7.0));
+    }
+
+    #[test]
+    fn measure_newlines() {
+        let font = BitmapFont::default_font();
+
+        let (width, height) = measure_text(&font, "ABC\nD", &TextLayoutSettings::default());
+
+        assert!(approx_eq(width, 17.0));
+        assert!(approx_eq(height, 15.0));
+    }
+
+    #[test]
+    fn word_wrapping() {
+        let font = BitmapFont::default_font();
+        let settings = TextLayoutSettings {
+            max_width: Some(30.0),
+            ..Default::default()
+        };
+
+        let layout = layout_text(
+            &font,
+            &[TextSpan::new("AB CD EFGHIJ K", TextStyle::default())],
+            &settings,
+        );
+
+        assert_eq!(
+            line_starts(&layout),
+            vec![('A', 0.0, 0.0), ('E', 0.0, 8.0), ('K', 0.0, 16.0)]
+        );
+        assert!(approx_eq(layout.width, 35.0));
+    }
+
+    #[test]
+    fn horizontal_alignment() {
+        let font = BitmapFont::default_font();
+        let mut settings = TextLayoutSettings {
+            max_width: Some(21.0),
+            horizontal_alignment: HorizontalAlignment::Center,
+            ..Default::default()
+        };
+
+        let centered = layout_text(
+            &font,
+            &[TextSpan::new("AB", TextStyle::default())],
+            &settings,
+        );
+        settings.horizontal_alignment = HorizontalAlignment::Right;
+        let right_aligned = layout_text(
+            &font,
+            &[TextSpan::new("AB", TextStyle::default())],
+            &settings,
+        );
+
+        assert!(approx_eq(centered.glyphs[0].x, 5.0));
+        assert!(approx_eq(right_aligned.glyphs[0].x, 10.0));
+        assert!(approx_eq(right_aligned.glyphs[1].x, 16.0));
+    }
+
+    #[test]
+    fn vertical_alignment() {
+        let font = BitmapFont::default_font();
+        let settings = TextLayoutSettings {
+            height: Some(27.0),
+            vertical_alignment: VerticalAlignment::Middle,
+            ..Default::default()
+        };
+
+        let layout = layout_text(
+            &font,
+            &[TextSpan::new("A", TextStyle::default())],
+            &settings,
+        );
+
+        assert!(approx_eq(layout.glyphs[0].y, 10.0));
+    }
+
+    #[test]
+    fn line_spacing() {
+        let font = BitmapFont::default_font();
+        let settings = TextLayoutSettings {
+            line_spacing: Some(5.0),
+            ..Default::default()
+        };
+
+        let layout = layout_text(
+            &font,
+            &[TextSpan::new("A\nB", TextStyle::default())],
+            &settings,
+        );
+
+        assert!(approx_eq(layout.glyphs[1].y, 12.0));
+        assert!(approx_eq(layout.height, 19.0));
+    }
+
+    #[test]
+    fn spans() {
+        let font = BitmapFont::default_font();
+        let red = TextStyle {
+            color: [1.0, 0.0, 0.0, 1.0],
+            scale: 2.0,
+        };
+
+        let layout = layout_text(
+            &font,
+            &[
+                TextSpan::new("A", TextStyle::default()),
+                TextSpan::new("B", red),
+            ],
+            &TextLayoutSettings::default(),
+        );
+
+        let (a, b) = (&layout.glyphs[0], &layout.glyphs[1]);
+        assert_eq!(b.style, red);
+        assert!(approx_eq(b.x, 6.0));
+        assert!(approx_eq(b.width, 10.0));
+        assert!(approx_eq(a.y, 7.0));
+        assert!(approx_eq(b.y, 0.0));
+        assert!(approx_eq(layout.width, 16.0));
+        assert!(approx_eq(layout.height, 14.0));
+    }
+}
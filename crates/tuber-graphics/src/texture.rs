@@ -0,0 +1,245 @@
+use png::{ColorType, Decoder, Transformations};
+use serde_derive::Deserialize;
+use tuber_core::asset::{read_asset_file, Metadata};
+use tuber_core::vfs::Vfs;
+use tuber_core::{CoreError, CoreResult};
+
+use crate::{GraphicsError, GraphicsResult};
+
+/// A rectangular region of a texture, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub struct TextureRegion {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl TextureRegion {
+    #[must_use]
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// The pixels of a texture, stored as RGBA8 rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextureData {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl TextureData {
+    pub const BYTES_PER_PIXEL: usize = 4;
+
+    /// Creates a texture filled with transparent pixels
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * Self::BYTES_PER_PIXEL],
+        }
+    }
+
+    /// Decodes a PNG image, whatever its color type and bit depth
+    pub fn from_png(bytes: &[u8]) -> GraphicsResult<Self> {
+        let mut decoder = Decoder::new(bytes);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|e| GraphicsError::TextureParseError(e.to_string()))?;
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let frame = reader
+            .next_frame(&mut pixels)
+            .map_err(|e| GraphicsError::TextureParseError(e.to_string()))?;
+        pixels.truncate(frame.buffer_size());
+
+        let data = match frame.color_type {
+            ColorType::Rgba => pixels,
+            ColorType::Rgb => pixels
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+                .collect(),
+            ColorType::GrayscaleAlpha => pixels
+                .chunks_exact(2)
+                .flat_map(|gray_alpha| {
+                    let [gray, alpha] = [gray_alpha[0], gray_alpha[1]];
+                    [gray, gray, gray, alpha]
+                })
+                .collect(),
+            ColorType::Grayscale => pixels
+                .iter()
+                .flat_map(|&gray| [gray, gray, gray, u8::MAX])
+                .collect(),
+            ColorType::Indexed => {
+                return Err(GraphicsError::TextureParseError(
+                    "indexed colors weren't expanded".into(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            width: frame.width,
+            height: frame.height,
+            data,
+        })
+    }
+
+    /// Checks that the texture isn't empty, that its data holds all its pixels
+    /// and that its dimensions don't exceed the given maximum
+    pub fn validate(&self, max_dimension: u32) -> GraphicsResult<()> {
+        if self.width == 0 || self.height == 0 {
+            return Err(GraphicsError::InvalidTextureData(format!(
+                "the texture is {}x{}",
+                self.width, self.height
+            )));
+        }
+
+        if self.width > max_dimension || self.height > max_dimension {
+            return Err(GraphicsError::InvalidTextureData(format!(
+                "the texture is {}x{}, exceeding the maximum dimension of {max_dimension}",
+                self.width, self.height
+            )));
+        }
+
+        let expected_length = self.width as usize * self.height as usize * Self::BYTES_PER_PIXEL;
+        if self.data.len() != expected_length {
+            return Err(GraphicsError::InvalidTextureData(format!(
+                "the texture data is {} bytes long instead of {expected_length}",
+                self.data.len()
+            )));
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let offset = self.pixel_offset(x, y);
+        [
+            self.data[offset],
+            self.data[offset + 1],
+            self.data[offset + 2],
+            self.data[offset + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let offset = self.pixel_offset(x, y);
+        self.data[offset..offset + Self::BYTES_PER_PIXEL].copy_from_slice(&pixel);
+    }
+
+    /// Copies the whole source texture at the given position
+    pub fn blit(&mut self, source: &TextureData, x: u32, y: u32) {
+        let row_length = source.width as usize * Self::BYTES_PER_PIXEL;
+        for row in 0..source.height {
+            let source_offset = source.pixel_offset(0, row);
+            let destination_offset = self.pixel_offset(x, y + row);
+            self.data[destination_offset..destination_offset + row_length]
+                .copy_from_slice(&source.data[source_offset..source_offset + row_length]);
+        }
+    }
+
+    fn pixel_offset(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * Self::BYTES_PER_PIXEL
+    }
+}
+
+/// Loads the PNG image named by the "file" metadata of the asset
+pub fn texture_loader(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Box<TextureData>> {
+    let texture = TextureData::from_png(&read_asset_file(metadata, vfs)?)
+        .map_err(|e| CoreError::AssetFileParseError(format!("{e:?}")))?;
+    Ok(Box::new(texture))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use png::{BitDepth, Encoder};
+    use tuber_core::vfs::InMemoryVfs;
+
+    use super::*;
+
+    fn png_bytes(width: u32, height: u32, color_type: ColorType, pixels: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut encoder = Encoder::new(&mut bytes, width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(pixels).unwrap();
+        writer.finish().unwrap();
+        bytes
+    }
+
+    #[test]
+    fn from_png() {
+        let rgba = png_bytes(2, 1, ColorType::Rgba, &[255, 0, 0, 255, 0, 0, 255, 128]);
+        let rgb = png_bytes(1, 2, ColorType::Rgb, &[0, 255, 0, 10, 20, 30]);
+        let gray = png_bytes(1, 1, ColorType::Grayscale, &[64]);
+
+        let rgba = TextureData::from_png(&rgba).unwrap();
+        let rgb = TextureData::from_png(&rgb).unwrap();
+        let gray = TextureData::from_png(&gray).unwrap();
+
+        assert_eq!((rgba.width, rgba.height), (2, 1));
+        assert_eq!(rgba.pixel(1, 0), [0, 0, 255, 128]);
+        assert_eq!((rgb.width, rgb.height), (1, 2));
+        assert_eq!(rgb.pixel(0, 1), [10, 20, 30, 255]);
+        assert_eq!(gray.pixel(0, 0), [64, 64, 64, 255]);
+        assert!(matches!(
+            TextureData::from_png(b"not a png"),
+            Err(GraphicsError::TextureParseError(_))
+        ));
+    }
+
+    #[test]
+    fn validate() {
+        let mut texture = TextureData::new(4, 2);
+        assert!(texture.validate(4).is_ok());
+        assert!(matches!(
+            texture.validate(3),
+            Err(GraphicsError::InvalidTextureData(_))
+        ));
+
+        texture.data.pop();
+        assert!(matches!(
+            texture.validate(4),
+            Err(GraphicsError::InvalidTextureData(_))
+        ));
+        assert!(matches!(
+            TextureData::new(0, 2).validate(4),
+            Err(GraphicsError::InvalidTextureData(_))
+        ));
+    }
+
+    #[test]
+    fn load_texture() {
+        let mut vfs = InMemoryVfs::default();
+        vfs.insert_file(
+            "assets/grass/grass.png",
+            png_bytes(1, 1, ColorType::Rgba, &[0, 128, 0, 255]),
+        );
+        let mut metadata = HashMap::new();
+        metadata.insert("file".to_string(), "grass.png".to_string());
+        let metadata = Metadata {
+            identifier: "grass".into(),
+            kind: "texture".into(),
+            metadata,
+            dependencies: vec![],
+            asset_path: PathBuf::from("assets/grass"),
+        };
+
+        let texture = texture_loader(&metadata, &vfs).unwrap();
+
+        assert_eq!(texture.pixel(0, 0), [0, 128, 0, 255]);
+    }
+}
@@ -0,0 +1,220 @@
+//! Packing of many small textures into a single texture atlas, so sprites
+//! using them can share the same GPU texture.
+
+use std::collections::HashMap;
+
+use crate::texture::{TextureData, TextureRegion};
+use crate::{GraphicsError, GraphicsResult};
+
+const DEFAULT_PADDING: u32 = 1;
+
+pub struct TextureAtlasBuilder {
+    max_size: u32,
+    padding: u32,
+    textures: Vec<(String, TextureData)>,
+}
+
+impl TextureAtlasBuilder {
+    /// Creates a builder for an atlas whose width and height can't exceed
+    /// `max_size`
+    #[must_use]
+    pub fn new(max_size: u32) -> Self {
+        Self {
+            max_size,
+            padding: DEFAULT_PADDING,
+            textures: vec![],
+        }
+    }
+
+    /// Sets the number of transparent pixels left between packed textures
+    #[must_use]
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn add_texture(&mut self, identifier: &str, texture: TextureData) {
+        self.textures.push((identifier.into(), texture));
+    }
+
+    /// Packs the textures into the smallest square power of two atlas they
+    /// fit in
+    pub fn build(mut self) -> GraphicsResult<TextureAtlas> {
+        self.textures
+            .sort_by(|(_, a), (_, b)| b.height.cmp(&a.height).then_with(|| b.width.cmp(&a.width)));
+
+        let mut size = 1;
+        let positions = loop {
+            if size > self.max_size {
+                return Err(GraphicsError::TextureAtlasOverflow);
+            }
+
+            if let Some(positions) = self.shelf_pack(size) {
+                break positions;
+            }
+
+            size *= 2;
+        };
+
+        let mut texture = TextureData::new(size, size);
+        let mut regions = HashMap::new();
+        for ((identifier, packed_texture), (x, y)) in self.textures.iter().zip(positions) {
+            texture.blit(packed_texture, x, y);
+            regions.insert(
+                identifier.clone(),
+                region(x, y, packed_texture.width, packed_texture.height),
+            );
+        }
+
+        Ok(TextureAtlas { texture, regions })
+    }
+
+    /// Places the textures on shelves from top to bottom, returns None if they
+    /// don't fit in an atlas of the given size
+    fn shelf_pack(&self, size: u32) -> Option<Vec<(u32, u32)>> {
+        let mut positions = Vec::with_capacity(self.textures.len());
+        let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+        for (_, texture) in &self.textures {
+            if x + texture.width > size {
+                x = 0;
+                y += shelf_height + self.padding;
+                shelf_height = 0;
+            }
+
+            if texture.width > size || y + texture.height > size {
+                return None;
+            }
+
+            positions.push((x, y));
+            x += texture.width + self.padding;
+            shelf_height = shelf_height.max(texture.height);
+        }
+
+        Some(positions)
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn region(x: u32, y: u32, width: u32, height: u32) -> TextureRegion {
+    TextureRegion::new(x as f32, y as f32, width as f32, height as f32)
+}
+
+pub struct TextureAtlas {
+    texture: TextureData,
+    regions: HashMap<String, TextureRegion>,
+}
+
+impl TextureAtlas {
+    #[must_use]
+    pub fn texture(&self) -> &TextureData {
+        &self.texture
+    }
+
+    /// Returns the region of the atlas occupied by a packed texture
+    #[must_use]
+    pub fn region(&self, identifier: &str) -> Option<TextureRegion> {
+        self.regions.get(identifier).copied()
+    }
+
+    /// Converts a region of a packed texture into the equivalent region of the
+    /// atlas
+    #[must_use]
+    pub fn remap_region(&self, identifier: &str, region: &TextureRegion) -> Option<TextureRegion> {
+        let packed_region = self.regions.get(identifier)?;
+        Some(TextureRegion::new(
+            packed_region.x + region.x,
+            packed_region.y + region.y,
+            region.width,
+            region.height,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuber_math::geometry::Rect;
+
+    fn filled_texture(width: u32, height: u32, value: u8) -> TextureData {
+        let mut texture = TextureData::new(width, height);
+        texture.data.fill(value);
+        texture
+    }
+
+    fn overlaps(a: &TextureRegion, b: &TextureRegion) -> bool {
+        Rect::new(a.x, a.y, a.width, a.height).intersects(&Rect::new(b.x, b.y, b.width, b.height))
+    }
+
+    #[test]
+    fn build() {
+        let mut builder = TextureAtlasBuilder::new(256);
+        builder.add_texture("a", filled_texture(16, 16, 1));
+        builder.add_texture("b", filled_texture(32, 8, 2));
+        builder.add_texture("c", filled_texture(8, 24, 3));
+
+        let atlas = builder.build().unwrap();
+
+        assert_eq!(atlas.texture().width, 64);
+        assert_eq!(atlas.texture().height, 64);
+        let regions: Vec<_> = ["a", "b", "c"]
+            .iter()
+            .map(|identifier| atlas.region(identifier).unwrap())
+            .collect();
+        for (i, a) in regions.iter().enumerate() {
+            for b in &regions[i + 1..] {
+                assert!(!overlaps(a, b));
+            }
+        }
+
+        let b = atlas.region("b").unwrap();
+        assert_eq!((b.width, b.height), (32.0, 8.0));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let pixel = atlas.texture().pixel(b.x as u32 + 31, b.y as u32 + 7);
+        assert_eq!(pixel, [2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn build_overflow() {
+        let mut builder = TextureAtlasBuilder::new(32);
+        builder.add_texture("a", filled_texture(32, 32, 1));
+        builder.add_texture("b", filled_texture(1, 1, 1));
+
+        assert!(matches!(
+            builder.build(),
+            Err(GraphicsError::TextureAtlasOverflow)
+        ));
+    }
+
+    #[test]
+    fn build_without_padding() {
+        let mut builder = TextureAtlasBuilder::new(32).with_padding(0);
+        for identifier in ["a", "b", "c", "d"] {
+            builder.add_texture(identifier, filled_texture(16, 16, 1));
+        }
+
+        let atlas = builder.build().unwrap();
+
+        assert_eq!(atlas.texture().width, 32);
+    }
+
+    #[test]
+    fn remap_region() {
+        let mut builder = TextureAtlasBuilder::new(64);
+        builder.add_texture("big", filled_texture(32, 32, 1));
+        builder.add_texture("sheet", filled_texture(16, 16, 1));
+        let atlas = builder.build().unwrap();
+        let sheet = atlas.region("sheet").unwrap();
+
+        let remapped = atlas
+            .remap_region("sheet", &TextureRegion::new(8.0, 4.0, 8.0, 8.0))
+            .unwrap();
+
+        assert_eq!(
+            remapped,
+            TextureRegion::new(sheet.x + 8.0, sheet.y + 4.0, 8.0, 8.0)
+        );
+        assert!(atlas
+            .remap_region("missing", &TextureRegion::default())
+            .is_none());
+    }
+}
@@ -0,0 +1,250 @@
+//! Bookkeeping of the textures uploaded to VRAM, evicting the least recently
+//! used ones when a budget is exceeded.
+
+use std::collections::{HashMap, HashSet};
+
+use log::warn;
+
+/// Identifies an uploaded texture, usually by the identifier of its asset
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextureId(pub String);
+
+impl From<&str> for TextureId {
+    fn from(identifier: &str) -> Self {
+        Self(identifier.into())
+    }
+}
+
+#[derive(Debug)]
+struct CachedTexture<T> {
+    texture: T,
+    /// The VRAM used by the texture, in bytes
+    size: u64,
+    /// The value of the cache's clock when the texture was last used
+    last_used: u64,
+}
+
+/// The textures in VRAM and the memory they use. Storing a texture evicts the
+/// least recently used ones until the budget is respected again, except the
+/// pinned ones.
+#[derive(Debug)]
+pub struct TextureCache<T> {
+    textures: HashMap<TextureId, CachedTexture<T>>,
+    /// The textures never evicted, such as the ones the frame being rendered
+    /// displays
+    pinned: HashSet<TextureId>,
+    /// In bytes, textures are never evicted if unset
+    budget: Option<u64>,
+    used: u64,
+    clock: u64,
+}
+
+impl<T> Default for TextureCache<T> {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl<T> TextureCache<T> {
+    #[must_use]
+    pub fn new(budget: Option<u64>) -> Self {
+        Self {
+            textures: HashMap::new(),
+            pinned: HashSet::new(),
+            budget,
+            used: 0,
+            clock: 0,
+        }
+    }
+
+    /// Stores a texture using the given VRAM size in bytes, replacing the
+    /// texture with the same identifier, and returns the textures evicted to
+    /// respect the budget. The stored texture itself is never evicted, even
+    /// if it exceeds the budget on its own.
+    pub fn insert(&mut self, id: TextureId, texture: T, size: u64) -> Vec<(TextureId, T)> {
+        let mut evicted: Vec<_> = self
+            .remove(&id)
+            .map(|replaced| (id.clone(), replaced))
+            .into_iter()
+            .collect();
+
+        if self.budget.is_some_and(|budget| size > budget) {
+            warn!(
+                "Texture {} uses {} bytes, exceeding the VRAM budget on its own",
+                id.0, size
+            );
+        }
+
+        self.clock += 1;
+        self.used += size;
+        self.textures.insert(
+            id,
+            CachedTexture {
+                texture,
+                size,
+                last_used: self.clock,
+            },
+        );
+
+        evicted.extend(self.evict_over_budget(Some(self.clock)));
+        evicted
+    }
+
+    /// Returns the texture, marking it as the most recently used
+    pub fn get(&mut self, id: &TextureId) -> Option<&T> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.textures.get_mut(id).map(|cached_texture| {
+            cached_texture.last_used = clock;
+            &cached_texture.texture
+        })
+    }
+
+    pub fn remove(&mut self, id: &TextureId) -> Option<T> {
+        let cached_texture = self.textures.remove(id)?;
+        self.used -= cached_texture.size;
+        Some(cached_texture.texture)
+    }
+
+    #[must_use]
+    pub fn contains(&self, id: &TextureId) -> bool {
+        self.textures.contains_key(id)
+    }
+
+    /// Returns the VRAM used by the stored textures, in bytes
+    #[must_use]
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    #[must_use]
+    pub fn budget(&self) -> Option<u64> {
+        self.budget
+    }
+
+    /// Pins the given textures in place of the previously pinned ones and
+    /// returns the textures evicted to respect the budget
+    pub fn pin(&mut self, ids: impl IntoIterator<Item = TextureId>) -> Vec<(TextureId, T)> {
+        self.pinned = ids.into_iter().collect();
+        self.evict_over_budget(None)
+    }
+
+    /// Changes the budget and returns the textures evicted to respect it
+    pub fn set_budget(&mut self, budget: Option<u64>) -> Vec<(TextureId, T)> {
+        self.budget = budget;
+        self.evict_over_budget(None)
+    }
+
+    /// Evicts the least recently used textures until the budget is respected,
+    /// except the pinned ones and the texture last used at the given time
+    fn evict_over_budget(&mut self, kept_last_used: Option<u64>) -> Vec<(TextureId, T)> {
+        let mut evicted = vec![];
+        let Some(budget) = self.budget else {
+            return evicted;
+        };
+
+        while self.used > budget {
+            let least_recently_used = self
+                .textures
+                .iter()
+                .filter(|(id, cached_texture)| {
+                    !self.pinned.contains(id) && Some(cached_texture.last_used) != kept_last_used
+                })
+                .min_by_key(|(_, cached_texture)| cached_texture.last_used)
+                .map(|(id, _)| id.clone());
+            let Some(id) = least_recently_used else {
+                break;
+            };
+
+            if let Some(texture) = self.remove(&id) {
+                evicted.push((id, texture));
+            }
+        }
+
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evicted_ids(evicted: Vec<(TextureId, ())>) -> Vec<String> {
+        evicted.into_iter().map(|(id, ())| id.0).collect()
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let mut cache = TextureCache::new(Some(100));
+        assert!(cache.insert("grass".into(), (), 40).is_empty());
+        assert!(cache.insert("rock".into(), (), 40).is_empty());
+        assert!(cache.get(&"grass".into()).is_some());
+
+        let evicted = cache.insert("water".into(), (), 40);
+
+        assert_eq!(evicted_ids(evicted), ["rock"]);
+        assert!(cache.contains(&"grass".into()));
+        assert!(!cache.contains(&"rock".into()));
+        assert_eq!(cache.used(), 80);
+    }
+
+    #[test]
+    fn replace_texture() {
+        let mut cache = TextureCache::new(None);
+        cache.insert("grass".into(), 1, 40);
+
+        let evicted = cache.insert("grass".into(), 2, 60);
+
+        assert_eq!(evicted, [("grass".into(), 1)]);
+        assert_eq!(cache.get(&"grass".into()), Some(&2));
+        assert_eq!(cache.used(), 60);
+    }
+
+    #[test]
+    fn texture_exceeding_budget_is_kept() {
+        let mut cache = TextureCache::new(Some(100));
+        cache.insert("grass".into(), (), 40);
+
+        let evicted = cache.insert("sky".into(), (), 150);
+
+        assert_eq!(evicted_ids(evicted), ["grass"]);
+        assert!(cache.contains(&"sky".into()));
+        assert_eq!(cache.used(), 150);
+    }
+
+    #[test]
+    fn pinned_textures_are_kept() {
+        let mut cache = TextureCache::new(Some(100));
+        cache.insert("grass".into(), (), 40);
+        cache.insert("rock".into(), (), 40);
+        assert!(cache.pin(["grass".into(), "rock".into()]).is_empty());
+
+        let evicted = cache.insert("water".into(), (), 40);
+
+        assert!(evicted.is_empty());
+        assert_eq!(cache.used(), 120);
+
+        let evicted = cache.pin(["water".into()]);
+
+        assert_eq!(evicted_ids(evicted), ["grass"]);
+        assert_eq!(cache.used(), 80);
+    }
+
+    #[test]
+    fn remove_and_set_budget() {
+        let mut cache = TextureCache::new(None);
+        cache.insert("grass".into(), (), 40);
+        cache.insert("rock".into(), (), 40);
+        cache.insert("water".into(), (), 40);
+
+        assert_eq!(cache.remove(&"rock".into()), Some(()));
+        assert_eq!(cache.remove(&"rock".into()), None);
+        assert_eq!(cache.used(), 80);
+
+        let evicted = cache.set_budget(Some(50));
+
+        assert_eq!(evicted_ids(evicted), ["grass"]);
+        assert_eq!(cache.used(), 40);
+        assert_eq!(cache.budget(), Some(50));
+    }
+}
@@ -0,0 +1,261 @@
+//! RGBA colors, with conversions from and to hexadecimal notation, HSV and
+//! HSL.
+//!
+//! Components are in [0, 1]. Colors convert from and to `[f32; 4]` arrays,
+//! which is how they are stored in components and vertices.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::interpolation::lerp;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::new(0.0, 0.0, 0.0, 0.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const GRAY: Color = Color::rgb(0.5, 0.5, 0.5);
+    pub const RED: Color = Color::rgb(1.0, 0.0, 0.0);
+    pub const GREEN: Color = Color::rgb(0.0, 1.0, 0.0);
+    pub const BLUE: Color = Color::rgb(0.0, 0.0, 1.0);
+    pub const YELLOW: Color = Color::rgb(1.0, 1.0, 0.0);
+    pub const CYAN: Color = Color::rgb(0.0, 1.0, 1.0);
+    pub const MAGENTA: Color = Color::rgb(1.0, 0.0, 1.0);
+    pub const ORANGE: Color = Color::rgb(1.0, 0.5, 0.0);
+    pub const PURPLE: Color = Color::rgb(0.5, 0.0, 1.0);
+
+    #[must_use]
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Creates an opaque color
+    #[must_use]
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+
+    /// Parses a color written `#RRGGBB` or `#RRGGBBAA`, the `#` being optional
+    #[must_use]
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !matches!(hex.len(), 6 | 8) || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let mut components = [255u8; 4];
+        for (component, digits) in components.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *component = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+        }
+
+        let [r, g, b, a] = components.map(|component| f32::from(component) / 255.0);
+        Some(Self::new(r, g, b, a))
+    }
+
+    /// Returns the color written `#RRGGBBAA`
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let [r, g, b, a] = self
+            .components()
+            .map(|component| (component.clamp(0.0, 1.0) * 255.0).round() as u8);
+        format!("#{r:02X}{g:02X}{b:02X}{a:02X}")
+    }
+
+    /// Creates a color from a hue in degrees, a saturation and a value
+    #[must_use]
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32, alpha: f32) -> Self {
+        let chroma = value * saturation;
+        from_hue_chroma(hue, chroma, value - chroma, alpha)
+    }
+
+    /// Returns the hue in degrees, the saturation and the value of the color
+    #[must_use]
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (max, chroma) = self.max_and_chroma();
+        let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+        (self.hue(max, chroma), saturation, max)
+    }
+
+    /// Creates a color from a hue in degrees, a saturation and a lightness
+    #[must_use]
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        from_hue_chroma(hue, chroma, lightness - chroma / 2.0, alpha)
+    }
+
+    /// Returns the hue in degrees, the saturation and the lightness of the
+    /// color
+    #[must_use]
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (max, chroma) = self.max_and_chroma();
+        let lightness = max - chroma / 2.0;
+        let saturation = if lightness > 0.0 && lightness < 1.0 {
+            chroma / (1.0 - (2.0 * lightness - 1.0).abs())
+        } else {
+            0.0
+        };
+        (self.hue(max, chroma), saturation, lightness)
+    }
+
+    /// Interpolates linearly between two colors, alpha included
+    #[must_use]
+    pub fn lerp(&self, other: &Color, t: f32) -> Self {
+        Self::new(
+            lerp(self.r, other.r, t),
+            lerp(self.g, other.g, t),
+            lerp(self.b, other.b, t),
+            lerp(self.a, other.a, t),
+        )
+    }
+
+    /// Returns the color with its RGB components multiplied by its alpha, as
+    /// expected by premultiplied alpha blending
+    #[must_use]
+    pub fn premultiplied(&self) -> Self {
+        Self::new(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    #[must_use]
+    pub fn with_alpha(&self, alpha: f32) -> Self {
+        Self::new(self.r, self.g, self.b, alpha)
+    }
+
+    #[must_use]
+    pub fn components(&self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    fn max_and_chroma(&self) -> (f32, f32) {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        (max, max - min)
+    }
+
+    fn hue(&self, max: f32, chroma: f32) -> f32 {
+        if chroma <= 0.0 {
+            return 0.0;
+        }
+
+        let sector = if (max - self.r).abs() < f32::EPSILON {
+            ((self.g - self.b) / chroma).rem_euclid(6.0)
+        } else if (max - self.g).abs() < f32::EPSILON {
+            (self.b - self.r) / chroma + 2.0
+        } else {
+            (self.r - self.g) / chroma + 4.0
+        };
+        sector * 60.0
+    }
+}
+
+/// Creates a color from the hue, the chroma and the amount added to every
+/// component, shared by the HSV and HSL conversions
+fn from_hue_chroma(hue: f32, chroma: f32, offset: f32, alpha: f32) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let second = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    Color::new(r + offset, g + offset, b + offset, alpha)
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::WHITE
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self::new(r, g, b, a)
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.components()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_color_eq(a: Color, b: Color) {
+        for (a, b) in a.components().into_iter().zip(b.components()) {
+            assert_float_absolute_eq!(a, b, 0.002);
+        }
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(
+            Color::from_hex("#FF8000"),
+            Some(Color::rgb(1.0, 128.0 / 255.0, 0.0))
+        );
+        assert_color_eq(
+            Color::from_hex("00ff0080").unwrap(),
+            Color::new(0.0, 1.0, 0.0, 0.5),
+        );
+        assert_eq!(Color::from_hex("#FF80"), None);
+        assert_eq!(Color::from_hex("#GG0000"), None);
+        assert_eq!(Color::from_hex("#+F+F+F"), None);
+        assert_eq!(Color::new(1.0, 0.5, 0.0, 1.0).to_hex(), "#FF8000FF");
+    }
+
+    #[test]
+    fn hsv() {
+        assert_color_eq(Color::from_hsv(120.0, 1.0, 1.0, 1.0), Color::GREEN);
+        assert_color_eq(Color::from_hsv(-60.0, 1.0, 1.0, 1.0), Color::MAGENTA);
+
+        let (hue, saturation, value) = Color::rgb(0.2, 0.4, 0.8).to_hsv();
+        assert_float_absolute_eq!(hue, 220.0, 0.01);
+        assert_float_absolute_eq!(saturation, 0.75, 0.001);
+        assert_float_absolute_eq!(value, 0.8, 0.001);
+        assert_color_eq(
+            Color::from_hsv(hue, saturation, value, 1.0),
+            Color::rgb(0.2, 0.4, 0.8),
+        );
+    }
+
+    #[test]
+    fn hsl() {
+        assert_color_eq(Color::from_hsl(0.0, 1.0, 0.5, 1.0), Color::RED);
+        assert_color_eq(Color::from_hsl(200.0, 0.0, 0.5, 1.0), Color::GRAY);
+
+        let (hue, saturation, lightness) = Color::rgb(0.2, 0.4, 0.8).to_hsl();
+        assert_float_absolute_eq!(hue, 220.0, 0.01);
+        assert_float_absolute_eq!(saturation, 0.6, 0.001);
+        assert_float_absolute_eq!(lightness, 0.5, 0.001);
+        assert_color_eq(
+            Color::from_hsl(hue, saturation, lightness, 1.0),
+            Color::rgb(0.2, 0.4, 0.8),
+        );
+    }
+
+    #[test]
+    fn lerp_and_premultiply() {
+        assert_color_eq(
+            Color::BLACK.lerp(&Color::WHITE.with_alpha(0.0), 0.25),
+            Color::new(0.25, 0.25, 0.25, 0.75),
+        );
+        assert_color_eq(
+            Color::new(1.0, 0.5, 0.2, 0.5).premultiplied(),
+            Color::new(0.5, 0.25, 0.1, 0.5),
+        );
+        assert_eq!(Color::from(<[f32; 4]>::from(Color::CYAN)), Color::CYAN);
+    }
+}
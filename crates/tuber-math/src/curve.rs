@@ -0,0 +1,409 @@
+//! Parametric curves for paths and rails, with arc length parameterization
+//! and tessellation to polylines.
+//!
+//! Curves are parameterized over [0, 1], the parameter being clamped to this
+//! range.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::geometry::Segment;
+use crate::vector::Vector2;
+
+/// The arc length samples taken per span by [`Curve::length`]
+const ARC_LENGTH_SAMPLES_PER_SPAN: usize = 64;
+/// The maximum depth of the subdivision of [`Curve::tessellate`], bounding
+/// the point count of degenerate curves
+const MAX_TESSELLATION_DEPTH: u32 = 16;
+
+pub trait Curve {
+    fn point_at(&self, t: f32) -> Vector2<f32>;
+
+    /// Returns the derivative of the curve with respect to its parameter,
+    /// which is tangent to the curve
+    fn derivative_at(&self, t: f32) -> Vector2<f32>;
+
+    /// Returns the number of polynomial pieces the curve is made of
+    fn span_count(&self) -> usize {
+        1
+    }
+
+    /// Returns an approximation of the length of the curve
+    fn length(&self) -> f32 {
+        ArcLengthTable::new(self, ARC_LENGTH_SAMPLES_PER_SPAN * self.span_count()).length()
+    }
+
+    /// Approximates the curve with a polyline, subdividing it until the curve
+    /// is less than `tolerance` away from the polyline. Straight parts get few
+    /// points while sharp turns get many.
+    fn tessellate(&self, tolerance: f32) -> Vec<Vector2<f32>> {
+        let start = self.point_at(0.0);
+        let mut points = vec![start];
+
+        // Starts from a few intervals per span so S-shaped spans, whose middle
+        // point lies on their chord, still get subdivided
+        let intervals = 4 * self.span_count();
+        let mut previous = (0.0, start);
+        for interval in 1..=intervals {
+            #[allow(clippy::cast_precision_loss)]
+            let t = interval as f32 / intervals as f32;
+            let point = self.point_at(t);
+            subdivide(self, previous, (t, point), tolerance, 0, &mut points);
+            previous = (t, point);
+        }
+
+        points
+    }
+}
+
+fn subdivide<C: Curve + ?Sized>(
+    curve: &C,
+    (start_t, start): (f32, Vector2<f32>),
+    (end_t, end): (f32, Vector2<f32>),
+    tolerance: f32,
+    depth: u32,
+    points: &mut Vec<Vector2<f32>>,
+) {
+    let middle_t = f32::midpoint(start_t, end_t);
+    let middle = curve.point_at(middle_t);
+    if depth < MAX_TESSELLATION_DEPTH
+        && Segment::new(start, end).distance_to_point(&middle) > tolerance
+    {
+        let middle = (middle_t, middle);
+        subdivide(
+            curve,
+            (start_t, start),
+            middle,
+            tolerance,
+            depth + 1,
+            points,
+        );
+        subdivide(curve, middle, (end_t, end), tolerance, depth + 1, points);
+    } else {
+        points.push(end);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct QuadraticBezier {
+    pub start: Vector2<f32>,
+    pub control: Vector2<f32>,
+    pub end: Vector2<f32>,
+}
+
+impl QuadraticBezier {
+    #[must_use]
+    pub fn new(start: Vector2<f32>, control: Vector2<f32>, end: Vector2<f32>) -> Self {
+        Self {
+            start,
+            control,
+            end,
+        }
+    }
+}
+
+impl Curve for QuadraticBezier {
+    fn point_at(&self, t: f32) -> Vector2<f32> {
+        let t = t.clamp(0.0, 1.0);
+        let u = 1.0 - t;
+        self.start * (u * u) + self.control * (2.0 * u * t) + self.end * (t * t)
+    }
+
+    fn derivative_at(&self, t: f32) -> Vector2<f32> {
+        let t = t.clamp(0.0, 1.0);
+        (self.control - self.start) * (2.0 * (1.0 - t)) + (self.end - self.control) * (2.0 * t)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct CubicBezier {
+    pub start: Vector2<f32>,
+    pub start_control: Vector2<f32>,
+    pub end_control: Vector2<f32>,
+    pub end: Vector2<f32>,
+}
+
+impl CubicBezier {
+    #[must_use]
+    pub fn new(
+        start: Vector2<f32>,
+        start_control: Vector2<f32>,
+        end_control: Vector2<f32>,
+        end: Vector2<f32>,
+    ) -> Self {
+        Self {
+            start,
+            start_control,
+            end_control,
+            end,
+        }
+    }
+}
+
+impl Curve for CubicBezier {
+    fn point_at(&self, t: f32) -> Vector2<f32> {
+        let t = t.clamp(0.0, 1.0);
+        let u = 1.0 - t;
+        self.start * (u * u * u)
+            + self.start_control * (3.0 * u * u * t)
+            + self.end_control * (3.0 * u * t * t)
+            + self.end * (t * t * t)
+    }
+
+    fn derivative_at(&self, t: f32) -> Vector2<f32> {
+        let t = t.clamp(0.0, 1.0);
+        let u = 1.0 - t;
+        (self.start_control - self.start) * (3.0 * u * u)
+            + (self.end_control - self.start_control) * (6.0 * u * t)
+            + (self.end - self.end_control) * (3.0 * t * t)
+    }
+}
+
+/// A uniform Catmull-Rom spline, passing through all of its points
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatmullRom {
+    points: Vec<Vector2<f32>>,
+}
+
+impl CatmullRom {
+    /// Creates a spline through points, of which there must be at least two
+    #[must_use]
+    pub fn new(points: Vec<Vector2<f32>>) -> Self {
+        assert!(
+            points.len() >= 2,
+            "a Catmull-Rom spline needs at least two points"
+        );
+        Self { points }
+    }
+
+    pub fn points(&self) -> &[Vector2<f32>] {
+        &self.points
+    }
+
+    /// Returns the four points shaping the span containing the parameter and
+    /// the parameter within this span. The first and last points are repeated
+    /// to shape the first and last spans.
+    fn span(&self, t: f32) -> ([Vector2<f32>; 4], f32) {
+        let span_count = self.span_count();
+        #[allow(clippy::cast_precision_loss)]
+        let position = t.clamp(0.0, 1.0) * span_count as f32;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let span = (position as usize).min(span_count - 1);
+        let point = |index: usize| self.points[index.min(self.points.len() - 1)];
+
+        #[allow(clippy::cast_precision_loss)]
+        let local_t = position - span as f32;
+        (
+            [
+                point(span.saturating_sub(1)),
+                point(span),
+                point(span + 1),
+                point(span + 2),
+            ],
+            local_t,
+        )
+    }
+}
+
+impl Curve for CatmullRom {
+    fn point_at(&self, t: f32) -> Vector2<f32> {
+        let ([p0, p1, p2, p3], t) = self.span(t);
+        (p1 * 2.0
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (t * t)
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (t * t * t))
+            * 0.5
+    }
+
+    fn derivative_at(&self, t: f32) -> Vector2<f32> {
+        let ([p0, p1, p2, p3], t) = self.span(t);
+        let span_derivative = ((p2 - p0)
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * (2.0 * t)
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * (3.0 * t * t))
+            * 0.5;
+
+        // Each span only covers a fraction of the parameter range
+        #[allow(clippy::cast_precision_loss)]
+        let span_count = self.span_count() as f32;
+        span_derivative * span_count
+    }
+
+    fn span_count(&self) -> usize {
+        self.points.len() - 1
+    }
+}
+
+/// The cumulative length of a curve at evenly spaced parameters, mapping
+/// distances along the curve to parameters so objects can move along it at
+/// a constant speed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArcLengthTable {
+    lengths: Vec<f32>,
+}
+
+impl ArcLengthTable {
+    pub fn new<C: Curve + ?Sized>(curve: &C, sample_count: usize) -> Self {
+        let sample_count = sample_count.max(1);
+        let mut lengths = Vec::with_capacity(sample_count + 1);
+        lengths.push(0.0);
+
+        let mut length = 0.0;
+        let mut previous = curve.point_at(0.0);
+        for sample in 1..=sample_count {
+            #[allow(clippy::cast_precision_loss)]
+            let point = curve.point_at(sample as f32 / sample_count as f32);
+            length += (point - previous).norm();
+            lengths.push(length);
+            previous = point;
+        }
+
+        Self { lengths }
+    }
+
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        *self.lengths.last().unwrap()
+    }
+
+    /// Returns the parameter of the point at a distance along the curve,
+    /// the distance being clamped to the length of the curve
+    #[must_use]
+    pub fn parameter_at_distance(&self, distance: f32) -> f32 {
+        let length = self.length();
+        if length <= 0.0 {
+            return 0.0;
+        }
+
+        let distance = distance.clamp(0.0, length);
+        let index = self
+            .lengths
+            .partition_point(|&sample_length| sample_length < distance)
+            .clamp(1, self.lengths.len() - 1);
+        let (before, after) = (self.lengths[index - 1], self.lengths[index]);
+        let fraction = if after > before {
+            (distance - before) / (after - before)
+        } else {
+            0.0
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let parameter = (index as f32 - 1.0 + fraction) / (self.lengths.len() - 1) as f32;
+        parameter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vector_eq(a: Vector2<f32>, b: Vector2<f32>) {
+        assert_float_absolute_eq!(a.x, b.x, 0.001);
+        assert_float_absolute_eq!(a.y, b.y, 0.001);
+    }
+
+    fn arch() -> CubicBezier {
+        CubicBezier::new(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(0.0, 10.0),
+            Vector2::new(10.0, 10.0),
+            Vector2::new(10.0, 0.0),
+        )
+    }
+
+    #[test]
+    fn bezier_points() {
+        let curve = arch();
+        assert_vector_eq(curve.point_at(0.0), curve.start);
+        assert_vector_eq(curve.point_at(1.0), curve.end);
+        assert_vector_eq(curve.point_at(0.5), Vector2::new(5.0, 7.5));
+
+        let quadratic = QuadraticBezier::new(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(5.0, 10.0),
+            Vector2::new(10.0, 0.0),
+        );
+        assert_vector_eq(quadratic.point_at(0.5), Vector2::new(5.0, 5.0));
+    }
+
+    #[test]
+    fn derivatives_match_finite_differences() {
+        let spline = CatmullRom::new(vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(4.0, 3.0),
+            Vector2::new(8.0, -1.0),
+            Vector2::new(12.0, 2.0),
+        ]);
+        let curves: [&dyn Curve; 2] = [&arch(), &spline];
+
+        for curve in curves {
+            for t in [0.1, 0.4, 0.6, 0.9] {
+                let step = 0.001;
+                let difference =
+                    (curve.point_at(t + step) - curve.point_at(t - step)) / (2.0 * step);
+                let derivative = curve.derivative_at(t);
+                assert_float_absolute_eq!(derivative.x, difference.x, 0.05);
+                assert_float_absolute_eq!(derivative.y, difference.y, 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn catmull_rom_passes_through_points() {
+        let points = vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(2.0, 5.0),
+            Vector2::new(6.0, 1.0),
+        ];
+        let spline = CatmullRom::new(points.clone());
+
+        assert_eq!(spline.span_count(), 2);
+        assert_vector_eq(spline.point_at(0.0), points[0]);
+        assert_vector_eq(spline.point_at(0.5), points[1]);
+        assert_vector_eq(spline.point_at(1.0), points[2]);
+    }
+
+    #[test]
+    fn arc_length() {
+        let line = CubicBezier::new(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(2.0, 0.0),
+            Vector2::new(10.0, 0.0),
+        );
+        assert_float_absolute_eq!(line.length(), 10.0, 0.001);
+
+        // Control points bunched at the start make the parameter speed up
+        // along the line, the table compensates for it
+        let table = ArcLengthTable::new(&line, 256);
+        for distance in [0.0, 2.5, 5.0, 7.5, 10.0] {
+            let point = line.point_at(table.parameter_at_distance(distance));
+            assert_float_absolute_eq!(point.x, distance, 0.05);
+        }
+    }
+
+    #[test]
+    fn tessellate() {
+        let curve = arch();
+        let tolerance = 0.01;
+        let points = curve.tessellate(tolerance);
+
+        assert_vector_eq(points[0], curve.start);
+        assert_vector_eq(*points.last().unwrap(), curve.end);
+        for sample in 0..=100u8 {
+            let point = curve.point_at(f32::from(sample) / 100.0);
+            let distance = points
+                .windows(2)
+                .map(|window| Segment::new(window[0], window[1]).distance_to_point(&point))
+                .fold(f32::MAX, f32::min);
+            assert!(distance <= tolerance * 1.5, "{distance}");
+        }
+
+        let straight = CubicBezier::new(
+            Vector2::new(0.0, 0.0),
+            Vector2::new(1.0, 0.0),
+            Vector2::new(2.0, 0.0),
+            Vector2::new(3.0, 0.0),
+        );
+        assert_eq!(straight.tessellate(tolerance).len(), 5);
+        assert!(points.len() > curve.tessellate(1.0).len());
+    }
+}
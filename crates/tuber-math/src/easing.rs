@@ -0,0 +1,214 @@
+//! Easing functions, shaping the progress of an animation.
+
+use std::f32::consts::PI;
+
+const BACK_OVERSHOOT: f32 = 1.701_58;
+const BOUNCE_AMPLITUDE: f32 = 7.5625;
+const BOUNCE_DURATION: f32 = 2.75;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    /// Goes slightly backwards before moving forward
+    BackIn,
+    /// Overshoots the end before settling back
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 31] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::QuartIn,
+        Easing::QuartOut,
+        Easing::QuartInOut,
+        Easing::QuintIn,
+        Easing::QuintOut,
+        Easing::QuintInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::ExpoInOut,
+        Easing::CircIn,
+        Easing::CircOut,
+        Easing::CircInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+    ];
+
+    /// Returns the eased progress for a linear progress `t` in [0, 1]. The
+    /// result is 0 at 0 and 1 at 1, but back and elastic easings go past
+    /// these values in between.
+    #[must_use]
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t.powi(2),
+            Easing::QuadOut => ease_out(t, |t| t.powi(2)),
+            Easing::QuadInOut => ease_in_out(t, |t| t.powi(2)),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => ease_out(t, |t| t.powi(3)),
+            Easing::CubicInOut => ease_in_out(t, |t| t.powi(3)),
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => ease_out(t, |t| t.powi(4)),
+            Easing::QuartInOut => ease_in_out(t, |t| t.powi(4)),
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => ease_out(t, |t| t.powi(5)),
+            Easing::QuintInOut => ease_in_out(t, |t| t.powi(5)),
+            Easing::SineIn => sine_in(t),
+            Easing::SineOut => ease_out(t, sine_in),
+            Easing::SineInOut => ease_in_out(t, sine_in),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => ease_out(t, expo_in),
+            Easing::ExpoInOut => ease_in_out(t, expo_in),
+            Easing::CircIn => circ_in(t),
+            Easing::CircOut => ease_out(t, circ_in),
+            Easing::CircInOut => ease_in_out(t, circ_in),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => ease_out(t, back_in),
+            Easing::BackInOut => ease_in_out(t, back_in),
+            Easing::ElasticIn => elastic_in(t),
+            Easing::ElasticOut => ease_out(t, elastic_in),
+            Easing::ElasticInOut => ease_in_out(t, elastic_in),
+            Easing::BounceIn => ease_out(t, bounce_out),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => ease_in_out(t, |t| ease_out(t, bounce_out)),
+        }
+    }
+}
+
+/// Plays an ease in function backwards
+fn ease_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    1.0 - ease_in(1.0 - t)
+}
+
+/// Plays an ease in function over the first half and backwards over the second
+fn ease_in_out(t: f32, ease_in: impl Fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(2.0 * t) / 2.0
+    } else {
+        1.0 - ease_in(2.0 - 2.0 * t) / 2.0
+    }
+}
+
+fn sine_in(t: f32) -> f32 {
+    1.0 - (t * PI / 2.0).cos()
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2.0f32.powf(10.0 * t - 10.0)
+    }
+}
+
+fn circ_in(t: f32) -> f32 {
+    1.0 - (1.0 - t * t).sqrt()
+}
+
+fn back_in(t: f32) -> f32 {
+    (BACK_OVERSHOOT + 1.0) * t.powi(3) - BACK_OVERSHOOT * t.powi(2)
+}
+
+fn elastic_in(t: f32) -> f32 {
+    if t <= 0.0 || t >= 1.0 {
+        return t;
+    }
+
+    -(2.0f32.powf(10.0 * t - 10.0)) * ((10.0 * t - 10.75) * 2.0 * PI / 3.0).sin()
+}
+
+fn bounce_out(t: f32) -> f32 {
+    if t < 1.0 / BOUNCE_DURATION {
+        BOUNCE_AMPLITUDE * t * t
+    } else if t < 2.0 / BOUNCE_DURATION {
+        let t = t - 1.5 / BOUNCE_DURATION;
+        BOUNCE_AMPLITUDE * t * t + 0.75
+    } else if t < 2.5 / BOUNCE_DURATION {
+        let t = t - 2.25 / BOUNCE_DURATION;
+        BOUNCE_AMPLITUDE * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / BOUNCE_DURATION;
+        BOUNCE_AMPLITUDE * t * t + 0.984_375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints() {
+        for easing in Easing::ALL {
+            assert_float_absolute_eq!(easing.apply(0.0), 0.0, 0.001);
+            assert_float_absolute_eq!(easing.apply(1.0), 1.0, 0.001);
+        }
+    }
+
+    #[test]
+    fn symmetric_in_out() {
+        for easing in Easing::ALL {
+            let value = easing.apply(0.5);
+            if format!("{easing:?}").ends_with("InOut") {
+                assert_float_absolute_eq!(value, 0.5, 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn values() {
+        assert_float_absolute_eq!(Easing::QuadIn.apply(0.5), 0.25, 0.001);
+        assert_float_absolute_eq!(Easing::QuadOut.apply(0.5), 0.75, 0.001);
+        assert_float_absolute_eq!(Easing::CubicInOut.apply(0.25), 0.0625, 0.001);
+        assert_float_absolute_eq!(Easing::SineInOut.apply(0.25), 0.146, 0.001);
+        assert_float_absolute_eq!(Easing::BounceOut.apply(0.5), 0.766, 0.001);
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+        assert!(Easing::ElasticOut.apply(0.2) > 1.0);
+        assert_float_absolute_eq!(Easing::Linear.apply(2.0), 1.0);
+    }
+}
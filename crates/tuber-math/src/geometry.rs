@@ -0,0 +1,456 @@
+//! Geometric primitives with containment, intersection and distance tests.
+//!
+//! Boundaries are inclusive for containment tests, while two shapes only
+//! touching along an edge don't intersect.
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::vector::{Vector2, Vector3};
+
+/// A rectangle defined by its top left corner and its size
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Rect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Rect {
+    #[must_use]
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn min(&self) -> Vector2<f32> {
+        Vector2::new(self.x, self.y)
+    }
+
+    pub fn max(&self) -> Vector2<f32> {
+        Vector2::new(self.x + self.width, self.y + self.height)
+    }
+
+    pub fn center(&self) -> Vector2<f32> {
+        Vector2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    #[must_use]
+    pub fn contains_point(&self, point: &Vector2<f32>) -> bool {
+        Aabb2::from(*self).contains_point(point)
+    }
+
+    #[must_use]
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        other.x >= self.x
+            && other.y >= self.y
+            && other.x + other.width <= self.x + self.width
+            && other.y + other.height <= self.y + self.height
+    }
+
+    #[must_use]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        Aabb2::from(*self).intersects(&Aabb2::from(*other))
+    }
+
+    /// Returns the overlapping area of two rectangles
+    #[must_use]
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        Aabb2::from(*self)
+            .intersection(&Aabb2::from(*other))
+            .map(Rect::from)
+    }
+
+    /// Returns the smallest rectangle containing both rectangles
+    #[must_use]
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect::from(Aabb2::from(*self).merged(&Aabb2::from(*other)))
+    }
+}
+
+impl From<Aabb2> for Rect {
+    fn from(aabb: Aabb2) -> Self {
+        let size = aabb.max - aabb.min;
+        Self::new(aabb.min.x, aabb.min.y, size.x, size.y)
+    }
+}
+
+impl From<Rect> for Aabb2 {
+    fn from(rect: Rect) -> Self {
+        Self::new(rect.min(), rect.max())
+    }
+}
+
+macro_rules! aabb {
+    ($name:ident, $vector:ident, ($($dim:ident),*)) => {
+        /// An axis-aligned bounding box defined by its minimum and maximum
+        /// corners
+        #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+        pub struct $name {
+            pub min: $vector<f32>,
+            pub max: $vector<f32>,
+        }
+
+        impl $name {
+            #[must_use]
+            pub fn new(min: $vector<f32>, max: $vector<f32>) -> Self {
+                Self { min, max }
+            }
+
+            #[must_use]
+            pub fn from_center_half_extents(center: $vector<f32>, half_extents: $vector<f32>) -> Self {
+                Self::new(center - half_extents, center + half_extents)
+            }
+
+            pub fn center(&self) -> $vector<f32> {
+                (self.min + self.max) * 0.5
+            }
+
+            pub fn half_extents(&self) -> $vector<f32> {
+                (self.max - self.min) * 0.5
+            }
+
+            #[must_use]
+            pub fn contains_point(&self, point: &$vector<f32>) -> bool {
+                $(point.$dim >= self.min.$dim && point.$dim <= self.max.$dim)&&*
+            }
+
+            #[must_use]
+            pub fn intersects(&self, other: &Self) -> bool {
+                $(self.min.$dim < other.max.$dim && other.min.$dim < self.max.$dim)&&*
+            }
+
+            #[must_use]
+            pub fn intersection(&self, other: &Self) -> Option<Self> {
+                if !self.intersects(other) {
+                    return None;
+                }
+
+                Some(Self::new(
+                    $vector::new($(self.min.$dim.max(other.min.$dim)),*),
+                    $vector::new($(self.max.$dim.min(other.max.$dim)),*),
+                ))
+            }
+
+            /// Returns the smallest box containing both boxes
+            #[must_use]
+            pub fn merged(&self, other: &Self) -> Self {
+                Self::new(
+                    $vector::new($(self.min.$dim.min(other.min.$dim)),*),
+                    $vector::new($(self.max.$dim.max(other.max.$dim)),*),
+                )
+            }
+
+            /// Returns the point of the box closest to a point
+            pub fn closest_point(&self, point: &$vector<f32>) -> $vector<f32> {
+                $vector::new($(point.$dim.clamp(self.min.$dim, self.max.$dim)),*)
+            }
+
+            /// Returns the distance from a point to the box, zero if the point
+            /// is inside
+            #[must_use]
+            pub fn distance_to_point(&self, point: &$vector<f32>) -> f32 {
+                (*point - self.closest_point(point)).norm()
+            }
+        }
+    };
+}
+
+aabb!(Aabb2, Vector2, (x, y));
+aabb!(Aabb3, Vector3, (x, y, z));
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Circle {
+    pub center: Vector2<f32>,
+    pub radius: f32,
+}
+
+impl Circle {
+    #[must_use]
+    pub fn new(center: Vector2<f32>, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    #[must_use]
+    pub fn contains_point(&self, point: &Vector2<f32>) -> bool {
+        (*point - self.center).length_squared() <= self.radius * self.radius
+    }
+
+    #[must_use]
+    pub fn intersects(&self, other: &Circle) -> bool {
+        let radii = self.radius + other.radius;
+        (other.center - self.center).length_squared() < radii * radii
+    }
+
+    #[must_use]
+    pub fn intersects_aabb(&self, aabb: &Aabb2) -> bool {
+        let closest_point = aabb.closest_point(&self.center);
+        (closest_point - self.center).length_squared() < self.radius * self.radius
+    }
+
+    /// Returns the distance from a point to the circle, zero if the point is
+    /// inside
+    #[must_use]
+    pub fn distance_to_point(&self, point: &Vector2<f32>) -> f32 {
+        ((*point - self.center).norm() - self.radius).max(0.0)
+    }
+}
+
+/// A half-line starting at an origin
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Ray {
+    pub origin: Vector2<f32>,
+    /// The direction of the ray, of length 1
+    pub direction: Vector2<f32>,
+}
+
+impl Ray {
+    /// Creates a ray, normalizing its direction
+    #[must_use]
+    pub fn new(origin: Vector2<f32>, direction: Vector2<f32>) -> Self {
+        Self {
+            origin,
+            direction: direction.normalized(),
+        }
+    }
+
+    /// Returns the point at a distance from the origin of the ray
+    pub fn point_at(&self, distance: f32) -> Vector2<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// Returns the distance along the ray to the first hit of a box, zero if
+    /// the origin is inside
+    #[must_use]
+    pub fn intersect_aabb(&self, aabb: &Aabb2) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = f32::INFINITY;
+        for (origin, direction, min, max) in [
+            (self.origin.x, self.direction.x, aabb.min.x, aabb.max.x),
+            (self.origin.y, self.direction.y, aabb.min.y, aabb.max.y),
+        ] {
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let first = (min - origin) / direction;
+            let second = (max - origin) / direction;
+            near = near.max(first.min(second));
+            far = far.min(first.max(second));
+            if near > far {
+                return None;
+            }
+        }
+
+        Some(near)
+    }
+
+    /// Returns the distance along the ray to the first hit of a circle, zero
+    /// if the origin is inside
+    #[must_use]
+    pub fn intersect_circle(&self, circle: &Circle) -> Option<f32> {
+        let to_origin = self.origin - circle.center;
+        let b = to_origin.dot(&self.direction);
+        let c = to_origin.length_squared() - circle.radius * circle.radius;
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 {
+            return None;
+        }
+
+        Some(-b - discriminant.sqrt())
+    }
+
+    /// Returns the distance along the ray to its intersection with a segment
+    #[must_use]
+    pub fn intersect_segment(&self, segment: &Segment) -> Option<f32> {
+        let segment_direction = segment.end - segment.start;
+        let denominator = self.direction.cross(&segment_direction);
+        if denominator == 0.0 {
+            return None;
+        }
+
+        let to_start = segment.start - self.origin;
+        let distance = to_start.cross(&segment_direction) / denominator;
+        let segment_position = to_start.cross(&self.direction) / denominator;
+        (distance >= 0.0 && (0.0..=1.0).contains(&segment_position)).then_some(distance)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Segment {
+    pub start: Vector2<f32>,
+    pub end: Vector2<f32>,
+}
+
+impl Segment {
+    #[must_use]
+    pub fn new(start: Vector2<f32>, end: Vector2<f32>) -> Self {
+        Self { start, end }
+    }
+
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        (self.end - self.start).norm()
+    }
+
+    /// Returns the point of the segment closest to a point
+    pub fn closest_point(&self, point: &Vector2<f32>) -> Vector2<f32> {
+        let direction = self.end - self.start;
+        let length_squared = direction.length_squared();
+        if length_squared == 0.0 {
+            return self.start;
+        }
+
+        let position = ((*point - self.start).dot(&direction) / length_squared).clamp(0.0, 1.0);
+        self.start + direction * position
+    }
+
+    #[must_use]
+    pub fn distance_to_point(&self, point: &Vector2<f32>) -> f32 {
+        (*point - self.closest_point(point)).norm()
+    }
+
+    /// Returns the point where two segments cross, parallel segments never
+    /// cross
+    #[must_use]
+    pub fn intersection(&self, other: &Segment) -> Option<Vector2<f32>> {
+        let direction = self.end - self.start;
+        let other_direction = other.end - other.start;
+        let denominator = direction.cross(&other_direction);
+        if denominator == 0.0 {
+            return None;
+        }
+
+        let to_other = other.start - self.start;
+        let position = to_other.cross(&other_direction) / denominator;
+        let other_position = to_other.cross(&direction) / denominator;
+        ((0.0..=1.0).contains(&position) && (0.0..=1.0).contains(&other_position))
+            .then(|| self.start + direction * position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 5.0);
+        let other = Rect::new(8.0, 2.0, 4.0, 4.0);
+
+        assert!(rect.contains_point(&Vector2::new(10.0, 5.0)));
+        assert!(!rect.contains_point(&Vector2::new(10.1, 5.0)));
+        assert!(rect.intersects(&other));
+        assert!(!rect.intersects(&Rect::new(10.0, 0.0, 1.0, 1.0)));
+        assert_eq!(
+            rect.intersection(&other),
+            Some(Rect::new(8.0, 2.0, 2.0, 3.0))
+        );
+        assert_eq!(rect.union(&other), Rect::new(0.0, 0.0, 12.0, 6.0));
+        assert!(rect.contains_rect(&Rect::new(1.0, 1.0, 2.0, 2.0)));
+        assert!(!rect.contains_rect(&other));
+    }
+
+    #[test]
+    fn aabb3() {
+        let aabb = Aabb3::from_center_half_extents(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        );
+
+        assert!(aabb.contains_point(&Vector3::new(1.0, -1.0, 0.5)));
+        assert!(aabb.intersects(&Aabb3::new(
+            Vector3::new(0.5, 0.5, 0.5),
+            Vector3::new(2.0, 2.0, 2.0)
+        )));
+        assert_float_absolute_eq!(
+            aabb.distance_to_point(&Vector3::new(4.0, 0.0, 5.0)),
+            5.0,
+            0.001
+        );
+        assert_float_absolute_eq!(aabb.distance_to_point(&Vector3::new(0.5, 0.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn circle() {
+        let circle = Circle::new(Vector2::new(0.0, 0.0), 2.0);
+
+        assert!(circle.contains_point(&Vector2::new(0.0, 2.0)));
+        assert!(circle.intersects(&Circle::new(Vector2::new(3.0, 0.0), 1.5)));
+        assert!(!circle.intersects(&Circle::new(Vector2::new(3.0, 0.0), 1.0)));
+        assert!(circle.intersects_aabb(&Aabb2::new(Vector2::new(1.0, 1.0), Vector2::new(3.0, 3.0))));
+        assert!(
+            !circle.intersects_aabb(&Aabb2::new(Vector2::new(1.5, 1.5), Vector2::new(3.0, 3.0)))
+        );
+        assert_float_absolute_eq!(circle.distance_to_point(&Vector2::new(0.0, 5.0)), 3.0);
+    }
+
+    #[test]
+    fn ray() {
+        let ray = Ray::new(Vector2::new(0.0, 0.0), Vector2::new(2.0, 0.0));
+
+        assert_eq!(
+            ray.intersect_aabb(&Aabb2::new(Vector2::new(3.0, -1.0), Vector2::new(4.0, 1.0))),
+            Some(3.0)
+        );
+        assert_eq!(
+            ray.intersect_aabb(&Aabb2::new(
+                Vector2::new(-4.0, -1.0),
+                Vector2::new(-3.0, 1.0)
+            )),
+            None
+        );
+        assert_eq!(
+            ray.intersect_circle(&Circle::new(Vector2::new(5.0, 0.0), 1.0)),
+            Some(4.0)
+        );
+        assert_eq!(
+            ray.intersect_circle(&Circle::new(Vector2::new(5.0, 2.0), 1.0)),
+            None
+        );
+        assert_eq!(
+            ray.intersect_segment(&Segment::new(
+                Vector2::new(2.0, -1.0),
+                Vector2::new(2.0, 1.0)
+            )),
+            Some(2.0)
+        );
+        assert_eq!(ray.point_at(1.5), Vector2::new(1.5, 0.0));
+    }
+
+    #[test]
+    fn segment() {
+        let segment = Segment::new(Vector2::new(0.0, 0.0), Vector2::new(4.0, 0.0));
+
+        assert_eq!(
+            segment.closest_point(&Vector2::new(6.0, 3.0)),
+            Vector2::new(4.0, 0.0)
+        );
+        assert_float_absolute_eq!(segment.distance_to_point(&Vector2::new(2.0, 3.0)), 3.0);
+        assert_eq!(
+            segment.intersection(&Segment::new(
+                Vector2::new(1.0, -1.0),
+                Vector2::new(1.0, 1.0)
+            )),
+            Some(Vector2::new(1.0, 0.0))
+        );
+        assert_eq!(
+            segment.intersection(&Segment::new(
+                Vector2::new(0.0, 1.0),
+                Vector2::new(4.0, 1.0)
+            )),
+            None
+        );
+    }
+}
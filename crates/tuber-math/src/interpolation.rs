@@ -0,0 +1,63 @@
+//! Scalar interpolation functions.
+
+/// Interpolates linearly between two values, `t` being 0 at `from` and 1 at
+/// `to`. `t` isn't clamped so values outside of [0, 1] extrapolate.
+#[must_use]
+pub fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+/// Returns where a value lies between two values, the inverse of `lerp`
+#[must_use]
+pub fn inverse_lerp(from: f32, to: f32, value: f32) -> f32 {
+    let range = to - from;
+    if range.abs() < f32::EPSILON {
+        return 0.0;
+    }
+
+    (value - from) / range
+}
+
+/// Maps a value from one range to another
+#[must_use]
+pub fn remap(value: f32, from_range: (f32, f32), to_range: (f32, f32)) -> f32 {
+    lerp(
+        to_range.0,
+        to_range.1,
+        inverse_lerp(from_range.0, from_range.1, value),
+    )
+}
+
+/// Returns 0 below `edge0`, 1 above `edge1` and a smooth Hermite
+/// interpolation in between
+#[must_use]
+pub fn smoothstep(edge0: f32, edge1: f32, value: f32) -> f32 {
+    let t = inverse_lerp(edge0, edge1, value).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lerp_and_inverse_lerp() {
+        assert_float_absolute_eq!(lerp(2.0, 6.0, 0.25), 3.0);
+        assert_float_absolute_eq!(lerp(2.0, 6.0, 1.5), 8.0);
+        assert_float_absolute_eq!(inverse_lerp(2.0, 6.0, 3.0), 0.25);
+        assert_float_absolute_eq!(inverse_lerp(2.0, 2.0, 3.0), 0.0);
+    }
+
+    #[test]
+    fn remap_range() {
+        assert_float_absolute_eq!(remap(5.0, (0.0, 10.0), (100.0, 200.0)), 150.0);
+        assert_float_absolute_eq!(remap(0.0, (-1.0, 1.0), (1.0, 0.0)), 0.5);
+    }
+
+    #[test]
+    fn smoothstep_edges() {
+        assert_float_absolute_eq!(smoothstep(1.0, 3.0, 0.0), 0.0);
+        assert_float_absolute_eq!(smoothstep(1.0, 3.0, 2.0), 0.5);
+        assert_float_absolute_eq!(smoothstep(1.0, 3.0, 4.0), 1.0);
+    }
+}
@@ -7,7 +7,13 @@
 #[macro_use]
 extern crate assert_float_eq;
 
+pub mod color;
+pub mod curve;
+pub mod easing;
+pub mod geometry;
+pub mod interpolation;
 pub mod matrix;
-mod number_traits;
+pub mod noise;
+pub mod number_traits;
 pub mod quaternion;
 pub mod vector;
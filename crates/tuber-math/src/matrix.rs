@@ -2,10 +2,162 @@ use std::fmt::{Debug, Display, Formatter};
 use std::ops::{Add, Index, IndexMut, Mul, MulAssign};
 
 use crate::number_traits::{Float, IsZero, NumericOps, One, Zero};
-use crate::vector::{Vector3, Vector4};
+use crate::quaternion::Quaternion;
+use crate::vector::{Vector2, Vector3, Vector4};
 
+pub type Matrix3f = Matrix3<f32>;
 pub type Matrix4f = Matrix4<f32>;
 
+/// A 3x3 matrix, used for 2D affine transforms
+#[derive(Clone, Copy)]
+pub struct Matrix3<T = f32> {
+    values: [T; 9],
+}
+
+impl<T> Debug for Matrix3<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[")?;
+        for i in 0..Self::ROWS {
+            write!(f, "\t")?;
+            for j in 0..Self::COLS {
+                write!(f, "{}, ", self.values[i * Self::COLS + j])?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "]")
+    }
+}
+
+impl<T> Matrix3<T> {
+    const COLS: usize = 3;
+    const ROWS: usize = 3;
+
+    pub fn with_values(values: [T; 9]) -> Self {
+        Self { values }
+    }
+
+    #[rustfmt::skip]
+    pub fn new_translation(translation: &Vector2<T>) -> Self
+        where T: Copy + Zero + One {
+        Self {
+            values: [
+                T::one(), T::zero(), translation.x,
+                T::zero(), T::one(), translation.y,
+                T::zero(), T::zero(), T::one(),
+            ]
+        }
+    }
+
+    /// Creates a counterclockwise rotation of the given angle in radians
+    #[rustfmt::skip]
+    pub fn new_rotation(angle: T) -> Self
+        where T: Float {
+        let (sin, cos) = (angle.sin(), angle.cos());
+        Self {
+            values: [
+                cos, -sin, T::zero(),
+                sin, cos, T::zero(),
+                T::zero(), T::zero(), T::one(),
+            ]
+        }
+    }
+
+    #[rustfmt::skip]
+    pub fn new_scale(scale: &Vector2<T>) -> Self
+        where T: Copy + Zero + One {
+        Self {
+            values: [
+                scale.x, T::zero(), T::zero(),
+                T::zero(), scale.y, T::zero(),
+                T::zero(), T::zero(), T::one(),
+            ]
+        }
+    }
+}
+
+impl<T> Matrix3<T>
+where
+    T: Copy + NumericOps + Zero + One,
+{
+    /// Transforms a point, which is affected by translations
+    pub fn transform_point(&self, point: &Vector2<T>) -> Vector2<T> {
+        Vector2::new(
+            self[0][0] * point.x + self[0][1] * point.y + self[0][2],
+            self[1][0] * point.x + self[1][1] * point.y + self[1][2],
+        )
+    }
+
+    /// Transforms a direction, which isn't affected by translations
+    pub fn transform_vector(&self, vector: &Vector2<T>) -> Vector2<T> {
+        Vector2::new(
+            self[0][0] * vector.x + self[0][1] * vector.y,
+            self[1][0] * vector.x + self[1][1] * vector.y,
+        )
+    }
+}
+
+impl<T> Mul<Self> for Matrix3<T>
+where
+    T: Copy + Zero + Add<Output = T> + Mul<Output = T>,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut values = [T::zero(); 9];
+
+        for j in 0..3 {
+            for i in 0..3 {
+                values[j * Self::COLS + i] = self.values[j * Self::COLS] * rhs.values[i]
+                    + self.values[j * Self::COLS + 1] * rhs.values[i + Self::COLS]
+                    + self.values[j * Self::COLS + 2] * rhs.values[i + Self::COLS * 2];
+            }
+        }
+
+        Self { values }
+    }
+}
+
+impl<T> MulAssign<Self> for Matrix3<T>
+where
+    T: Copy + Zero + Add<Output = T> + Mul<Output = T>,
+{
+    fn mul_assign(&mut self, rhs: Self) {
+        self.values = (*self * rhs).values;
+    }
+}
+
+impl<T> Index<usize> for Matrix3<T> {
+    type Output = [T];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.values[index * Self::ROWS..index * Self::ROWS + Self::COLS]
+    }
+}
+
+impl<T> IndexMut<usize> for Matrix3<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.values[index * Self::ROWS..index * Self::ROWS + Self::COLS]
+    }
+}
+
+/// Converts a 2D transform to a 3D transform in the XY plane
+#[rustfmt::skip]
+impl<T> From<Matrix3<T>> for Matrix4<T>
+    where T: Copy + Zero + One {
+    fn from(matrix: Matrix3<T>) -> Self {
+        let m = matrix.values;
+        Matrix4::with_values([
+            m[0], m[1], T::zero(), m[2],
+            m[3], m[4], T::zero(), m[5],
+            T::zero(), T::zero(), T::one(), T::zero(),
+            m[6], m[7], T::zero(), m[8],
+        ])
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Matrix4<T = f32> {
     values: [T; 16],
@@ -57,6 +209,46 @@ impl<T> Matrix4<T> {
         }
     }
 
+    /// Creates a perspective projection looking towards -Z, mapping the
+    /// depth between the near and far planes to [-1, 1]
+    #[rustfmt::skip]
+    pub fn new_perspective<U>(
+        vertical_fov: U,
+        aspect_ratio: U,
+        near: U,
+        far: U,
+    ) -> Matrix4<U>
+        where U: Copy + Float {
+        let half_fov = vertical_fov.half();
+        let focal_length = half_fov.cos() / half_fov.sin();
+        Matrix4 {
+            values: [
+                focal_length / aspect_ratio, U::zero(), U::zero(), U::zero(),
+                U::zero(), focal_length, U::zero(), U::zero(),
+                U::zero(), U::zero(), (far + near) / (near - far), U::two() * far * near / (near - far),
+                U::zero(), U::zero(), -U::one(), U::zero()
+            ]
+        }
+    }
+
+    /// Creates the view matrix of a camera at `eye` looking at `target`, with
+    /// the +Y axis of the view as close as possible to `up`
+    #[rustfmt::skip]
+    pub fn new_look_at<U>(eye: &Vector3<U>, target: &Vector3<U>, up: &Vector3<U>) -> Matrix4<U>
+        where U: Copy + Float {
+        let back = (*eye - *target).normalized();
+        let right = up.cross(&back).normalized();
+        let up = back.cross(&right);
+        Matrix4 {
+            values: [
+                right.x, right.y, right.z, -right.dot(eye),
+                up.x, up.y, up.z, -up.dot(eye),
+                back.x, back.y, back.z, -back.dot(eye),
+                U::zero(), U::zero(), U::zero(), U::one()
+            ]
+        }
+    }
+
     #[rustfmt::skip]
     pub fn new_translation<U>(translation: &Vector3<U>) -> Matrix4<U>
         where U: Copy + Zero + One {
@@ -171,6 +363,62 @@ where
     }
 }
 
+impl<T> Matrix4<T>
+where
+    T: Copy,
+{
+    #[must_use]
+    pub fn transpose(&self) -> Self {
+        let mut values = self.values;
+        for row in 0..Self::ROWS {
+            for col in 0..Self::COLS {
+                values[col * Self::COLS + row] = self.values[row * Self::COLS + col];
+            }
+        }
+
+        Self { values }
+    }
+}
+
+impl<T> Matrix4<T>
+where
+    T: Debug + Float + IsZero,
+{
+    /// Decomposes an affine transform into the translation, rotation and scale
+    /// it can be built from, applied in the scale, rotation then translation
+    /// order. A mirroring transform gets a negative x scale, and a transform
+    /// with a zero scale gets the identity rotation.
+    pub fn decompose(&self) -> (Vector3<T>, Quaternion<T>, Vector3<T>) {
+        let translation = Vector3::new(self[0][3], self[1][3], self[2][3]);
+        let mut columns =
+            [0, 1, 2].map(|col| Vector3::new(self[0][col], self[1][col], self[2][col]));
+        let mut scale = Vector3::new(columns[0].norm(), columns[1].norm(), columns[2].norm());
+        if columns[0].dot(&columns[1].cross(&columns[2])) < T::zero() {
+            scale.x = -scale.x;
+        }
+
+        if scale.x.is_zero() || scale.y.is_zero() || scale.z.is_zero() {
+            return (translation, Quaternion::identity(), scale);
+        }
+
+        columns[0] /= scale.x;
+        columns[1] /= scale.y;
+        columns[2] /= scale.z;
+        #[rustfmt::skip]
+        let rotation = Matrix4::with_values([
+            columns[0].x, columns[1].x, columns[2].x, T::zero(),
+            columns[0].y, columns[1].y, columns[2].y, T::zero(),
+            columns[0].z, columns[1].z, columns[2].z, T::zero(),
+            T::zero(), T::zero(), T::zero(), T::one(),
+        ]);
+        (
+            translation,
+            Quaternion::from_rotation_matrix(&rotation),
+            scale,
+        )
+    }
+}
+
 impl<T> Mul<Self> for Matrix4<T>
 where
     T: Copy + Zero + Add<Output = T> + Mul<Output = T>,
@@ -254,6 +502,20 @@ pub trait Identity {
     fn identity() -> Self;
 }
 
+#[rustfmt::skip]
+impl<T> Identity for Matrix3<T>
+    where T: One + Zero {
+    fn identity() -> Self {
+        Self {
+            values: [
+                T::one(), T::zero(), T::zero(),
+                T::zero(), T::one(), T::zero(),
+                T::zero(), T::zero(), T::one()
+            ]
+        }
+    }
+}
+
 #[rustfmt::skip]
 impl<T> Identity for Matrix4<T>
     where T: One + Zero {
@@ -376,6 +638,44 @@ mod tests {
         assert_eq!(a[3][3], 1528);
     }
 
+    #[test]
+    fn matrix3_transform_point() {
+        let transform = Matrix3f::new_translation(&Vector2::new(1.0, 2.0))
+            * Matrix3f::new_rotation(std::f32::consts::FRAC_PI_2)
+            * Matrix3f::new_scale(&Vector2::new(2.0, 2.0));
+
+        let point = transform.transform_point(&Vector2::new(1.0, 0.0));
+        let vector = transform.transform_vector(&Vector2::new(1.0, 0.0));
+
+        assert_float_absolute_eq!(point.x, 1.0, 0.001);
+        assert_float_absolute_eq!(point.y, 4.0, 0.001);
+        assert_float_absolute_eq!(vector.x, 0.0, 0.001);
+        assert_float_absolute_eq!(vector.y, 2.0, 0.001);
+    }
+
+    #[test]
+    fn matrix3_identity() {
+        let m = Matrix3::<i32>::identity() * Matrix3::<i32>::new_translation(&Vector2::new(3, 4));
+
+        assert_eq!(m.transform_point(&Vector2::new(1, 1)), Vector2::new(4, 5));
+    }
+
+    #[test]
+    fn matrix3_to_matrix4() {
+        let transform = Matrix3f::new_translation(&Vector2::new(3.0, -1.0))
+            * Matrix3f::new_rotation(0.5)
+            * Matrix3f::new_scale(&Vector2::new(2.0, 3.0));
+        let point = Vector2::new(0.5, 2.0);
+
+        let expected = transform.transform_point(&point);
+        let transformed =
+            Matrix4f::from(transform).transform_vec3(&Vector3::new(point.x, point.y, 7.0));
+
+        assert_float_absolute_eq!(transformed.x, expected.x, 0.001);
+        assert_float_absolute_eq!(transformed.y, expected.y, 0.001);
+        assert_float_absolute_eq!(transformed.z, 7.0, 0.001);
+    }
+
     #[rustfmt::skip]
     #[test]
     fn try_inverse() {
@@ -405,4 +705,93 @@ mod tests {
         assert_float_absolute_eq!(inverse[3][2], -1.0, 0.1);
         assert_float_absolute_eq!(inverse[3][3], -0.5, 0.1);
     }
+
+    #[rustfmt::skip]
+    #[test]
+    fn transpose() {
+        let a = Matrix4::<i32>::with_values([
+            1, 2, 3, 4,
+            5, 6, 7, 8,
+            9, 10, 11, 12,
+            13, 14, 15, 16
+        ]);
+
+        let transposed = a.transpose();
+
+        assert_eq!(transposed[0][1], 5);
+        assert_eq!(transposed[1][0], 2);
+        assert_eq!(transposed[3][2], 12);
+        assert_eq!(transposed[2][2], 11);
+    }
+
+    #[test]
+    fn perspective() {
+        let projection = Matrix4f::new_perspective(std::f32::consts::FRAC_PI_2, 2.0, 1.0, 10.0);
+
+        let project = |point: Vector3<f32>| {
+            let clip = projection.transform_vec(&Vector4::new(point.x, point.y, point.z, 1.0));
+            Vector3::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w)
+        };
+        let near = project(Vector3::new(2.0, 1.0, -1.0));
+        let far = project(Vector3::new(0.0, 0.0, -10.0));
+
+        assert_float_absolute_eq!(near.x, 1.0, 0.001);
+        assert_float_absolute_eq!(near.y, 1.0, 0.001);
+        assert_float_absolute_eq!(near.z, -1.0, 0.001);
+        assert_float_absolute_eq!(far.z, 1.0, 0.001);
+    }
+
+    #[test]
+    fn look_at() {
+        let eye = Vector3::new(1.0, 2.0, 3.0);
+        let view = Matrix4f::new_look_at(
+            &eye,
+            &Vector3::new(1.0, 2.0, -5.0),
+            &Vector3::new(0.0, 1.0, 0.0),
+        );
+
+        let eye = view.transform_vec3(&eye);
+        let target = view.transform_vec3(&Vector3::new(1.0, 2.0, -5.0));
+        let above = view.transform_vec3(&Vector3::new(1.0, 3.0, 3.0));
+
+        assert_float_absolute_eq!(eye.norm(), 0.0, 0.001);
+        assert_float_absolute_eq!(target.z, -8.0, 0.001);
+        assert_float_absolute_eq!(above.y, 1.0, 0.001);
+    }
+
+    #[test]
+    fn decompose() {
+        let rotation = Quaternion::from_axis_angle(&Vector3::new(0.0, 1.0, 0.0), 0.7);
+        let transform = Matrix4f::new_translation(&Vector3::new(4.0, -2.0, 1.0))
+            * rotation.rotation_matrix()
+            * Matrix4f::new_scale(&Vector3::new(-2.0, 3.0, 0.5));
+
+        let (translation, decomposed_rotation, scale) = transform.decompose();
+        let rebuilt = Matrix4f::new_translation(&translation)
+            * decomposed_rotation.rotation_matrix()
+            * Matrix4f::new_scale(&scale);
+
+        assert_eq!(translation, Vector3::new(4.0, -2.0, 1.0));
+        assert_float_absolute_eq!(scale.x, -2.0, 0.001);
+        assert_float_absolute_eq!(scale.y, 3.0, 0.001);
+        assert_float_absolute_eq!(scale.z, 0.5, 0.001);
+        for row in 0..4 {
+            for col in 0..4 {
+                assert_float_absolute_eq!(rebuilt[row][col], transform[row][col], 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn double_precision() {
+        let transform = Matrix4::<f64>::new_translation(&Vector3::new(1.0e9, 0.0, 0.0))
+            * Matrix4::<f64>::new_scale_uniform(2.0);
+
+        let point = transform
+            .try_inverse()
+            .unwrap()
+            .transform_vec3(&Vector3::new(1.0e9 + 0.002, 0.0, 0.0));
+
+        assert!((point.x - 0.001).abs() < 1.0e-6);
+    }
 }
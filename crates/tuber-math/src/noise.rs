@@ -0,0 +1,479 @@
+//! Coherent noise for procedural generation: value, Perlin and simplex noise
+//! in one to three dimensions, and fractal Brownian motion summing octaves of
+//! any of them.
+//!
+//! Every noise function returns values in approximately [-1, 1], and the same
+//! seed always produces the same noise.
+
+const PERMUTATION_SIZE: usize = 256;
+
+/// The 12 gradients of 3D Perlin and simplex noise, pointing to the middle
+/// of the edges of a cube
+const GRADIENTS_3D: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+];
+
+const GRADIENTS_2D: [[f32; 2]; 8] = [
+    [1.0, 1.0],
+    [-1.0, 1.0],
+    [1.0, -1.0],
+    [-1.0, -1.0],
+    [1.0, 0.0],
+    [-1.0, 0.0],
+    [0.0, 1.0],
+    [0.0, -1.0],
+];
+
+/// A noise generator, whose permutation table is shuffled from a seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Noise {
+    /// A permutation of [0, 256) repeated twice, so hashing several
+    /// coordinates doesn't need to wrap indices
+    permutation: Vec<u8>,
+}
+
+impl Noise {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut permutation: Vec<u8> = (0..PERMUTATION_SIZE).map(|i| i as u8).collect();
+
+        // Fisher-Yates shuffle driven by SplitMix64
+        let mut state = seed;
+        for i in (1..PERMUTATION_SIZE).rev() {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^= z >> 31;
+            #[allow(clippy::cast_possible_truncation)]
+            let j = (z % (i as u64 + 1)) as usize;
+            permutation.swap(i, j);
+        }
+
+        permutation.extend_from_within(..);
+        Self { permutation }
+    }
+
+    #[must_use]
+    pub fn value1(&self, x: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        lerp(
+            self.lattice_value(self.hash1(x0)),
+            self.lattice_value(self.hash1(x0 + 1)),
+            fade(fx),
+        )
+    }
+
+    #[must_use]
+    pub fn value2(&self, x: f32, y: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        let (y0, fy) = lattice(y);
+        let value = |dx, dy| self.lattice_value(self.hash2(x0 + dx, y0 + dy));
+
+        lerp(
+            lerp(value(0, 0), value(1, 0), fade(fx)),
+            lerp(value(0, 1), value(1, 1), fade(fx)),
+            fade(fy),
+        )
+    }
+
+    #[must_use]
+    pub fn value3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        let (y0, fy) = lattice(y);
+        let (z0, fz) = lattice(z);
+        let value = |dx, dy, dz| self.lattice_value(self.hash3(x0 + dx, y0 + dy, z0 + dz));
+        let plane = |dz| {
+            lerp(
+                lerp(value(0, 0, dz), value(1, 0, dz), fade(fx)),
+                lerp(value(0, 1, dz), value(1, 1, dz), fade(fx)),
+                fade(fy),
+            )
+        };
+
+        lerp(plane(0), plane(1), fade(fz))
+    }
+
+    #[must_use]
+    pub fn perlin1(&self, x: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        let gradient = |dx: i32, offset: f32| gradient1(self.hash1(x0 + dx)) * offset;
+
+        // The largest value with unit gradients is 0.5
+        2.0 * lerp(gradient(0, fx), gradient(1, fx - 1.0), fade(fx))
+    }
+
+    #[must_use]
+    pub fn perlin2(&self, x: f32, y: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        let (y0, fy) = lattice(y);
+        let gradient = |dx: i32, dy: i32| {
+            let [gx, gy] = GRADIENTS_2D[usize::from(self.hash2(x0 + dx, y0 + dy)) % 8];
+            #[allow(clippy::cast_precision_loss)]
+            let value = gx * (fx - dx as f32) + gy * (fy - dy as f32);
+            value
+        };
+
+        lerp(
+            lerp(gradient(0, 0), gradient(1, 0), fade(fx)),
+            lerp(gradient(0, 1), gradient(1, 1), fade(fx)),
+            fade(fy),
+        )
+    }
+
+    #[must_use]
+    pub fn perlin3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        let (y0, fy) = lattice(y);
+        let (z0, fz) = lattice(z);
+        let gradient = |dx: i32, dy: i32, dz: i32| {
+            let [gx, gy, gz] =
+                GRADIENTS_3D[usize::from(self.hash3(x0 + dx, y0 + dy, z0 + dz)) % 12];
+            #[allow(clippy::cast_precision_loss)]
+            let value = gx * (fx - dx as f32) + gy * (fy - dy as f32) + gz * (fz - dz as f32);
+            value
+        };
+        let plane = |dz| {
+            lerp(
+                lerp(gradient(0, 0, dz), gradient(1, 0, dz), fade(fx)),
+                lerp(gradient(0, 1, dz), gradient(1, 1, dz), fade(fx)),
+                fade(fy),
+            )
+        };
+
+        lerp(plane(0), plane(1), fade(fz))
+    }
+
+    #[must_use]
+    pub fn simplex1(&self, x: f32) -> f32 {
+        let (x0, fx) = lattice(x);
+        let contribution = |dx: i32, offset: f32| {
+            let t = (1.0 - offset * offset).max(0.0);
+            t.powi(4) * gradient1(self.hash1(x0 + dx)) * offset
+        };
+
+        // Scales the largest contribution sum, about 0.4, to 1
+        2.5 * (contribution(0, fx) + contribution(1, fx - 1.0))
+    }
+
+    #[allow(clippy::similar_names, clippy::many_single_char_names)]
+    #[must_use]
+    pub fn simplex2(&self, x: f32, y: f32) -> f32 {
+        let skew = (3.0f32.sqrt() - 1.0) / 2.0;
+        let unskew = (3.0 - 3.0f32.sqrt()) / 6.0;
+
+        // Finds the simplex cell containing the point in the skewed grid
+        let s = (x + y) * skew;
+        let (i, _) = lattice(x + s);
+        let (j, _) = lattice(y + s);
+        #[allow(clippy::cast_precision_loss)]
+        let t = (i + j) as f32 * unskew;
+        #[allow(clippy::cast_precision_loss)]
+        let (x0, y0) = (x - (i as f32 - t), y - (j as f32 - t));
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+
+        #[allow(clippy::cast_precision_loss)]
+        let corners = [
+            (0, 0, x0, y0),
+            (i1, j1, x0 - i1 as f32 + unskew, y0 - j1 as f32 + unskew),
+            (1, 1, x0 - 1.0 + 2.0 * unskew, y0 - 1.0 + 2.0 * unskew),
+        ];
+        let sum: f32 = corners
+            .iter()
+            .map(|&(di, dj, cx, cy)| {
+                let t = 0.5 - cx * cx - cy * cy;
+                if t < 0.0 {
+                    return 0.0;
+                }
+
+                let [gx, gy, _] = GRADIENTS_3D[usize::from(self.hash2(i + di, j + dj)) % 12];
+                t.powi(4) * (gx * cx + gy * cy)
+            })
+            .sum();
+
+        70.0 * sum
+    }
+
+    #[allow(clippy::similar_names, clippy::many_single_char_names)]
+    #[must_use]
+    pub fn simplex3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let skew = 1.0 / 3.0;
+        let unskew = 1.0 / 6.0;
+
+        let s = (x + y + z) * skew;
+        let (i, _) = lattice(x + s);
+        let (j, _) = lattice(y + s);
+        let (k, _) = lattice(z + s);
+        #[allow(clippy::cast_precision_loss)]
+        let t = (i + j + k) as f32 * unskew;
+        #[allow(clippy::cast_precision_loss)]
+        let (x0, y0, z0) = (x - (i as f32 - t), y - (j as f32 - t), z - (k as f32 - t));
+
+        // The two middle corners of the tetrahedron containing the point,
+        // found by ordering the offsets
+        let ((i1, j1, k1), (i2, j2, k2)) = if x0 >= y0 {
+            if y0 >= z0 {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x0 >= z0 {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y0 < z0 {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x0 < z0 {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+
+        #[allow(clippy::cast_precision_loss)]
+        let corner = |di: i32, dj: i32, dk: i32, corner_index: f32| {
+            (
+                di,
+                dj,
+                dk,
+                x0 - di as f32 + corner_index * unskew,
+                y0 - dj as f32 + corner_index * unskew,
+                z0 - dk as f32 + corner_index * unskew,
+            )
+        };
+        let corners = [
+            corner(0, 0, 0, 0.0),
+            corner(i1, j1, k1, 1.0),
+            corner(i2, j2, k2, 2.0),
+            corner(1, 1, 1, 3.0),
+        ];
+        let sum: f32 = corners
+            .iter()
+            .map(|&(di, dj, dk, cx, cy, cz)| {
+                let t = 0.6 - cx * cx - cy * cy - cz * cz;
+                if t < 0.0 {
+                    return 0.0;
+                }
+
+                let [gx, gy, gz] =
+                    GRADIENTS_3D[usize::from(self.hash3(i + di, j + dj, k + dk)) % 12];
+                t.powi(4) * (gx * cx + gy * cy + gz * cz)
+            })
+            .sum();
+
+        32.0 * sum
+    }
+
+    fn hash1(&self, x: i32) -> u8 {
+        self.permutation[wrap(x)]
+    }
+
+    fn hash2(&self, x: i32, y: i32) -> u8 {
+        self.permutation[usize::from(self.hash1(x)) + wrap(y)]
+    }
+
+    fn hash3(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.permutation[usize::from(self.hash2(x, y)) + wrap(z)]
+    }
+
+    /// Maps a hash to a value in [-1, 1]
+    fn lattice_value(&self, hash: u8) -> f32 {
+        let _ = self;
+        f32::from(hash) / 255.0 * 2.0 - 1.0
+    }
+}
+
+/// Settings of a fractal Brownian motion, summing octaves of noise of
+/// increasing frequency and decreasing amplitude to add detail
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fbm {
+    pub octaves: u32,
+    /// The frequency multiplier between two octaves
+    pub lacunarity: f32,
+    /// The amplitude multiplier between two octaves
+    pub gain: f32,
+}
+
+impl Default for Fbm {
+    fn default() -> Self {
+        Self {
+            octaves: 4,
+            lacunarity: 2.0,
+            gain: 0.5,
+        }
+    }
+}
+
+impl Fbm {
+    /// Sums the octaves of a noise, given as a function of the frequency of
+    /// the octave. The sum is normalized to stay in the range of the noise.
+    ///
+    /// ```
+    /// use tuber_math::noise::{Fbm, Noise};
+    ///
+    /// let noise = Noise::new(42);
+    /// let (x, y) = (3.5, 1.25);
+    /// let height = Fbm::default().sample(|frequency| noise.perlin2(x * frequency, y * frequency));
+    /// assert!((-1.0..=1.0).contains(&height));
+    /// ```
+    #[must_use]
+    pub fn sample(&self, noise: impl Fn(f32) -> f32) -> f32 {
+        let mut frequency = 1.0;
+        let mut amplitude = 1.0;
+        let mut sum = 0.0;
+        let mut amplitude_sum = 0.0;
+        for _ in 0..self.octaves {
+            sum += noise(frequency) * amplitude;
+            amplitude_sum += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.gain;
+        }
+
+        if amplitude_sum == 0.0 {
+            return 0.0;
+        }
+        sum / amplitude_sum
+    }
+}
+
+/// Returns the lattice cell of a coordinate and the offset in the cell
+#[allow(clippy::cast_possible_truncation)]
+fn lattice(coordinate: f32) -> (i32, f32) {
+    let cell = coordinate.floor();
+    (cell as i32, coordinate - cell)
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn wrap(coordinate: i32) -> usize {
+    (coordinate & 255) as usize
+}
+
+fn gradient1(hash: u8) -> f32 {
+    if hash & 1 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// The quintic interpolation curve of improved Perlin noise, whose first
+/// and second derivatives are zero at 0 and 1
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> impl Iterator<Item = (f32, f32, f32)> {
+        #[allow(clippy::cast_precision_loss)]
+        (0..2000).map(|i| {
+            let i = i as f32;
+            (i * 0.173 - 40.0, i * 0.071 + 3.3, i * -0.119 + 7.7)
+        })
+    }
+
+    #[test]
+    fn same_seed_same_noise() {
+        let noise = Noise::new(7);
+
+        assert_eq!(noise, Noise::new(7));
+        assert_ne!(noise, Noise::new(8));
+        assert_float_absolute_eq!(noise.simplex2(1.3, 2.7), Noise::new(7).simplex2(1.3, 2.7));
+    }
+
+    #[test]
+    fn permutation() {
+        let noise = Noise::new(3);
+
+        let mut values = noise.permutation[..PERMUTATION_SIZE].to_vec();
+        values.sort_unstable();
+        assert!(values
+            .iter()
+            .enumerate()
+            .all(|(i, value)| usize::from(*value) == i));
+        assert_eq!(
+            noise.permutation[..PERMUTATION_SIZE],
+            noise.permutation[PERMUTATION_SIZE..]
+        );
+    }
+
+    #[test]
+    fn range() {
+        let noise = Noise::new(11);
+
+        for (x, y, z) in samples() {
+            for value in [
+                noise.value1(x),
+                noise.value2(x, y),
+                noise.value3(x, y, z),
+                noise.perlin1(x),
+                noise.perlin2(x, y),
+                noise.perlin3(x, y, z),
+                noise.simplex1(x),
+                noise.simplex2(x, y),
+                noise.simplex3(x, y, z),
+            ] {
+                assert!((-1.01..=1.01).contains(&value), "{value}");
+            }
+        }
+    }
+
+    #[test]
+    fn gradient_noise_is_zero_on_the_lattice() {
+        let noise = Noise::new(5);
+
+        assert_float_absolute_eq!(noise.perlin1(4.0), 0.0);
+        assert_float_absolute_eq!(noise.perlin2(-3.0, 2.0), 0.0);
+        assert_float_absolute_eq!(noise.perlin3(1.0, 0.0, -6.0), 0.0);
+    }
+
+    #[test]
+    fn continuity() {
+        let noise = Noise::new(13);
+
+        for (x, y, z) in samples() {
+            let step = 0.001;
+            assert!((noise.value3(x, y, z) - noise.value3(x + step, y, z)).abs() < 0.05);
+            assert!((noise.perlin3(x, y, z) - noise.perlin3(x, y + step, z)).abs() < 0.05);
+            assert!((noise.simplex3(x, y, z) - noise.simplex3(x, y, z + step)).abs() < 0.05);
+            assert!((noise.simplex2(x, y) - noise.simplex2(x + step, y)).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn fbm() {
+        let noise = Noise::new(17);
+        let fbm = Fbm {
+            octaves: 5,
+            ..Fbm::default()
+        };
+
+        for (x, y, _) in samples() {
+            let value = fbm.sample(|frequency| noise.simplex2(x * frequency, y * frequency));
+            assert!((-1.01..=1.01).contains(&value));
+        }
+        assert_float_absolute_eq!(
+            Fbm {
+                octaves: 0,
+                ..Fbm::default()
+            }
+            .sample(|_| 1.0),
+            0.0
+        );
+    }
+}
@@ -1,3 +1,6 @@
+//! The numeric traits tuber-math types are generic over, implemented for
+//! `i32`, `i64`, `f32` and `f64`.
+
 use std::fmt::Display;
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
@@ -11,6 +14,12 @@ impl Two for i32 {
     }
 }
 
+impl Two for i64 {
+    fn two() -> Self {
+        2
+    }
+}
+
 impl Two for f32 {
     fn two() -> Self {
         2.0
@@ -33,6 +42,12 @@ impl One for i32 {
     }
 }
 
+impl One for i64 {
+    fn one() -> Self {
+        1
+    }
+}
+
 impl One for f32 {
     fn one() -> Self {
         1.0
@@ -55,6 +70,12 @@ impl Zero for i32 {
     }
 }
 
+impl Zero for i64 {
+    fn zero() -> Self {
+        0
+    }
+}
+
 impl Zero for f32 {
     fn zero() -> Self {
         0.0
@@ -77,6 +98,12 @@ impl IsZero for i32 {
     }
 }
 
+impl IsZero for i64 {
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
 impl IsZero for f32 {
     fn is_zero(&self) -> bool {
         self.abs() < 0.000_000_01
@@ -121,19 +148,46 @@ pub trait NumericOps:
 
 impl NumericOps for i32 {}
 
+impl NumericOps for i64 {}
+
 impl NumericOps for f32 {}
 
 impl NumericOps for f64 {}
 
-pub trait Float: Display + Copy + Zero + One + Two + Pi + NumericOps {
+/// The numbers vectors and matrices can be made of, integers for grid
+/// coordinates or floating point numbers for everything else
+pub trait Scalar: Copy + PartialOrd + Zero + One + Two + NumericOps {}
+
+impl<T> Scalar for T where T: Copy + PartialOrd + Zero + One + Two + NumericOps {}
+
+pub trait Float: Display + Copy + PartialOrd + Zero + One + Two + Pi + NumericOps {
+    fn from_f64(value: f64) -> Self;
+    #[must_use]
     fn sin(self) -> Self;
+    #[must_use]
     fn cos(self) -> Self;
+    #[must_use]
+    fn asin(self) -> Self;
+    #[must_use]
+    fn acos(self) -> Self;
+    #[must_use]
+    fn atan2(self, other: Self) -> Self;
+    #[must_use]
+    fn abs(self) -> Self;
+    #[must_use]
     fn half(self) -> Self;
+    #[must_use]
     fn squared(self) -> Self;
+    #[must_use]
     fn sqrt(self) -> Self;
 }
 
 impl Float for f32 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
     fn sin(self) -> Self {
         self.sin()
     }
@@ -141,6 +195,22 @@ impl Float for f32 {
     fn cos(self) -> Self {
         self.cos()
     }
+
+    fn asin(self) -> Self {
+        self.asin()
+    }
+
+    fn acos(self) -> Self {
+        self.acos()
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        self.atan2(other)
+    }
+
+    fn abs(self) -> Self {
+        self.abs()
+    }
     fn half(self) -> Self {
         self * 0.5
     }
@@ -155,6 +225,10 @@ impl Float for f32 {
 }
 
 impl Float for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
     fn sin(self) -> Self {
         self.sin()
     }
@@ -162,6 +236,22 @@ impl Float for f64 {
     fn cos(self) -> Self {
         self.cos()
     }
+
+    fn asin(self) -> Self {
+        self.asin()
+    }
+
+    fn acos(self) -> Self {
+        self.acos()
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        self.atan2(other)
+    }
+
+    fn abs(self) -> Self {
+        self.abs()
+    }
     fn half(self) -> Self {
         self * 0.5
     }
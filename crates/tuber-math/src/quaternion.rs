@@ -1,7 +1,7 @@
 use std::fmt::{Debug, Display, Formatter};
 use std::ops::Mul;
 
-use crate::matrix::Matrix4;
+use crate::matrix::{Identity, Matrix4};
 use crate::number_traits::Float;
 use crate::vector::Vector3;
 
@@ -58,6 +58,152 @@ where
         Quaternion::new(w, Vector3::new(x, y, z))
     }
 
+    /// Returns the rotation taking the direction `from` to the direction `to`
+    /// along the shortest arc
+    pub fn rotation_between(from: &Vector3<T>, to: &Vector3<T>) -> Self {
+        let from = from.normalized();
+        let to = to.normalized();
+        let cos_angle = from.dot(&to);
+
+        if cos_angle < T::from_f64(-0.999_999) {
+            // Opposite directions, any axis orthogonal to them works
+            let mut axis = Vector3::new(T::one(), T::zero(), T::zero()).cross(&from);
+            if axis.norm() < T::from_f64(0.000_001) {
+                axis = Vector3::new(T::zero(), T::one(), T::zero()).cross(&from);
+            }
+            return Self::from_axis_angle(&axis.normalized(), T::pi());
+        }
+
+        Self::new(T::one() + cos_angle, from.cross(&to)).normalized()
+    }
+
+    /// Returns the rotation making the -Z axis point in a direction, with the
+    /// +Y axis as close as possible to `up`
+    pub fn look_at(direction: &Vector3<T>, up: &Vector3<T>) -> Self {
+        let back = -direction.normalized();
+        let right = up.cross(&back).normalized();
+        let up = back.cross(&right);
+
+        // The columns of the rotation matrix are right, up and back
+        #[rustfmt::skip]
+        let rotation = Matrix4::with_values([
+            right.x, up.x, back.x, T::zero(),
+            right.y, up.y, back.y, T::zero(),
+            right.z, up.z, back.z, T::zero(),
+            T::zero(), T::zero(), T::zero(), T::one(),
+        ]);
+        Self::from_rotation_matrix(&rotation)
+    }
+
+    /// Returns the rotation of a matrix whose upper left 3x3 part is a
+    /// rotation matrix
+    #[allow(clippy::similar_names)]
+    pub fn from_rotation_matrix(matrix: &Matrix4<T>) -> Self {
+        let (m00, m01, m02) = (matrix[0][0], matrix[0][1], matrix[0][2]);
+        let (m10, m11, m12) = (matrix[1][0], matrix[1][1], matrix[1][2]);
+        let (m20, m21, m22) = (matrix[2][0], matrix[2][1], matrix[2][2]);
+        let trace = m00 + m11 + m22;
+        let quaternion = if trace > T::zero() {
+            let s = (trace + T::one()).sqrt() * T::two();
+            Self::new(
+                s / T::from_f64(4.0),
+                Vector3::new((m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s),
+            )
+        } else if m00 > m11 && m00 > m22 {
+            let s = (T::one() + m00 - m11 - m22).sqrt() * T::two();
+            Self::new(
+                (m21 - m12) / s,
+                Vector3::new(s / T::from_f64(4.0), (m01 + m10) / s, (m02 + m20) / s),
+            )
+        } else if m11 > m22 {
+            let s = (T::one() + m11 - m00 - m22).sqrt() * T::two();
+            Self::new(
+                (m02 - m20) / s,
+                Vector3::new((m01 + m10) / s, s / T::from_f64(4.0), (m12 + m21) / s),
+            )
+        } else {
+            let s = (T::one() + m22 - m00 - m11).sqrt() * T::two();
+            Self::new(
+                (m10 - m01) / s,
+                Vector3::new((m02 + m20) / s, (m12 + m21) / s, s / T::from_f64(4.0)),
+            )
+        };
+        quaternion.normalized()
+    }
+
+    /// Returns the roll, pitch and yaw angles the quaternion can be created
+    /// from with `from_euler`
+    pub fn to_euler(&self) -> Vector3<T> {
+        let (w, x, y, z) = (
+            self.scalar_part,
+            self.vector_part.x,
+            self.vector_part.y,
+            self.vector_part.z,
+        );
+
+        let roll = (T::two() * (w * x + y * z)).atan2(T::one() - T::two() * (x * x + y * y));
+        let sin_pitch = T::two() * (w * y - z * x);
+        let pitch = if sin_pitch.abs() >= T::one() {
+            // Gimbal lock, the roll and yaw rotate around the same axis
+            if sin_pitch > T::zero() {
+                T::pi().half()
+            } else {
+                -T::pi().half()
+            }
+        } else {
+            sin_pitch.asin()
+        };
+        let yaw = (T::two() * (w * z + x * y)).atan2(T::one() - T::two() * (y * y + z * z));
+
+        Vector3::new(roll, pitch, yaw)
+    }
+
+    pub fn dot(&self, other: &Self) -> T {
+        self.scalar_part * other.scalar_part + self.vector_part.dot(&other.vector_part)
+    }
+
+    #[must_use]
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.scalar_part, -self.vector_part)
+    }
+
+    /// Rotates a vector by the quaternion, which must be normalized
+    pub fn rotate_vector(&self, vector: &Vector3<T>) -> Vector3<T> {
+        let rotated = self.clone() * Self::new(T::zero(), *vector) * self.conjugate();
+        rotated.vector_part
+    }
+
+    /// Interpolates along the shortest arc between two normalized
+    /// quaternions, at a constant angular speed
+    #[must_use]
+    pub fn slerp(&self, other: &Self, t: T) -> Self {
+        let mut other = other.clone();
+        let mut cos_angle = self.dot(&other);
+        if cos_angle < T::zero() {
+            other = Self::new(-other.scalar_part, -other.vector_part);
+            cos_angle = -cos_angle;
+        }
+
+        let (self_weight, other_weight) = if cos_angle > T::from_f64(0.9995) {
+            // Nearly identical rotations, a linear interpolation is precise
+            // enough and avoids dividing by a sine close to zero
+            (T::one() - t, t)
+        } else {
+            let angle = cos_angle.acos();
+            let sin_angle = angle.sin();
+            (
+                ((T::one() - t) * angle).sin() / sin_angle,
+                (t * angle).sin() / sin_angle,
+            )
+        };
+
+        Self::new(
+            self.scalar_part * self_weight + other.scalar_part * other_weight,
+            self.vector_part * self_weight + other.vector_part * other_weight,
+        )
+        .normalized()
+    }
+
     #[rustfmt::skip]
     #[allow(clippy::similar_names)]
     pub fn rotation_matrix(&self) -> Matrix4<T> {
@@ -116,6 +262,15 @@ where
     }
 }
 
+impl<T> Identity for Quaternion<T>
+where
+    T: Debug + Float,
+{
+    fn identity() -> Self {
+        Self::new(T::one(), Vector3::new(T::zero(), T::zero(), T::zero()))
+    }
+}
+
 impl<T> Display for Quaternion<T>
 where
     T: Debug + Float,
@@ -257,4 +412,73 @@ mod tests {
         assert_float_absolute_eq!(quaternion.vector_part.y, -0.38, 0.01);
         assert_float_absolute_eq!(quaternion.vector_part.z, 0.56, 0.01);
     }
+
+    fn assert_vector_eq(vector: Vector3<f32>, expected: Vector3<f32>) {
+        assert_float_absolute_eq!(vector.x, expected.x, 0.001);
+        assert_float_absolute_eq!(vector.y, expected.y, 0.001);
+        assert_float_absolute_eq!(vector.z, expected.z, 0.001);
+    }
+
+    #[test]
+    fn to_euler() {
+        let angles = Vector3::new(0.4, 1.3, -1.2);
+
+        assert_vector_eq(Quaternion::from_euler(&angles).to_euler(), angles);
+    }
+
+    #[test]
+    fn rotate_vector() {
+        let quaternion =
+            Quaternion::from_axis_angle(&Vector3::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_2);
+
+        assert_vector_eq(
+            quaternion.rotate_vector(&Vector3::new(1.0, 0.0, 0.0)),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn slerp() {
+        let axis = Vector3::new(0.0, 1.0, 0.0);
+        let start = Quaternion::identity();
+        let end = Quaternion::from_axis_angle(&axis, 2.0);
+
+        let halfway = start.slerp(&end, 0.5);
+        let expected = Quaternion::from_axis_angle(&axis, 1.0);
+
+        assert_float_absolute_eq!(halfway.dot(&expected).abs(), 1.0, 0.001);
+        assert_float_absolute_eq!(start.slerp(&end, 1.0).dot(&end).abs(), 1.0, 0.001);
+    }
+
+    #[test]
+    fn rotation_between() {
+        let from = Vector3::new(1.0, 0.0, 0.0);
+
+        for to in [
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        ] {
+            let rotation = Quaternion::rotation_between(&from, &to);
+            assert_vector_eq(rotation.rotate_vector(&from), to.normalized());
+        }
+    }
+
+    #[test]
+    fn look_at() {
+        let up = Vector3::new(0.0, 1.0, 0.0);
+
+        for direction in [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(-1.0, 0.5, -2.0),
+        ] {
+            let rotation = Quaternion::look_at(&direction, &up);
+            assert_vector_eq(
+                rotation.rotate_vector(&Vector3::new(0.0, 0.0, -1.0)),
+                direction.normalized(),
+            );
+            assert!(rotation.rotate_vector(&up).y > 0.0);
+        }
+    }
 }
@@ -1,15 +1,21 @@
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 
-use crate::number_traits::{Float, Zero};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::number_traits::{Float, Scalar, Zero};
 
 pub type Vector3f = Vector3<f32>;
 pub type Vector4f = Vector4<f32>;
+pub type Vec2i = Vector2<i32>;
+pub type Vec3i = Vector3<i32>;
+pub type Vec2d = Vector2<f64>;
+pub type Vec3d = Vector3<f64>;
 
 macro_rules! struct_vec {
     ($name:ident : $display_fmt:literal, ($($dim:ident : $TY:ty => $idx:tt,)*)) => {
         #[must_use]
-        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
         pub struct $name<T = f32> {
             $(pub $dim: T,)*
         }
@@ -20,6 +26,14 @@ macro_rules! struct_vec {
                     $($dim),*
                 }
             }
+
+            /// Applies a function to each component, such as a conversion to
+            /// another scalar type
+            pub fn map<U>(self, f: impl Fn(T) -> U) -> $name<U> {
+                $name {
+                    $($dim: f(self.$dim)),*
+                }
+            }
         }
 
         impl<T> $name<T>
@@ -40,6 +54,65 @@ macro_rules! struct_vec {
                 normalized.normalize();
                 normalized
             }
+
+            /// Returns the length of the vector, the same as its norm
+            pub fn length(&self) -> T {
+                self.norm()
+            }
+
+            /// Returns the component of the vector along another vector, the
+            /// projection onto the zero vector being the zero vector
+            pub fn project_onto(&self, other: &Self) -> Self {
+                let other_length_squared = other.length_squared();
+                if other_length_squared <= T::zero() {
+                    return Self::default();
+                }
+
+                *other * (self.dot(other) / other_length_squared)
+            }
+
+            /// Returns the vector bounced off a surface of normalized normal
+            /// `normal`
+            pub fn reflect(&self, normal: &Self) -> Self {
+                *self - *normal * (T::two() * self.dot(normal))
+            }
+        }
+
+        impl<T> $name<T>
+        where T: Scalar {
+            pub fn dot(&self, other: &Self) -> T {
+                let mut dot = T::zero();
+                $(dot += self.$dim * other.$dim;)*
+                dot
+            }
+
+            /// Returns the squared length of the vector, cheaper to compute
+            /// than the length when comparing lengths
+            pub fn length_squared(&self) -> T {
+                self.dot(self)
+            }
+        }
+
+        impl<T> $name<T>
+        where T: Copy + PartialOrd {
+            /// Returns the component-wise minimum of two vectors
+            pub fn min(&self, other: &Self) -> Self {
+                Self {
+                    $($dim: if other.$dim < self.$dim { other.$dim } else { self.$dim }),*
+                }
+            }
+
+            /// Returns the component-wise maximum of two vectors
+            pub fn max(&self, other: &Self) -> Self {
+                Self {
+                    $($dim: if other.$dim > self.$dim { other.$dim } else { self.$dim }),*
+                }
+            }
+
+            /// Clamps each component between the components of `min` and `max`
+            pub fn clamp(&self, min: &Self, max: &Self) -> Self {
+                self.max(min).min(max)
+            }
         }
 
         impl<T> Default for $name<T>
@@ -186,6 +259,56 @@ struct_vec!(Vector2: "({}, {})", (x: T => 0, y: T => 1,));
 struct_vec!(Vector3: "({}, {}, {})", (x: T => 0, y: T => 1, z: T => 2,));
 struct_vec!(Vector4: "({}, {}, {}, {})", (x: T => 0, y: T => 1, z: T => 2, w: T => 3,));
 
+impl<T> Vector2<T>
+where
+    T: Scalar,
+{
+    /// Returns the z component of the cross product of two vectors of the XY
+    /// plane, positive when `other` is counterclockwise from the vector
+    pub fn cross(&self, other: &Self) -> T {
+        self.x * other.y - self.y * other.x
+    }
+}
+
+impl<T> Vector3<T>
+where
+    T: Scalar,
+{
+    pub fn cross(&self, other: &Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+}
+
+impl<T> Vector3<T>
+where
+    T: Copy,
+{
+    pub fn xy(&self) -> Vector2<T> {
+        Vector2::new(self.x, self.y)
+    }
+
+    pub fn xz(&self) -> Vector2<T> {
+        Vector2::new(self.x, self.z)
+    }
+}
+
+impl<T> Vector4<T>
+where
+    T: Copy,
+{
+    pub fn xy(&self) -> Vector2<T> {
+        Vector2::new(self.x, self.y)
+    }
+
+    pub fn xyz(&self) -> Vector3<T> {
+        Vector3::new(self.x, self.y, self.z)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -340,6 +463,19 @@ mod tests {
         assert_float_absolute_eq!(normalized.z, 0.80, 0.01);
     }
 
+    #[test]
+    fn dot_and_cross() {
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let y = Vector3::new(0.0, 1.0, 0.0);
+
+        assert_float_absolute_eq!(x.dot(&y), 0.0);
+        assert_float_absolute_eq!(
+            Vector3::new(1.0, 2.0, 3.0).dot(&Vector3::new(4.0, 5.0, 6.0)),
+            32.0
+        );
+        assert_eq!(x.cross(&y), Vector3::new(0.0, 0.0, 1.0));
+    }
+
     #[test]
     fn default() {
         let vector = Vector4::<f32>::default();
@@ -371,4 +507,84 @@ mod tests {
         assert_eq!(tuple.2, 2);
         assert_eq!(tuple.3, 3);
     }
+
+    #[test]
+    fn length_and_projection() {
+        let v = Vector2::new(3.0, 4.0);
+
+        assert_float_absolute_eq!(v.length(), 5.0);
+        assert_float_absolute_eq!(v.length_squared(), 25.0);
+        assert_eq!(
+            v.project_onto(&Vector2::new(2.0, 0.0)),
+            Vector2::new(3.0, 0.0)
+        );
+        assert_eq!(
+            v.project_onto(&Vector2::new(0.0, 0.0)),
+            Vector2::new(0.0, 0.0)
+        );
+        assert_float_absolute_eq!(v.cross(&Vector2::new(1.0, 0.0)), -4.0);
+    }
+
+    #[test]
+    fn reflect() {
+        let velocity = Vector2::new(2.0, -3.0);
+
+        let bounced = velocity.reflect(&Vector2::new(0.0, 1.0));
+
+        assert_eq!(bounced, Vector2::new(2.0, 3.0));
+    }
+
+    #[test]
+    fn min_max_clamp() {
+        let a = Vector3::new(1, 5, -2);
+        let b = Vector3::new(3, 2, -4);
+
+        assert_eq!(a.min(&b), Vector3::new(1, 2, -4));
+        assert_eq!(a.max(&b), Vector3::new(3, 5, -2));
+        assert_eq!(
+            Vector3::new(-5, 3, 10).clamp(&Vector3::new(0, 0, 0), &Vector3::new(4, 4, 4)),
+            Vector3::new(0, 3, 4)
+        );
+    }
+
+    #[test]
+    fn swizzles() {
+        let v = Vector4::new(1, 2, 3, 4);
+
+        assert_eq!(v.xy(), Vector2::new(1, 2));
+        assert_eq!(v.xyz().xz(), Vector2::new(1, 3));
+    }
+
+    #[test]
+    fn integer_vectors() {
+        let tile = Vec2i::new(3, -2) + Vec2i::new(1, 1) * 2;
+
+        assert_eq!(tile, Vec2i::new(5, 0));
+        assert_eq!(tile.dot(&Vec2i::new(2, 7)), 10);
+        assert_eq!(
+            Vec3i::new(1, 0, 0).cross(&Vec3i::new(0, 1, 0)),
+            Vec3i::new(0, 0, 1)
+        );
+        assert_eq!(
+            Vector2::<i64>::new(1 << 20, 1 << 20).length_squared(),
+            1 << 41
+        );
+    }
+
+    #[test]
+    fn double_precision_vectors() {
+        let position = Vec2d::new(1.0e8, 0.0) + Vec2d::new(1.0e-3, 0.0);
+
+        assert!((position.x - 100_000_000.001).abs() < 1.0e-6);
+        assert_float_absolute_eq!(Vec3d::new(2.0, 3.0, 6.0).length(), 7.0);
+    }
+
+    #[test]
+    fn map() {
+        let tile = Vec2i::new(3, 4);
+
+        let center = tile.map(f64::from) + Vec2d::new(0.5, 0.5);
+
+        assert_eq!(center, Vec2d::new(3.5, 4.5));
+    }
 }
@@ -0,0 +1,27 @@
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::module_name_repetitions)]
+
+use tuber_core::CoreError;
+
+pub mod replication;
+pub mod transport;
+
+pub type NetworkResult<T> = Result<T, NetworkError>;
+
+#[derive(Debug)]
+pub enum NetworkError {
+    SocketError(std::io::Error),
+    PacketSerializationError(serde_json::Error),
+    /// The size of the serialized packet, larger than a datagram can hold
+    PacketTooLarge(usize),
+    ReplicationError(CoreError),
+}
+
+impl From<CoreError> for NetworkError {
+    fn from(error: CoreError) -> Self {
+        Self::ReplicationError(error)
+    }
+}
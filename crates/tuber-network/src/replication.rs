@@ -0,0 +1,331 @@
+//! Replication of the entities marked with [`Replicated`]: the server captures
+//! snapshots of their registered components and sends them as deltas from the
+//! last snapshot each client acknowledged, which the clients apply to their
+//! replica of the world.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use tuber_core::registry::ComponentRegistry;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::EntityIndex;
+
+use crate::NetworkResult;
+
+/// Identifies a replicated entity on the server and on every client
+pub type NetworkId = u64;
+
+type EntityState = BTreeMap<String, Value>;
+
+/// Marks an entity whose registered components are replicated to the clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replicated(pub NetworkId);
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub entities: BTreeMap<NetworkId, EntityState>,
+}
+
+impl Snapshot {
+    /// Captures the registered components of the replicated entities
+    pub fn capture(ecs: &Ecs, registry: &ComponentRegistry, tick: u64) -> NetworkResult<Self> {
+        let replicated_entities: Vec<(EntityIndex, NetworkId)> = ecs
+            .query::<(&Replicated,)>()
+            .map(|(entity, (replicated,))| (entity, replicated.0))
+            .collect();
+
+        let mut entities = BTreeMap::new();
+        for (entity, network_id) in replicated_entities {
+            entities.insert(network_id, registry.serialize_components(ecs, entity)?);
+        }
+
+        Ok(Self { tick, entities })
+    }
+
+    /// Returns the changes from a base snapshot to this one, or this whole
+    /// snapshot if there is no base
+    #[must_use]
+    pub fn delta_from(&self, base: Option<&Snapshot>) -> SnapshotDelta {
+        let empty_snapshot = Snapshot::default();
+        let base_snapshot = base.unwrap_or(&empty_snapshot);
+
+        let mut delta = SnapshotDelta {
+            tick: self.tick,
+            base_tick: base.map(|base| base.tick),
+            ..SnapshotDelta::default()
+        };
+        for (network_id, components) in &self.entities {
+            let base_components = base_snapshot.entities.get(network_id);
+            let changed_components: EntityState = components
+                .iter()
+                .filter(|&(name, value)| {
+                    base_components.and_then(|base_components| base_components.get(name))
+                        != Some(value)
+                })
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            if !changed_components.is_empty() || base_components.is_none() {
+                delta
+                    .changed_components
+                    .insert(*network_id, changed_components);
+            }
+
+            let removed_components: Vec<String> = base_components
+                .into_iter()
+                .flat_map(BTreeMap::keys)
+                .filter(|name| !components.contains_key(*name))
+                .cloned()
+                .collect();
+            if !removed_components.is_empty() {
+                delta
+                    .removed_components
+                    .insert(*network_id, removed_components);
+            }
+        }
+
+        delta.removed_entities = base_snapshot
+            .entities
+            .keys()
+            .filter(|network_id| !self.entities.contains_key(network_id))
+            .copied()
+            .collect();
+        delta
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    pub tick: u64,
+    /// The tick of the snapshot the delta applies to, none for a whole
+    /// snapshot
+    pub base_tick: Option<u64>,
+    /// The components added or changed since the base snapshot, entities
+    /// created since then are present even without components
+    pub changed_components: BTreeMap<NetworkId, EntityState>,
+    pub removed_components: BTreeMap<NetworkId, Vec<String>>,
+    pub removed_entities: Vec<NetworkId>,
+}
+
+impl SnapshotDelta {
+    /// Rebuilds the snapshot the delta was computed from, given its base
+    #[must_use]
+    pub fn apply_to(&self, base: &Snapshot) -> Snapshot {
+        let mut snapshot = Snapshot {
+            tick: self.tick,
+            entities: base.entities.clone(),
+        };
+
+        for network_id in &self.removed_entities {
+            snapshot.entities.remove(network_id);
+        }
+        for (network_id, removed_components) in &self.removed_components {
+            if let Some(components) = snapshot.entities.get_mut(network_id) {
+                for name in removed_components {
+                    components.remove(name);
+                }
+            }
+        }
+        for (network_id, changed_components) in &self.changed_components {
+            snapshot
+                .entities
+                .entry(*network_id)
+                .or_default()
+                .extend(changed_components.clone());
+        }
+
+        snapshot
+    }
+}
+
+/// The number of applied snapshots a client keeps as delta bases, as the
+/// server keeps sending deltas from an older snapshot until the client's
+/// acknowledgement reaches it
+const REPLICA_HISTORY_SIZE: usize = 64;
+
+/// The replicated entities of a client, updated from the snapshot deltas
+/// sent by the server
+#[derive(Debug, Default)]
+pub struct Replica {
+    history: VecDeque<Snapshot>,
+    entities: HashMap<NetworkId, EntityIndex>,
+}
+
+impl Replica {
+    /// Returns the tick of the last applied snapshot
+    #[must_use]
+    pub fn tick(&self) -> Option<u64> {
+        self.history.back().map(|snapshot| snapshot.tick)
+    }
+
+    /// Returns the local entity of a replicated entity
+    #[must_use]
+    pub fn entity(&self, network_id: NetworkId) -> Option<EntityIndex> {
+        self.entities.get(&network_id).copied()
+    }
+
+    /// Applies a delta to the ECS, returns false if the delta is older than
+    /// the last applied snapshot or its base is no longer known
+    pub fn apply(
+        &mut self,
+        ecs: &mut Ecs,
+        registry: &ComponentRegistry,
+        delta: &SnapshotDelta,
+    ) -> NetworkResult<bool> {
+        if self.tick().is_some_and(|tick| delta.tick <= tick) {
+            return Ok(false);
+        }
+
+        let snapshot = if let Some(base_tick) = delta.base_tick {
+            let Some(base) = self
+                .history
+                .iter()
+                .find(|snapshot| snapshot.tick == base_tick)
+            else {
+                return Ok(false);
+            };
+            delta.apply_to(base)
+        } else {
+            delta.apply_to(&Snapshot::default())
+        };
+
+        let changes = snapshot.delta_from(self.history.back());
+        let removed_entities: Vec<EntityIndex> = changes
+            .removed_entities
+            .iter()
+            .filter_map(|network_id| self.entities.remove(network_id))
+            .collect();
+        ecs.delete_by_ids(&removed_entities);
+        for (network_id, removed_components) in &changes.removed_components {
+            let entity = self.entities[network_id];
+            for name in removed_components {
+                registry.remove_component(ecs, entity, name)?;
+            }
+        }
+        for (network_id, changed_components) in changes.changed_components {
+            let entity = *self
+                .entities
+                .entry(network_id)
+                .or_insert_with(|| ecs.insert((Replicated(network_id),)));
+            for (name, value) in changed_components {
+                registry.insert_component(ecs, entity, &name, value)?;
+            }
+        }
+
+        if self.history.len() >= REPLICA_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(snapshot);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Health(u32);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Shield(u32);
+
+    fn registry() -> ComponentRegistry {
+        let mut registry = ComponentRegistry::new();
+        registry.register_component::<Health>("Health");
+        registry.register_component::<Shield>("Shield");
+        registry
+    }
+
+    #[test]
+    fn capture_replicated_entities() {
+        let mut ecs = Ecs::default();
+        ecs.insert((Replicated(7), Health(3)));
+        ecs.insert((Health(5),));
+
+        let snapshot = Snapshot::capture(&ecs, &registry(), 1).unwrap();
+
+        assert_eq!(snapshot.entities.len(), 1);
+        assert_eq!(snapshot.entities[&7]["Health"], json!(3));
+    }
+
+    #[test]
+    fn delta() {
+        let base = Snapshot {
+            tick: 1,
+            entities: BTreeMap::from([
+                (1, BTreeMap::from([("Health".into(), json!(3))])),
+                (
+                    2,
+                    BTreeMap::from([("Health".into(), json!(5)), ("Shield".into(), json!(1))]),
+                ),
+                (3, BTreeMap::new()),
+            ]),
+        };
+        let snapshot = Snapshot {
+            tick: 2,
+            entities: BTreeMap::from([
+                (1, BTreeMap::from([("Health".into(), json!(3))])),
+                (2, BTreeMap::from([("Health".into(), json!(4))])),
+                (4, BTreeMap::from([("Health".into(), json!(9))])),
+            ]),
+        };
+
+        let delta = snapshot.delta_from(Some(&base));
+
+        assert_eq!(delta.base_tick, Some(1));
+        assert_eq!(
+            delta.changed_components,
+            BTreeMap::from([
+                (2, BTreeMap::from([("Health".into(), json!(4))])),
+                (4, BTreeMap::from([("Health".into(), json!(9))])),
+            ])
+        );
+        assert_eq!(
+            delta.removed_components,
+            BTreeMap::from([(2, vec!["Shield".to_string()])])
+        );
+        assert_eq!(delta.removed_entities, vec![3]);
+        assert_eq!(delta.apply_to(&base), snapshot);
+    }
+
+    #[test]
+    fn replica_apply() {
+        let registry = registry();
+        let mut server_ecs = Ecs::default();
+        let player = server_ecs.insert((Replicated(1), Health(3), Shield(2)));
+        server_ecs.insert((Replicated(2), Health(8)));
+        let first_snapshot = Snapshot::capture(&server_ecs, &registry, 1).unwrap();
+        let mut replica = Replica::default();
+        let mut client_ecs = Ecs::default();
+
+        assert!(replica
+            .apply(&mut client_ecs, &registry, &first_snapshot.delta_from(None))
+            .unwrap());
+        server_ecs.remove_component::<Shield>(player);
+        server_ecs.add_component(Health(1), player);
+        server_ecs.delete_by_ids(&[1]);
+        let second_snapshot = Snapshot::capture(&server_ecs, &registry, 2).unwrap();
+        let delta = second_snapshot.delta_from(Some(&first_snapshot));
+        assert!(replica.apply(&mut client_ecs, &registry, &delta).unwrap());
+        assert!(!replica.apply(&mut client_ecs, &registry, &delta).unwrap());
+        server_ecs.add_component(Health(0), player);
+        let third_snapshot = Snapshot::capture(&server_ecs, &registry, 3).unwrap();
+        let delta = third_snapshot.delta_from(Some(&first_snapshot));
+        assert!(replica.apply(&mut client_ecs, &registry, &delta).unwrap());
+
+        let client_player = replica.entity(1).unwrap();
+        let (_, (health,)) = client_ecs
+            .query_one_by_id::<(&Health,)>(client_player)
+            .unwrap();
+        assert_eq!(*health, Health(0));
+        assert!(client_ecs
+            .query_one_by_id::<(&Shield,)>(client_player)
+            .is_none());
+        assert!(replica.entity(2).is_none());
+        assert_eq!(replica.tick(), Some(3));
+    }
+}
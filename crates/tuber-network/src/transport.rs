@@ -0,0 +1,298 @@
+//! A server and a client exchanging snapshot deltas over UDP.
+//!
+//! Lost snapshots aren't resent: the server always sends the changes since the
+//! last snapshot the client acknowledged, so the next snapshot that gets
+//! through contains them.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use serde_derive::{Deserialize, Serialize};
+use tuber_core::registry::ComponentRegistry;
+use tuber_ecs::ecs::Ecs;
+
+use crate::replication::{Replica, Snapshot, SnapshotDelta};
+use crate::{NetworkError, NetworkResult};
+
+/// The largest payload of a UDP datagram
+const MAX_PACKET_SIZE: usize = 65_507;
+/// The number of sent snapshots kept as delta bases
+const SNAPSHOT_HISTORY_SIZE: usize = 64;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Packet {
+    Connect,
+    Disconnect,
+    Snapshot(SnapshotDelta),
+    Ack { tick: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerEvent {
+    ClientConnected(SocketAddr),
+    ClientDisconnected(SocketAddr),
+}
+
+struct ConnectedClient {
+    acked_tick: Option<u64>,
+}
+
+pub struct Server {
+    socket: UdpSocket,
+    clients: HashMap<SocketAddr, ConnectedClient>,
+    history: VecDeque<Snapshot>,
+    receive_buffer: Vec<u8>,
+}
+
+impl Server {
+    pub fn bind(address: impl ToSocketAddrs) -> NetworkResult<Self> {
+        let socket = UdpSocket::bind(address).map_err(NetworkError::SocketError)?;
+        socket
+            .set_nonblocking(true)
+            .map_err(NetworkError::SocketError)?;
+
+        Ok(Self {
+            socket,
+            clients: HashMap::new(),
+            history: VecDeque::new(),
+            receive_buffer: vec![0; MAX_PACKET_SIZE],
+        })
+    }
+
+    pub fn local_address(&self) -> NetworkResult<SocketAddr> {
+        self.socket.local_addr().map_err(NetworkError::SocketError)
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = &SocketAddr> {
+        self.clients.keys()
+    }
+
+    /// Handles the packets received since the last call
+    pub fn receive(&mut self) -> NetworkResult<Vec<ServerEvent>> {
+        let mut events = vec![];
+        while let Some((packet, address)) = receive_packet(&self.socket, &mut self.receive_buffer)?
+        {
+            match packet {
+                Packet::Connect => {
+                    if let Entry::Vacant(entry) = self.clients.entry(address) {
+                        log::info!("Client connected from {address}");
+                        entry.insert(ConnectedClient { acked_tick: None });
+                        events.push(ServerEvent::ClientConnected(address));
+                    }
+                }
+                Packet::Disconnect => {
+                    if self.clients.remove(&address).is_some() {
+                        log::info!("Client disconnected from {address}");
+                        events.push(ServerEvent::ClientDisconnected(address));
+                    }
+                }
+                Packet::Ack { tick } => {
+                    if let Some(client) = self.clients.get_mut(&address) {
+                        client.acked_tick = client.acked_tick.max(Some(tick));
+                    }
+                }
+                Packet::Snapshot(_) => {
+                    log::warn!("Ignoring a snapshot sent to the server by {address}");
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Sends a snapshot to every client, as a delta from the last snapshot the
+    /// client acknowledged. Failing to send to a client is logged and doesn't
+    /// prevent sending to the others, the client getting the changes with the
+    /// next snapshot that gets through.
+    pub fn send_snapshot(&mut self, snapshot: Snapshot) {
+        for (address, client) in &self.clients {
+            let base = client.acked_tick.and_then(|acked_tick| {
+                self.history
+                    .iter()
+                    .find(|snapshot| snapshot.tick == acked_tick)
+            });
+            let packet = Packet::Snapshot(snapshot.delta_from(base));
+            if let Err(e) = send_packet(&self.socket, &packet, *address) {
+                log::warn!(
+                    "Couldn't send snapshot {} to {address}: {e:?}",
+                    snapshot.tick
+                );
+            }
+        }
+
+        if self.history.len() >= SNAPSHOT_HISTORY_SIZE {
+            self.history.pop_front();
+        }
+        self.history.push_back(snapshot);
+    }
+}
+
+pub struct Client {
+    socket: UdpSocket,
+    server_address: SocketAddr,
+    replica: Replica,
+    receive_buffer: Vec<u8>,
+}
+
+impl Client {
+    pub fn connect(server_address: SocketAddr) -> NetworkResult<Self> {
+        let local_address = if server_address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local_address).map_err(NetworkError::SocketError)?;
+        socket
+            .set_nonblocking(true)
+            .map_err(NetworkError::SocketError)?;
+        send_packet(&socket, &Packet::Connect, server_address)?;
+
+        Ok(Self {
+            socket,
+            server_address,
+            replica: Replica::default(),
+            receive_buffer: vec![0; MAX_PACKET_SIZE],
+        })
+    }
+
+    #[must_use]
+    pub fn replica(&self) -> &Replica {
+        &self.replica
+    }
+
+    /// Applies the snapshots received since the last call to the ECS and
+    /// acknowledges them. Until the first snapshot arrives, the connection
+    /// request is sent again in case it was lost.
+    pub fn receive(&mut self, ecs: &mut Ecs, registry: &ComponentRegistry) -> NetworkResult<()> {
+        let mut applied_snapshot = false;
+        while let Some((packet, address)) = receive_packet(&self.socket, &mut self.receive_buffer)?
+        {
+            if address != self.server_address {
+                continue;
+            }
+
+            if let Packet::Snapshot(delta) = packet {
+                applied_snapshot |= self.replica.apply(ecs, registry, &delta)?;
+            }
+        }
+
+        if applied_snapshot {
+            if let Some(tick) = self.replica.tick() {
+                send_packet(&self.socket, &Packet::Ack { tick }, self.server_address)?;
+            }
+        } else if self.replica.tick().is_none() {
+            send_packet(&self.socket, &Packet::Connect, self.server_address)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn disconnect(self) -> NetworkResult<()> {
+        send_packet(&self.socket, &Packet::Disconnect, self.server_address)
+    }
+}
+
+fn send_packet(socket: &UdpSocket, packet: &Packet, address: SocketAddr) -> NetworkResult<()> {
+    let bytes = serde_json::to_vec(packet).map_err(NetworkError::PacketSerializationError)?;
+    if bytes.len() > MAX_PACKET_SIZE {
+        return Err(NetworkError::PacketTooLarge(bytes.len()));
+    }
+
+    socket
+        .send_to(&bytes, address)
+        .map_err(NetworkError::SocketError)?;
+    Ok(())
+}
+
+/// Returns the next valid packet, or none if no packet is waiting. The buffer
+/// must be able to hold the largest packet.
+fn receive_packet(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+) -> NetworkResult<Option<(Packet, SocketAddr)>> {
+    loop {
+        let (size, address) = match socket.recv_from(buffer) {
+            Ok(received) => received,
+            Err(error) if error.kind() == ErrorKind::WouldBlock => return Ok(None),
+            // Windows reports unreachable peers of previous sends on receive
+            Err(error) if error.kind() == ErrorKind::ConnectionReset => continue,
+            Err(error) => return Err(NetworkError::SocketError(error)),
+        };
+
+        match serde_json::from_slice(&buffer[..size]) {
+            Ok(packet) => return Ok(Some((packet, address))),
+            Err(error) => log::warn!("Ignoring an invalid packet from {address}: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replication::Replicated;
+    use serde_derive::{Deserialize, Serialize};
+    use std::time::Duration;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Score(u32);
+
+    #[test]
+    fn replicate_over_loopback() {
+        let mut registry = ComponentRegistry::new();
+        registry.register_component::<Score>("Score");
+        let mut server_ecs = Ecs::default();
+        let entity = server_ecs.insert((Replicated(1), Score(0)));
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        let mut client = Client::connect(server.local_address().unwrap()).unwrap();
+        let mut client_ecs = Ecs::default();
+
+        for tick in 1..=200 {
+            server.receive().unwrap();
+            server_ecs.add_component(Score(u32::try_from(tick).unwrap()), entity);
+            let snapshot = Snapshot::capture(&server_ecs, &registry, tick).unwrap();
+            server.send_snapshot(snapshot);
+            std::thread::sleep(Duration::from_millis(5));
+            client.receive(&mut client_ecs, &registry).unwrap();
+
+            if client.replica().tick() == Some(tick) && tick > 10 {
+                break;
+            }
+        }
+
+        let tick = client.replica().tick().unwrap();
+        let client_entity = client.replica().entity(1).unwrap();
+        let (_, (score,)) = client_ecs
+            .query_one_by_id::<(&Score,)>(client_entity)
+            .unwrap();
+        assert_eq!(*score, Score(u32::try_from(tick).unwrap()));
+        assert_eq!(server.clients().count(), 1);
+        client.disconnect().unwrap();
+    }
+
+    #[test]
+    fn send_snapshot_despite_failing_client() {
+        let registry = ComponentRegistry::new();
+        let ecs = Ecs::default();
+        let mut server = Server::bind("127.0.0.1:0").unwrap();
+        let mut client = Client::connect(server.local_address().unwrap()).unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+        server.receive().unwrap();
+        // An IPv4 socket can't send to an IPv6 address
+        server.clients.insert(
+            "[::1]:9".parse().unwrap(),
+            ConnectedClient { acked_tick: None },
+        );
+
+        for tick in 1..=2 {
+            server.send_snapshot(Snapshot::capture(&ecs, &registry, tick).unwrap());
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        let mut client_ecs = Ecs::default();
+        client.receive(&mut client_ecs, &registry).unwrap();
+
+        assert_eq!(server.history.len(), 2);
+        assert_eq!(client.replica().tick(), Some(2));
+    }
+}
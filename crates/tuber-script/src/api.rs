@@ -0,0 +1,214 @@
+//! The functions scripts call to access the entities, their transform and the
+//! input.
+//!
+//! The functions are registered once in the script engine, so they reach the
+//! Ecs and the input state through a [`SharedWorld`] the runtime lends them to
+//! while the scripts run.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FLOAT, INT};
+use serde_json::Value;
+
+use tuber_core::input::keyboard::Key;
+use tuber_core::input::{Input, Keymap, State as InputState};
+use tuber_core::transform::Transform;
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::EntityIndex;
+
+/// An entity, as seen by scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptEntity(pub EntityIndex);
+
+struct World {
+    ecs: Ecs,
+    input_state: InputState,
+}
+
+/// The Ecs and the input state, shared with the functions registered in the
+/// script engine while the scripts run
+#[derive(Clone, Default)]
+pub(crate) struct SharedWorld(Rc<RefCell<Option<World>>>);
+
+impl SharedWorld {
+    /// Moves the Ecs and the input state into the shared world while running
+    /// the given closure
+    pub(crate) fn lend<R>(
+        &self,
+        ecs: &mut Ecs,
+        input_state: &mut InputState,
+        run: impl FnOnce() -> R,
+    ) -> R {
+        *self.0.borrow_mut() = Some(World {
+            ecs: std::mem::take(ecs),
+            input_state: std::mem::replace(input_state, InputState::new(Keymap::default())),
+        });
+        let result = run();
+        let world = self.0.borrow_mut().take().expect("The world is lent");
+        *ecs = world.ecs;
+        *input_state = world.input_state;
+        result
+    }
+
+    fn with<R>(&self, access: impl FnOnce(&mut World) -> R) -> R {
+        let mut world = self.0.borrow_mut();
+        access(
+            world
+                .as_mut()
+                .expect("Scripts only run while the world is lent"),
+        )
+    }
+
+    fn transform(&self, entity: ScriptEntity) -> Transform {
+        self.with(|world| {
+            world
+                .ecs
+                .query_one_by_id::<(&Transform,)>(entity.0)
+                .map(|(_, (transform,))| *transform)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Updates the transform of the entity, adding one if it has none
+    fn update_transform(&self, entity: ScriptEntity, update: impl FnOnce(&mut Transform)) {
+        self.with(|world| {
+            if let Some((_, (mut transform,))) =
+                world.ecs.query_one_by_id::<(&mut Transform,)>(entity.0)
+            {
+                update(&mut transform);
+                return;
+            }
+
+            let mut transform = Transform::default();
+            update(&mut transform);
+            world.ecs.add_component(transform, entity.0);
+        });
+    }
+}
+
+/// Registers the functions of the scripting API in the engine
+pub(crate) fn register(engine: &mut Engine, world: &SharedWorld) {
+    register_entity(engine, world);
+    register_transform(engine, world);
+    register_input(engine, world);
+}
+
+fn register_entity(engine: &mut Engine, world: &SharedWorld) {
+    engine.register_type_with_name::<ScriptEntity>("Entity");
+    #[allow(clippy::cast_possible_wrap)]
+    engine.register_get("id", |entity: &mut ScriptEntity| entity.0 as INT);
+
+    // `spawn` is a reserved keyword of Rhai
+    let create_world = world.clone();
+    engine.register_fn("create_entity", move || {
+        create_world.with(|world| ScriptEntity(world.ecs.insert((Transform::default(),))))
+    });
+    let delete_world = world.clone();
+    engine.register_fn("delete_entity", move |entity: ScriptEntity| {
+        delete_world.with(|world| world.ecs.delete_by_ids(&[entity.0]));
+    });
+}
+
+fn register_transform(engine: &mut Engine, world: &SharedWorld) {
+    register_transform_property(
+        engine,
+        world,
+        "x",
+        |transform| transform.translation.x,
+        |transform, x| transform.translation.x = x,
+    );
+    register_transform_property(
+        engine,
+        world,
+        "y",
+        |transform| transform.translation.y,
+        |transform, y| transform.translation.y = y,
+    );
+    register_transform_property(
+        engine,
+        world,
+        "z",
+        |transform| transform.translation.z,
+        |transform, z| transform.translation.z = z,
+    );
+    register_transform_property(
+        engine,
+        world,
+        "angle",
+        |transform| transform.angle.z,
+        |transform, angle| transform.angle.z = angle,
+    );
+    register_transform_property(
+        engine,
+        world,
+        "scale_x",
+        |transform| transform.scale.x,
+        |transform, scale_x| transform.scale.x = scale_x,
+    );
+    register_transform_property(
+        engine,
+        world,
+        "scale_y",
+        |transform| transform.scale.y,
+        |transform, scale_y| transform.scale.y = scale_y,
+    );
+}
+
+/// Registers a property of the entities reading and writing a field of their
+/// transform
+fn register_transform_property(
+    engine: &mut Engine,
+    world: &SharedWorld,
+    name: &str,
+    get: fn(&Transform) -> f32,
+    set: fn(&mut Transform, f32),
+) {
+    let get_world = world.clone();
+    let set_world = world.clone();
+    engine.register_get_set(
+        name,
+        move |entity: &mut ScriptEntity| FLOAT::from(get(&get_world.transform(*entity))),
+        move |entity: &mut ScriptEntity, value: FLOAT| {
+            #[allow(clippy::cast_possible_truncation)]
+            let value = value as f32;
+            set_world.update_transform(*entity, |transform| set(transform, value));
+        },
+    );
+}
+
+fn register_input(engine: &mut Engine, world: &SharedWorld) {
+    let key_down_world = world.clone();
+    engine.register_fn(
+        "is_key_down",
+        move |key: &str| -> Result<bool, Box<EvalAltResult>> {
+            let key = parse_key(key)?;
+            Ok(key_down_world.with(|world| world.input_state.is(Input::KeyDown(key))))
+        },
+    );
+    let key_pressed_world = world.clone();
+    engine.register_fn(
+        "is_key_pressed",
+        move |key: &str| -> Result<bool, Box<EvalAltResult>> {
+            let key = parse_key(key)?;
+            Ok(key_pressed_world.with(|world| {
+                world.input_state.is(Input::KeyDown(key))
+                    && !world.input_state.was(Input::KeyDown(key))
+            }))
+        },
+    );
+    let mouse_world = world.clone();
+    engine.register_fn("mouse_position", move || -> Array {
+        let (x, y) = mouse_world.with(|world| world.input_state.mouse_position());
+        vec![
+            Dynamic::from_float(FLOAT::from(x)),
+            Dynamic::from_float(FLOAT::from(y)),
+        ]
+    });
+}
+
+/// Parses a key from its name, such as `"A"` or `"Spacebar"`
+fn parse_key(key: &str) -> Result<Key, Box<EvalAltResult>> {
+    serde_json::from_value(Value::String(key.into()))
+        .map_err(|_| format!("Unknown key {key}").into())
+}
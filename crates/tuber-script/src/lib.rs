@@ -0,0 +1,413 @@
+#![deny(clippy::all)]
+#![warn(clippy::pedantic)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::missing_errors_doc)]
+#![allow(clippy::module_name_repetitions)]
+
+//! Rhai scripting: the entities with a [`Script`] component run the
+//! `update(entity, delta_time)` function of their script every step.
+//!
+//! Scripts are assets of the `script` kind whose `file` metadata gives the
+//! source file. In debug builds, a script is compiled again once its file
+//! changes, through the scene watcher of the engine.
+
+use std::collections::{HashMap, HashSet};
+
+use log::{info, warn};
+use rhai::{CallFnOptions, Dynamic, Engine as RhaiEngine, Scope, AST};
+use serde_derive::{Deserialize, Serialize};
+
+use tuber_core::asset::{read_asset_file, Metadata, Store};
+use tuber_core::registry::Component;
+use tuber_core::scene_watcher::SceneWatcher;
+use tuber_core::vfs::Vfs;
+use tuber_core::{CoreError, CoreResult, DeltaTime};
+use tuber_ecs::ecs::Ecs;
+use tuber_ecs::system::SystemBundle;
+use tuber_ecs::EntityIndex;
+use tuber_engine::engine_context::EngineContext;
+
+use api::{ScriptEntity, SharedWorld};
+
+pub mod api;
+
+const SCRIPT_ASSET_KIND: &str = "script";
+const UPDATE_FUNCTION: &str = "update";
+/// The number of operations a script can run per call before being stopped,
+/// so a script looping forever doesn't freeze the game
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Runs the `update` function of a script asset every step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Component)]
+pub struct Script {
+    /// The identifier of the script asset
+    pub asset: String,
+}
+
+impl Script {
+    #[must_use]
+    pub fn new(asset: &str) -> Self {
+        Self {
+            asset: asset.into(),
+        }
+    }
+}
+
+/// The source code of a script
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptSource(pub String);
+
+pub fn script_loader(metadata: &Metadata, vfs: &dyn Vfs) -> CoreResult<Box<ScriptSource>> {
+    let source = String::from_utf8(read_asset_file(metadata, vfs)?)
+        .map_err(|e| CoreError::AssetFileParseError(e.to_string()))?;
+    Ok(Box::new(ScriptSource(source)))
+}
+
+/// Registers the `script` asset kind and its loader in the store
+pub fn register_script_assets(store: &mut Store) {
+    store.register_asset_kind::<ScriptSource>(SCRIPT_ASSET_KIND);
+    store.register_loader(script_loader);
+}
+
+/// Registers the script assets in the store of the engine and returns the
+/// system running the scripts
+pub fn default_system_bundle(context: &mut EngineContext) -> SystemBundle<EngineContext> {
+    register_script_assets(&mut context.asset_store);
+
+    let mut runtime = ScriptRuntime::default();
+    let mut system_bundle = SystemBundle::default();
+    system_bundle.add_system(move |ecs: &mut Ecs, context: &mut EngineContext| {
+        runtime.update(ecs, context);
+    });
+    system_bundle
+}
+
+struct CompiledScript {
+    /// The source the script was compiled from, it is compiled again once the
+    /// asset is reloaded with a different one
+    source: String,
+    /// Unset if the source doesn't compile or has no `update` function
+    ast: Option<AST>,
+    /// The entities the script failed for, reported once until the script
+    /// is compiled again
+    failed_entities: HashSet<EntityIndex>,
+}
+
+/// The script engine and the compiled scripts
+pub struct ScriptRuntime {
+    engine: RhaiEngine,
+    world: SharedWorld,
+    scripts: HashMap<String, CompiledScript>,
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        let world = SharedWorld::default();
+        let mut engine = RhaiEngine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        api::register(&mut engine, &world);
+        Self {
+            engine,
+            world,
+            scripts: HashMap::new(),
+        }
+    }
+}
+
+impl ScriptRuntime {
+    /// Runs the `update` function of the scripts of the entities, compiling
+    /// the scripts first used or changed since the last step
+    pub fn update(&mut self, ecs: &mut Ecs, context: &mut EngineContext) {
+        let delta_time = match ecs.shared_resource::<DeltaTime>() {
+            Some(delta_time) => delta_time.0,
+            None => return,
+        };
+
+        let scripted_entities: Vec<(EntityIndex, String)> = ecs
+            .query::<(&Script,)>()
+            .map(|(entity, (script,))| (entity, script.asset.clone()))
+            .collect();
+        for (_, asset) in &scripted_entities {
+            self.compile(asset, &mut context.asset_store, &mut context.scene_watcher);
+        }
+
+        let world = self.world.clone();
+        world.lend(ecs, &mut context.input_state, || {
+            for (entity, asset) in &scripted_entities {
+                self.run(*entity, asset, delta_time);
+            }
+        });
+    }
+
+    /// Compiles a script if it hasn't been compiled yet or if its source
+    /// changed. Scripts are loaded from the store and their file is watched
+    /// the first time they are used.
+    fn compile(&mut self, asset: &str, store: &mut Store, scene_watcher: &mut SceneWatcher) {
+        let source = if self.scripts.contains_key(asset) {
+            match store.stored_asset::<ScriptSource>(asset) {
+                Ok(source) if source.0 != self.scripts[asset].source => source,
+                _ => return,
+            }
+        } else {
+            if cfg!(debug_assertions) {
+                watch_script(asset, store, scene_watcher);
+            }
+
+            match store.asset::<ScriptSource>(asset) {
+                Ok(source) => source,
+                Err(e) => {
+                    warn!("Couldn't load script {asset}: {e:?}");
+                    self.scripts.insert(
+                        asset.into(),
+                        CompiledScript {
+                            source: String::new(),
+                            ast: None,
+                            failed_entities: HashSet::new(),
+                        },
+                    );
+                    return;
+                }
+            }
+        };
+
+        info!("Compiling script {asset}");
+        let ast = match self.engine.compile(&source.0) {
+            Ok(ast) if ast.iter_functions().any(|f| f.name == UPDATE_FUNCTION) => Some(ast),
+            Ok(_) => {
+                warn!("Script {asset} has no {UPDATE_FUNCTION} function");
+                None
+            }
+            Err(e) => {
+                warn!("Couldn't compile script {asset}: {e}");
+                None
+            }
+        };
+        self.scripts.insert(
+            asset.into(),
+            CompiledScript {
+                source: source.0.clone(),
+                ast,
+                failed_entities: HashSet::new(),
+            },
+        );
+    }
+
+    fn run(&mut self, entity: EntityIndex, asset: &str, delta_time: f64) {
+        let Some(CompiledScript {
+            ast: Some(ast),
+            failed_entities,
+            ..
+        }) = self.scripts.get_mut(asset)
+        else {
+            return;
+        };
+
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            CallFnOptions::new().eval_ast(false),
+            &mut Scope::new(),
+            ast,
+            UPDATE_FUNCTION,
+            (ScriptEntity(entity), delta_time),
+        );
+        if let Err(e) = result {
+            if failed_entities.insert(entity) {
+                warn!("Script {asset} failed for entity {entity}: {e}");
+            }
+        }
+    }
+}
+
+/// Watches the file of a script, so it is reloaded once it changes
+fn watch_script(asset: &str, store: &Store, scene_watcher: &mut SceneWatcher) {
+    let Ok(metadata) = store.metadata(asset) else {
+        return;
+    };
+    let Some(file) = metadata.metadata.get("file") else {
+        return;
+    };
+
+    let path = metadata.asset_path.join(file);
+    if let Err(e) = scene_watcher.watch_asset(store.vfs(), asset, &path) {
+        warn!("Couldn't watch script {asset}: {e:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use tuber_audio::Audio;
+    use tuber_core::input::keyboard::Key;
+    use tuber_core::input::{Input, Keymap, State as InputState};
+    use tuber_core::inspector::Inspector;
+    use tuber_core::logging::LogCapture;
+    use tuber_core::registry::ComponentRegistry;
+    use tuber_core::transform::Transform;
+    use tuber_core::vfs::OsVfs;
+
+    use super::*;
+
+    const PLAYER_SCRIPT: &str = r#"
+        fn update(entity, delta_time) {
+            if is_key_down("D") {
+                entity.x += 10.0 * delta_time;
+            }
+        }
+    "#;
+
+    fn assets_directory(test_name: &str) -> PathBuf {
+        let directory = std::env::temp_dir()
+            .join(format!("tuber-script-{}", std::process::id()))
+            .join(test_name);
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(directory.join("assets/player")).unwrap();
+        std::fs::write(
+            directory.join("assets/player/asset.json"),
+            r#"{ "identifier": "player", "kind": "script", "metadata": { "file": "player.rhai" } }"#,
+        )
+        .unwrap();
+        std::fs::write(directory.join("assets/player/player.rhai"), PLAYER_SCRIPT).unwrap();
+        directory
+    }
+
+    fn context(directory: &Path) -> EngineContext {
+        let mut asset_store = Store::new(Box::new(OsVfs::new(directory.into())));
+        asset_store.load_assets_metadata().unwrap();
+        EngineContext {
+            graphics: None,
+            asset_store,
+            audio: Audio::default(),
+            input_state: InputState::new(Keymap::default()),
+            component_registry: ComponentRegistry::new(),
+            scene_watcher: SceneWatcher::default(),
+            inspector: Inspector::new(false),
+            log_capture: LogCapture::default(),
+        }
+    }
+
+    fn x(ecs: &Ecs, entity: EntityIndex) -> f32 {
+        ecs.query_one_by_id::<(&Transform,)>(entity)
+            .unwrap()
+            .1
+             .0
+            .translation
+            .x
+    }
+
+    #[test]
+    fn update_moves_entity() {
+        let directory = assets_directory("update_moves_entity");
+        let mut context = context(&directory);
+        let mut system_bundle = default_system_bundle(&mut context);
+        let mut ecs = Ecs::default();
+        ecs.insert_shared_resource(DeltaTime(0.5));
+        let player = ecs.insert((Script::new("player"), Transform::default()));
+
+        system_bundle.step(&mut ecs, &mut context).unwrap();
+        assert!(x(&ecs, player).abs() < f32::EPSILON);
+        context.input_state.handle_input(&Input::KeyDown(Key::D));
+        system_bundle.step(&mut ecs, &mut context).unwrap();
+
+        assert!((x(&ecs, player) - 5.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn create_and_delete_entities() {
+        let directory = assets_directory("create_and_delete_entities");
+        std::fs::write(
+            directory.join("assets/player/player.rhai"),
+            "
+                fn update(entity, delta_time) {
+                    let bullet = create_entity();
+                    bullet.x = entity.x + 1.0;
+                    delete_entity(entity);
+                }
+            ",
+        )
+        .unwrap();
+        let mut context = context(&directory);
+        let mut system_bundle = default_system_bundle(&mut context);
+        let mut ecs = Ecs::default();
+        ecs.insert_shared_resource(DeltaTime(0.5));
+        ecs.insert((Script::new("player"), Transform::default()));
+
+        system_bundle.step(&mut ecs, &mut context).unwrap();
+
+        assert_eq!(ecs.query::<(&Script,)>().count(), 0);
+        let (bullet, _) = ecs.query_one::<(&Transform,)>().unwrap();
+        assert!((x(&ecs, bullet) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn hot_reload() {
+        let directory = assets_directory("hot_reload");
+        let mut context = context(&directory);
+        register_script_assets(&mut context.asset_store);
+        let mut runtime = ScriptRuntime::default();
+        let mut ecs = Ecs::default();
+        ecs.insert_shared_resource(DeltaTime(1.0));
+        let player = ecs.insert((Script::new("player"), Transform::default()));
+        runtime.update(&mut ecs, &mut context);
+
+        std::fs::write(
+            directory.join("assets/player/player.rhai"),
+            "fn update(entity, delta_time) { entity.x = 42.0; }",
+        )
+        .unwrap();
+        context.scene_watcher.poll(
+            context.asset_store.vfs(),
+            &mut ecs,
+            &context.component_registry,
+        );
+        for identifier in context.scene_watcher.drain_changed_assets() {
+            context.asset_store.reload_asset(&identifier).unwrap();
+        }
+        runtime.update(&mut ecs, &mut context);
+
+        assert!((x(&ecs, player) - 42.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn unknown_key_fails_script() {
+        let directory = assets_directory("unknown_key_fails_script");
+        std::fs::write(
+            directory.join("assets/player/player.rhai"),
+            r#"fn update(entity, delta_time) { if is_key_down("Hyper") { entity.x = 1.0; } }"#,
+        )
+        .unwrap();
+        let mut context = context(&directory);
+        let mut system_bundle = default_system_bundle(&mut context);
+        let mut ecs = Ecs::default();
+        ecs.insert_shared_resource(DeltaTime(1.0));
+        let player = ecs.insert((Script::new("player"), Transform::default()));
+
+        system_bundle.step(&mut ecs, &mut context).unwrap();
+
+        assert!(x(&ecs, player).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn endless_script_is_stopped_and_reported_once() {
+        let directory = assets_directory("endless_script_is_stopped_and_reported_once");
+        std::fs::write(
+            directory.join("assets/player/player.rhai"),
+            "fn update(entity, delta_time) { loop { entity.x += 1.0; } }",
+        )
+        .unwrap();
+        let mut context = context(&directory);
+        register_script_assets(&mut context.asset_store);
+        let mut runtime = ScriptRuntime::default();
+        let mut ecs = Ecs::default();
+        ecs.insert_shared_resource(DeltaTime(1.0));
+        let player = ecs.insert((Script::new("player"), Transform::default()));
+
+        runtime.update(&mut ecs, &mut context);
+        runtime.update(&mut ecs, &mut context);
+
+        assert!(x(&ecs, player) > 0.0);
+        assert_eq!(
+            runtime.scripts["player"].failed_entities,
+            HashSet::from([player])
+        );
+    }
+}
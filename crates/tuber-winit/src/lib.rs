@@ -8,21 +8,21 @@
 use std::convert::{TryFrom, TryInto};
 use std::time::Instant;
 
-use log::info;
+use log::{error, info};
 use winit::dpi::{LogicalSize, Size};
 use winit::event::{ElementState, KeyboardInput, MouseButton, VirtualKeyCode};
 use winit::platform::unix::WindowBuilderExtUnix;
 use winit::{
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{Window, WindowBuilder},
 };
 
 use tuber_core::input::keyboard::Key;
 use tuber_core::input::mouse::Button;
 use tuber_core::input::Input;
 use tuber_engine::{Engine, Result as TuberResult, TuberRunner};
-use tuber_graphics::{Graphics, WindowSize};
+use tuber_graphics::{Graphics, GraphicsError, GraphicsSettings, WindowSize};
 
 #[allow(clippy::enum_variant_names)]
 enum TuberWinitError {
@@ -46,31 +46,16 @@ impl TuberRunner for WinitTuberRunner {
 
         let event_loop = EventLoop::new();
 
-        info!(
-            "Creating window with title \"{}\"",
-            engine.application_title()
+        let window = create_window(&engine, &event_loop);
+        let graphics_settings = engine.graphics_settings().clone();
+        engine.set_graphics(create_graphics(&window, graphics_settings)?);
+        let physical_size = window.inner_size();
+        engine.on_scale_factor_changed(
+            window.scale_factor(),
+            physical_size.width,
+            physical_size.height,
         );
 
-        let window_size = WindowSize {
-            width: 800,
-            height: 600,
-        };
-
-        let window = WindowBuilder::new()
-            .with_class(
-                engine.application_title().to_string(),
-                String::from("tuber-application"),
-            )
-            .with_title(engine.application_title())
-            .with_inner_size(Size::new(LogicalSize::new(
-                window_size.width,
-                window_size.height,
-            )))
-            .build(&event_loop)
-            .unwrap();
-
-        engine.set_graphics(Graphics::new(&window, window_size));
-
         info!("Pushing initial game state on the state stack");
         engine.push_initial_state();
 
@@ -114,6 +99,20 @@ impl TuberRunner for WinitTuberRunner {
                 } if window_id == window.id() => {
                     engine.on_window_resized(new_size.width, new_size.height);
                 }
+                Event::WindowEvent {
+                    event:
+                        WindowEvent::ScaleFactorChanged {
+                            scale_factor,
+                            new_inner_size,
+                        },
+                    window_id,
+                } if window_id == window.id() => {
+                    engine.on_scale_factor_changed(
+                        scale_factor,
+                        new_inner_size.width,
+                        new_inner_size.height,
+                    );
+                }
                 Event::MainEventsCleared => {
                     let new_time = Instant::now();
                     let frame_time = new_time.duration_since(current_time).as_secs_f64();
@@ -125,10 +124,8 @@ impl TuberRunner for WinitTuberRunner {
                         return;
                     }
 
-                    while accumulator >= DELTA_TIME {
-                        engine.step(DELTA_TIME);
-                        accumulator -= DELTA_TIME;
-                    }
+                    step_engine(&mut engine, &mut accumulator, DELTA_TIME);
+                    engine.update_audio(frame_time);
 
                     if last_render_time.elapsed().as_secs_f64() >= TIME_BETWEEN_FRAME {
                         window.request_redraw();
@@ -136,7 +133,10 @@ impl TuberRunner for WinitTuberRunner {
                 }
                 Event::RedrawRequested(_) => {
                     let current_render_time = Instant::now();
-                    engine.render();
+                    if let Err(e) = engine.render() {
+                        error!("Rendering failed, exiting: {e:?}");
+                        *control_flow = ControlFlow::Exit;
+                    }
                     last_render_time = current_render_time;
                 }
                 _ => (),
@@ -145,6 +145,49 @@ impl TuberRunner for WinitTuberRunner {
     }
 }
 
+/// Runs the fixed timesteps the accumulated time allows, then tells the engine
+/// how far into the next step the remaining time is
+fn step_engine(engine: &mut Engine, accumulator: &mut f64, delta_time: f64) {
+    while *accumulator >= delta_time {
+        engine.step(delta_time);
+        *accumulator -= delta_time;
+    }
+    engine.set_step_interpolation(*accumulator / delta_time);
+}
+
+fn create_window(engine: &Engine, event_loop: &EventLoop<()>) -> Window {
+    info!(
+        "Creating window with title \"{}\"",
+        engine.application_title()
+    );
+
+    WindowBuilder::new()
+        .with_class(
+            engine.application_title().to_string(),
+            String::from("tuber-application"),
+        )
+        .with_title(engine.application_title())
+        .with_inner_size(Size::new(LogicalSize::new(800, 600)))
+        .build(event_loop)
+        .unwrap()
+}
+
+fn create_graphics(window: &Window, graphics_settings: GraphicsSettings) -> TuberResult<Graphics> {
+    // The surface is sized in physical pixels, which differ from the logical
+    // size of the window on high density displays
+    let physical_size = window.inner_size();
+    let window_size = WindowSize {
+        width: physical_size.width,
+        height: physical_size.height,
+    };
+    Graphics::new(window, window_size, graphics_settings).map_err(|e| {
+        if let GraphicsError::AdapterNotFound = e {
+            error!("No graphics adapter compatible with the window was found");
+        }
+        e.into()
+    })
+}
+
 struct KeyboardInputWrapper(KeyboardInput);
 
 impl TryFrom<KeyboardInputWrapper> for Input {
@@ -5,10 +5,10 @@ use tuber::engine::{Engine, EngineSettings};
 use tuber::WinitTuberRunner;
 
 fn main() -> Result<()> {
-    env_logger::init();
     let engine = Engine::new(EngineSettings {
         application_title: None,
         initial_state: Some(Box::new(MainState)),
+        ..Default::default()
     });
 
     WinitTuberRunner.run(engine)
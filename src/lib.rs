@@ -1,8 +1,12 @@
 #![deny(clippy::all)]
 #![warn(clippy::pedantic)]
 
+pub use tuber_audio as audio;
 pub use tuber_core as core;
 pub use tuber_ecs as ecs;
 pub use tuber_engine as engine;
 pub use tuber_graphics as graphics;
+pub use tuber_network as network;
+#[cfg(feature = "script")]
+pub use tuber_script as script;
 pub use tuber_winit::WinitTuberRunner;